@@ -1,13 +1,243 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::db::file_repository::is_valid_table_name;
+use crate::helper::report_template::ReportTemplate;
+use crate::helper::report_type::REPORT_KINDS;
+use crate::indexing_report_struct::column_mapping::ColumnMapping;
+use crate::money::{CurrencyRule, RoundingMode, MONEY_SCALE};
 use crate::r#type::types::ResponseError;
 
-#[derive(Debug)]
+/// Переменная окружения в которой можно указать путь до toml файла с настройками
+pub const CONFIG_PATH_ENV: &str = "CONFIG_PATH";
+
+#[derive(Debug, Clone)]
 pub struct Settings {
     prod: bool,
+    /// [Config path] Путь до toml файла с настройками, передается аргументом `config=` или через [CONFIG_PATH_ENV]
+    config_path: Option<String>,
+    /// [Host address] Адрес на котором запускается сервер
+    pub host_address: String,
+    /// [Host port] Порт на котором запускается сервер
+    pub host_port: u16,
+    /// [Path report] Папка с csv файлами по которым идет генерация (локально)
+    pub path_report: String,
+    /// [Prod path report] Папка с csv файлами по которым идет генерация (прод)
+    pub prod_path_report: String,
+    /// [Reports dir] Папка в которую сохраняются сгенерированные отчеты (локально)
+    pub reports_dir: String,
+    /// [Prod reports dir] Папка в которую сохраняются сгенерированные отчеты (прод)
+    pub prod_reports_dir: String,
+    /// [Max num of simultaneous generations] Сколько отчетов может генерироваться одновременно
+    pub max_num_of_simultaneous_generations_csv_in_xlsx: u16,
+    /// [Max number of reports in share] Сколько отчетов может храниться в share
+    pub max_number_of_reports_in_share: u16,
+    /// [Max organizations in batch] Сколько организаций может быть в одной пакетной генерации
+    pub max_organizations_in_batch: usize,
+    /// [Local mysql] Подключение к локальной базе данных
+    pub local_mysql: MysqlSettings,
+    /// [Global mysql] Подключение к базе данных на проде
+    pub global_mysql: MysqlSettings,
+    /// [Files table name] Таблица с информацией о csv файлах
+    pub files_table_name: String,
+    /// [Reports table name] Таблица каталога сгенерированных отчетов, создается при старте если её нет
+    pub reports_table_name: String,
+    /// [Used links table name] Таблица использованных одноразовых ссылок, создается при старте если её нет
+    pub used_links_table_name: String,
+    /// [Mysql pool min connections] Минимальное количество соединений которое держит пул
+    pub mysql_pool_min_connections: usize,
+    /// [Mysql pool max connections] Максимальное количество соединений в пуле
+    pub mysql_pool_max_connections: usize,
+    /// [Report currency] Валюта сумм в csv файлах, по ней выбирается правило округления из [Settings::currencies]
+    pub report_currency: String,
+    /// [Currencies] Правила округления сумм по валютам, ключ это код валюты
+    pub currencies: HashMap<String, CurrencyRule>,
+    /// [Report templates dir] Папка с шаблонами xlsx отчетов (agent.json, taxi_company.json, merchant.json).
+    /// Если пусто или файла нет, используется шаблон поставляемый вместе с сервисом
+    pub report_templates_dir: String,
+    /// [Column mapping path] toml файл с названиями колонок csv по типам файлов.
+    /// Если пусто, используются названия поставляемые вместе с сервисом (column_mapping.toml)
+    pub column_mapping_path: String,
+    /// [Download links] Подписанные ссылки на скачивание отчетов без токена
+    pub download_links: DownloadLinkSettings,
+    /// [Retention] Удаление старых файлов отчетов с диска
+    pub retention: RetentionSettings,
+}
+
+/// Ограничения для файлов отчетов, 0 отключает ограничение.
+/// Закрепленные отчеты не удаляются ни по одному из ограничений
+#[derive(Debug, Clone, Default)]
+pub struct RetentionSettings {
+    /// [Interval secs] Как часто проверяются файлы отчетов
+    pub interval_secs: u64,
+    /// [Max age secs] Файлы старше удаляются
+    pub max_age_secs: u64,
+    /// [User quota bytes] Сколько места могут занимать отчеты одного пользователя, при превышении удаляются самые старые
+    pub user_quota_bytes: u64,
+    /// [Disk budget bytes] Сколько места могут занимать все отчеты, при превышении удаляются самые старые
+    pub disk_budget_bytes: u64,
+    /// [Export path] Файл в который дописываются удаленные отчеты по одному json на строку, если пусто то только в лог
+    pub export_path: String,
+}
+
+impl RetentionSettings {
+    /// Включено ли хотя бы одно ограничение
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_secs > 0 || self.user_quota_bytes > 0 || self.disk_budget_bytes > 0
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DownloadLinkSettings {
+    /// [Secret] Ключ подписи ссылок, если пусто то ссылки не выдаются
+    pub secret: String,
+    /// [Base url] Адрес сервиса который подставляется перед /download, если пусто то ссылка относительная
+    pub base_url: String,
+    /// [Ttl secs] Сколько секунд живет ссылка, если в запросе не передан expires_in
+    pub ttl_secs: u64,
+    /// [Max ttl secs] Максимальный expires_in в запросе
+    pub max_ttl_secs: u64,
+}
+
+// Ключ подписи не должен попадать в логи при выводе настроек
+impl fmt::Debug for DownloadLinkSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DownloadLinkSettings")
+            .field("secret", &if self.secret.is_empty() { "" } else { "***" })
+            .field("base_url", &self.base_url)
+            .field("ttl_secs", &self.ttl_secs)
+            .field("max_ttl_secs", &self.max_ttl_secs)
+            .finish()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MysqlSettings {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+// Пароль не должен попадать в логи при выводе настроек
+impl fmt::Debug for MysqlSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MysqlSettings")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("user", &self.user)
+            .field("password", &"***")
+            .field("database", &self.database)
+            .finish()
+    }
+}
+
+/// Настройки из toml файла, все поля не обязательные,
+/// то что не указано в файле берется из значений по умолчанию или переменных окружения
+#[derive(Debug, Default, Deserialize)]
+struct FileSettings {
+    host_address: Option<String>,
+    host_port: Option<u16>,
+    path_report: Option<String>,
+    prod_path_report: Option<String>,
+    reports_dir: Option<String>,
+    prod_reports_dir: Option<String>,
+    max_num_of_simultaneous_generations_csv_in_xlsx: Option<u16>,
+    max_number_of_reports_in_share: Option<u16>,
+    max_organizations_in_batch: Option<usize>,
+    local_mysql: Option<FileMysqlSettings>,
+    global_mysql: Option<FileMysqlSettings>,
+    mysql_pool_min_connections: Option<usize>,
+    mysql_pool_max_connections: Option<usize>,
+    files_table_name: Option<String>,
+    reports_table_name: Option<String>,
+    used_links_table_name: Option<String>,
+    report_currency: Option<String>,
+    currencies: Option<HashMap<String, FileCurrencySettings>>,
+    report_templates_dir: Option<String>,
+    column_mapping_path: Option<String>,
+    download_links: Option<FileDownloadLinkSettings>,
+    retention: Option<FileRetentionSettings>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileRetentionSettings {
+    interval_secs: Option<u64>,
+    max_age_secs: Option<u64>,
+    user_quota_bytes: Option<u64>,
+    disk_budget_bytes: Option<u64>,
+    export_path: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileDownloadLinkSettings {
+    secret: Option<String>,
+    base_url: Option<String>,
+    ttl_secs: Option<u64>,
+    max_ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileCurrencySettings {
+    minor_units: Option<u32>,
+    rounding: Option<RoundingMode>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileMysqlSettings {
+    host: Option<String>,
+    port: Option<u16>,
+    user: Option<String>,
+    password: Option<String>,
+    database: Option<String>,
 }
 
 impl Settings {
     pub fn new() -> Self {
-        Settings { prod: false }
+        Settings {
+            prod: false,
+            config_path: None,
+            host_address: "0.0.0.0".to_string(),
+            host_port: 8080,
+            path_report: "".to_string(),
+            prod_path_report: "".to_string(),
+            reports_dir: "".to_string(),
+            prod_reports_dir: "".to_string(),
+            max_num_of_simultaneous_generations_csv_in_xlsx: 1000,
+            max_number_of_reports_in_share: 1000,
+            max_organizations_in_batch: 100,
+            local_mysql: MysqlSettings { port: 3306, ..Default::default() },
+            global_mysql: MysqlSettings { port: 3306, ..Default::default() },
+            mysql_pool_min_connections: 1,
+            mysql_pool_max_connections: 10,
+            files_table_name: "table_name".to_string(),
+            reports_table_name: "reports_catalog".to_string(),
+            used_links_table_name: "used_download_links".to_string(),
+            report_currency: "UZS".to_string(),
+            currencies: CurrencyRule::defaults()
+                .into_iter()
+                .map(|rule| (rule.code.clone(), rule))
+                .collect(),
+            report_templates_dir: "".to_string(),
+            column_mapping_path: "".to_string(),
+            download_links: DownloadLinkSettings {
+                ttl_secs: 3600,
+                max_ttl_secs: 7 * 24 * 3600,
+                ..Default::default()
+            },
+            retention: RetentionSettings {
+                interval_secs: 3600,
+                max_age_secs: 30 * 24 * 3600,
+                ..Default::default()
+            },
+        }
     }
 
     pub fn set_prod(&mut self, arg: Vec<String>) -> Result<(), ResponseError> {
@@ -23,6 +253,304 @@ impl Settings {
     pub fn get_prod(&self) -> bool {
         self.prod
     }
+
+    /// Загружает настройки: значения по умолчанию -> toml файл -> переменные окружения.
+    /// После загрузки все поля проходят валидацию
+    pub fn load(&mut self) -> Result<(), ResponseError> {
+        let config_path = match self.config_path.clone() {
+            Some(path) => Some(path),
+            None => env::var(CONFIG_PATH_ENV).ok().filter(|path| !path.trim().is_empty()),
+        };
+
+        if let Some(path) = config_path {
+            let file_settings = match fs::read_to_string(&path) {
+                Ok(content) => toml::from_str::<FileSettings>(&content)
+                    .map_err(|error| (5435433, format!("Не удалось прочитать файл настроек {}: {}", path, error))),
+                Err(error) => Err((5435434, format!("Не удалось открыть файл настроек {}: {}", path, error))),
+            };
+
+            if let Err(error) = file_settings {
+                return Err(error);
+            }
+
+            self.apply_file_settings(file_settings.unwrap());
+            self.config_path = Some(path);
+        }
+
+        let mut errors: Vec<String> = Vec::new();
+
+        self.apply_env_settings(&mut errors);
+        self.validate(&mut errors);
+
+        if !errors.is_empty() {
+            return Err((5435435, format!("Некорректные настройки: {}", errors.join("; "))));
+        }
+
+        Ok(())
+    }
+
+    /// Папка с csv файлами с учетом режима prod
+    pub fn get_path_report(&self) -> &str {
+        if self.prod {
+            self.prod_path_report.as_str()
+        } else {
+            self.path_report.as_str()
+        }
+    }
+
+    /// Папка со сгенерированными отчетами с учетом режима prod
+    pub fn get_reports_dir(&self) -> &str {
+        if self.prod {
+            self.prod_reports_dir.as_str()
+        } else {
+            self.reports_dir.as_str()
+        }
+    }
+
+    /// Настройки базы данных с учетом режима prod
+    pub fn get_mysql(&self) -> &MysqlSettings {
+        if self.prod {
+            &self.global_mysql
+        } else {
+            &self.local_mysql
+        }
+    }
+
+    /// Правило округления для валюты отчета, валюта проверяется при загрузке настроек
+    pub fn get_currency_rule(&self) -> &CurrencyRule {
+        &self.currencies[&self.report_currency]
+    }
+
+    fn apply_file_settings(&mut self, file: FileSettings) {
+        fn set<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+
+        set(&mut self.host_address, file.host_address);
+        set(&mut self.host_port, file.host_port);
+        set(&mut self.path_report, file.path_report);
+        set(&mut self.prod_path_report, file.prod_path_report);
+        set(&mut self.reports_dir, file.reports_dir);
+        set(&mut self.prod_reports_dir, file.prod_reports_dir);
+        set(&mut self.max_num_of_simultaneous_generations_csv_in_xlsx, file.max_num_of_simultaneous_generations_csv_in_xlsx);
+        set(&mut self.max_number_of_reports_in_share, file.max_number_of_reports_in_share);
+        set(&mut self.max_organizations_in_batch, file.max_organizations_in_batch);
+        set(&mut self.mysql_pool_min_connections, file.mysql_pool_min_connections);
+        set(&mut self.mysql_pool_max_connections, file.mysql_pool_max_connections);
+        set(&mut self.files_table_name, file.files_table_name);
+        set(&mut self.reports_table_name, file.reports_table_name);
+        set(&mut self.used_links_table_name, file.used_links_table_name);
+        set(&mut self.report_currency, file.report_currency.map(|code| code.to_uppercase()));
+        set(&mut self.report_templates_dir, file.report_templates_dir);
+        set(&mut self.column_mapping_path, file.column_mapping_path);
+
+        if let Some(file_links) = file.download_links {
+            set(&mut self.download_links.secret, file_links.secret);
+            set(&mut self.download_links.base_url, file_links.base_url);
+            set(&mut self.download_links.ttl_secs, file_links.ttl_secs);
+            set(&mut self.download_links.max_ttl_secs, file_links.max_ttl_secs);
+        }
+
+        if let Some(file_retention) = file.retention {
+            set(&mut self.retention.interval_secs, file_retention.interval_secs);
+            set(&mut self.retention.max_age_secs, file_retention.max_age_secs);
+            set(&mut self.retention.user_quota_bytes, file_retention.user_quota_bytes);
+            set(&mut self.retention.disk_budget_bytes, file_retention.disk_budget_bytes);
+            set(&mut self.retention.export_path, file_retention.export_path);
+        }
+
+        // Валюты из файла дополняют или переопределяют валюты по умолчанию
+        for (code, file_currency) in file.currencies.unwrap_or_default() {
+            let code = code.to_uppercase();
+            let rule = self
+                .currencies
+                .entry(code.clone())
+                .or_insert_with(|| CurrencyRule::new(&code, 2, RoundingMode::HalfUp));
+
+            set(&mut rule.minor_units, file_currency.minor_units);
+            set(&mut rule.rounding, file_currency.rounding);
+        }
+
+        for (mysql, file_mysql) in [
+            (&mut self.local_mysql, file.local_mysql),
+            (&mut self.global_mysql, file.global_mysql),
+        ] {
+            if let Some(file_mysql) = file_mysql {
+                set(&mut mysql.host, file_mysql.host);
+                set(&mut mysql.port, file_mysql.port);
+                set(&mut mysql.user, file_mysql.user);
+                set(&mut mysql.password, file_mysql.password);
+                set(&mut mysql.database, file_mysql.database);
+            }
+        }
+    }
+
+    fn apply_env_settings(&mut self, errors: &mut Vec<String>) {
+        env_string("HOST_ADDRESS", &mut self.host_address);
+        env_parse("HOST_PORT", &mut self.host_port, errors);
+        env_string("PATH_REPORT", &mut self.path_report);
+        env_string("PROD_PATH_REPORT", &mut self.prod_path_report);
+        env_string("REPORTS_DIR", &mut self.reports_dir);
+        env_string("PROD_REPORTS_DIR", &mut self.prod_reports_dir);
+        env_parse(
+            "MAX_NUM_OF_SIMULTANEOUS_GENERATIONS_CSV_IN_XLSX",
+            &mut self.max_num_of_simultaneous_generations_csv_in_xlsx,
+            errors,
+        );
+        env_parse("MAX_NUMBER_OF_REPORTS_IN_SHARE", &mut self.max_number_of_reports_in_share, errors);
+        env_parse("MAX_ORGANIZATIONS_IN_BATCH", &mut self.max_organizations_in_batch, errors);
+        env_parse("MYSQL_POOL_MIN_CONNECTIONS", &mut self.mysql_pool_min_connections, errors);
+        env_parse("MYSQL_POOL_MAX_CONNECTIONS", &mut self.mysql_pool_max_connections, errors);
+        env_string("FILES_TABLE_NAME", &mut self.files_table_name);
+        env_string("REPORTS_TABLE_NAME", &mut self.reports_table_name);
+        env_string("USED_LINKS_TABLE_NAME", &mut self.used_links_table_name);
+        env_string("REPORT_CURRENCY", &mut self.report_currency);
+        self.report_currency = self.report_currency.trim().to_uppercase();
+        env_string("REPORT_TEMPLATES_DIR", &mut self.report_templates_dir);
+        env_string("COLUMN_MAPPING_PATH", &mut self.column_mapping_path);
+        env_string("DOWNLOAD_LINK_SECRET", &mut self.download_links.secret);
+        env_string("DOWNLOAD_LINK_BASE_URL", &mut self.download_links.base_url);
+        env_parse("DOWNLOAD_LINK_TTL_SECS", &mut self.download_links.ttl_secs, errors);
+        env_parse("DOWNLOAD_LINK_MAX_TTL_SECS", &mut self.download_links.max_ttl_secs, errors);
+        env_parse("RETENTION_INTERVAL_SECS", &mut self.retention.interval_secs, errors);
+        env_parse("RETENTION_MAX_AGE_SECS", &mut self.retention.max_age_secs, errors);
+        env_parse("RETENTION_USER_QUOTA_BYTES", &mut self.retention.user_quota_bytes, errors);
+        env_parse("RETENTION_DISK_BUDGET_BYTES", &mut self.retention.disk_budget_bytes, errors);
+        env_string("RETENTION_EXPORT_PATH", &mut self.retention.export_path);
+
+        for (prefix, mysql) in [("LOCAL", &mut self.local_mysql), ("GLOBAL", &mut self.global_mysql)] {
+            env_string(&format!("{}_MYSQL_HOST", prefix), &mut mysql.host);
+            env_parse(&format!("{}_MYSQL_PORT", prefix), &mut mysql.port, errors);
+            env_string(&format!("{}_MYSQL_USER", prefix), &mut mysql.user);
+            env_string(&format!("{}_MYSQL_PASSWORD", prefix), &mut mysql.password);
+            env_string(&format!("{}_MYSQL_DATABASE", prefix), &mut mysql.database);
+        }
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.host_address.trim().is_empty() {
+            errors.push("HOST_ADDRESS не может быть пустым".to_string());
+        }
+        if self.host_port == 0 {
+            errors.push("HOST_PORT не может быть 0".to_string());
+        }
+        if self.get_path_report().trim().is_empty() {
+            errors.push(format!("{} не может быть пустым", if self.prod { "PROD_PATH_REPORT" } else { "PATH_REPORT" }));
+        }
+        if self.get_reports_dir().trim().is_empty() {
+            errors.push(format!("{} не может быть пустым", if self.prod { "PROD_REPORTS_DIR" } else { "REPORTS_DIR" }));
+        }
+        if self.max_num_of_simultaneous_generations_csv_in_xlsx == 0 {
+            errors.push("MAX_NUM_OF_SIMULTANEOUS_GENERATIONS_CSV_IN_XLSX должен быть больше 0".to_string());
+        }
+        if self.max_number_of_reports_in_share == 0 {
+            errors.push("MAX_NUMBER_OF_REPORTS_IN_SHARE должен быть больше 0".to_string());
+        }
+        if self.max_organizations_in_batch == 0 {
+            errors.push("MAX_ORGANIZATIONS_IN_BATCH должен быть больше 0".to_string());
+        }
+
+        if self.mysql_pool_max_connections == 0 {
+            errors.push("MYSQL_POOL_MAX_CONNECTIONS должен быть больше 0".to_string());
+        }
+        if self.mysql_pool_min_connections > self.mysql_pool_max_connections {
+            errors.push("MYSQL_POOL_MIN_CONNECTIONS не может быть больше MYSQL_POOL_MAX_CONNECTIONS".to_string());
+        }
+
+        if !is_valid_table_name(&self.files_table_name) {
+            errors.push(format!("FILES_TABLE_NAME имеет не корректное значение: {}", self.files_table_name));
+        }
+        if !is_valid_table_name(&self.reports_table_name) {
+            errors.push(format!("REPORTS_TABLE_NAME имеет не корректное значение: {}", self.reports_table_name));
+        }
+        if !is_valid_table_name(&self.used_links_table_name) {
+            errors.push(format!("USED_LINKS_TABLE_NAME имеет не корректное значение: {}", self.used_links_table_name));
+        }
+
+        if !self.currencies.contains_key(&self.report_currency) {
+            errors.push(format!("Для валюты REPORT_CURRENCY={} не задано правило округления", self.report_currency));
+        }
+        for rule in self.currencies.values() {
+            if rule.minor_units > MONEY_SCALE {
+                errors.push(format!(
+                    "Количество знаков после запятой для валюты {} не может быть больше {}",
+                    rule.code, MONEY_SCALE
+                ));
+            }
+        }
+
+        // Шаблоны проверяем при старте, чтобы ошибка в шаблоне не всплыла только при генерации
+        if !self.report_templates_dir.trim().is_empty() && !Path::new(&self.report_templates_dir).is_dir() {
+            errors.push(format!("REPORT_TEMPLATES_DIR={} не является папкой", self.report_templates_dir));
+        }
+        for kind in REPORT_KINDS.iter() {
+            if let Err((_, message)) = ReportTemplate::load(*kind, &self.report_templates_dir) {
+                errors.push(message);
+            }
+        }
+
+        let links = &self.download_links;
+        if !links.secret.is_empty() && links.secret.len() < 32 {
+            errors.push("DOWNLOAD_LINK_SECRET должен быть не короче 32 символов".to_string());
+        }
+        if links.ttl_secs == 0 {
+            errors.push("DOWNLOAD_LINK_TTL_SECS должен быть больше 0".to_string());
+        }
+        if links.ttl_secs > links.max_ttl_secs {
+            errors.push("DOWNLOAD_LINK_TTL_SECS не может быть больше DOWNLOAD_LINK_MAX_TTL_SECS".to_string());
+        }
+
+        if self.retention.interval_secs == 0 {
+            errors.push("RETENTION_INTERVAL_SECS должен быть больше 0".to_string());
+        }
+
+        // Названия колонок тоже проверяем при старте
+        if let Err((_, message)) = ColumnMapping::load(&self.column_mapping_path) {
+            errors.push(message);
+        }
+
+        // Проверяем только ту базу данных к которой будем подключаться
+        let prefix = if self.prod { "GLOBAL" } else { "LOCAL" };
+        let mysql = self.get_mysql();
+
+        if mysql.host.trim().is_empty() {
+            errors.push(format!("{}_MYSQL_HOST не может быть пустым", prefix));
+        }
+        if mysql.port == 0 {
+            errors.push(format!("{}_MYSQL_PORT не может быть 0", prefix));
+        }
+        if mysql.user.trim().is_empty() {
+            errors.push(format!("{}_MYSQL_USER не может быть пустым", prefix));
+        }
+        if mysql.database.trim().is_empty() {
+            errors.push(format!("{}_MYSQL_DATABASE не может быть пустым", prefix));
+        }
+    }
+}
+
+/// Берем строку из переменной окружения если она задана и не пустая
+fn env_string(key: &str, field: &mut String) {
+    if let Ok(value) = env::var(key) {
+        if !value.trim().is_empty() {
+            *field = value;
+        }
+    }
+}
+
+/// Берем значение из переменной окружения и приводим его к нужному типу
+fn env_parse<T: FromStr>(key: &str, field: &mut T, errors: &mut Vec<String>) {
+    if let Ok(value) = env::var(key) {
+        if value.trim().is_empty() {
+            return;
+        }
+
+        match value.trim().parse::<T>() {
+            Ok(parsed) => *field = parsed,
+            Err(_) => errors.push(format!("{} имеет не корректное значение: {}", key, value)),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -72,6 +600,13 @@ impl ArgReqeust {
                         ));
                     }
                 },
+                "config" => {
+                    if arg.val.trim().is_empty() {
+                        errors.push((1574321, "Путь до файла настроек не может быть пустым".to_string()));
+                    } else {
+                        settings.config_path = Some(arg.val);
+                    }
+                }
                 _ => errors.push((5435432, "Не известный ключ.".to_string())),
             }
         }
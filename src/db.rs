@@ -1 +1,4 @@
 pub mod connect;
+pub mod file_repository;
+pub mod report_catalog;
+pub mod used_links;
@@ -2,120 +2,72 @@ use crate::args::Settings;
 
 use crate::r#type::types::ResponseError;
 
-use actix_web::web::Data;
-
-use dotenv_codegen::dotenv;
-
 use mysql_async::prelude::*;
-use mysql_async::{Conn, Error as MysqlError, Error, Row};
-
-use std::sync::Arc;
+use mysql_async::{Conn, OptsBuilder, Pool, PoolConstraints, PoolOpts};
 
-use tokio::sync::{ Mutex as TokioMutex };
 use tracing::info;
 
+/// Создает пул подключений к базе данных.
+/// Пул сам переподключается: битые соединения выкидываются и заменяются новыми при следующем [get_connection]
 /// code @58602
-pub async fn connect_to_database(settings: &Settings) -> Result<Conn, ResponseError> {
-    let url = if settings.get_prod() {
-        format!(
-            "mysql://{}:{}@{}:{}/{}",
-            dotenv!("GLOBAL_MYSQL_USER"),
-            dotenv!("GLOBAL_MYSQL_PASSWORD"),
-            dotenv!("GLOBAL_MYSQL_HOST"),
-            dotenv!("GLOBAL_MYSQL_PORT"),
-            dotenv!("GLOBAL_MYSQL_DATABASE"),
-        )
-    } else {
-        format!(
-            "mysql://{}:{}@{}:{}/{}",
-            dotenv!("LOCAL_MYSQL_USER"),
-            dotenv!("LOCAL_MYSQL_PASSWORD"),
-            dotenv!("LOCAL_MYSQL_HOST"),
-            dotenv!("LOCAL_MYSQL_PORT"),
-            dotenv!("LOCAL_MYSQL_DATABASE")
-        )
-    };
+pub async fn connect_to_database(settings: &Settings) -> Result<Pool, ResponseError> {
+    let mysql = settings.get_mysql();
 
     info!(
         "\n
-        mysql address: {:?}
+        mysql address: {}:{}/{}
+        pool min: {} max: {}
         ",
-        url
+        mysql.host,
+        mysql.port,
+        mysql.database,
+        settings.mysql_pool_min_connections,
+        settings.mysql_pool_max_connections
     );
 
-    let pool = mysql_async::Pool::new(url.as_str());
-    let conn = pool.get_conn().await;
-
-    if let Err(err) = conn {
-        return Err((4586020, format!("{}", err.to_string())));
-    }
-
-    return Ok(conn.unwrap());
-}
-
-pub async fn get_info_about_files_by_id(
-    ids: Vec<u128>,
-    conn: Data<Arc<TokioMutex<Conn>>>,
-) -> Result<Vec<Row>, ResponseError> {
-    let mut conn_db = conn.lock().await;
-
-    let ids = ids
-        .iter()
-        .map(|id| id.to_string())
-        .collect::<Vec<String>>()
-        .join(", ");
-    let id_s = ids.split(", ").collect::<Vec<&str>>();
+    // Настройки уже прошли валидацию, но на всякий случай не падаем
+    let constraints = match PoolConstraints::new(
+        settings.mysql_pool_min_connections,
+        settings.mysql_pool_max_connections
+    ) {
+        Some(constraints) => constraints,
+        None => return Err((4586021, format!(
+            "Некорректные ограничения пула: min {} max {}",
+            settings.mysql_pool_min_connections,
+            settings.mysql_pool_max_connections
+        ))),
+    };
 
-    let result = format!(
-        "SELECT file_path, `segment`, `id`, `from`, `to`, `user_id` FROM table_name WHERE id IN ({});",
-        id_s.join(", ")
-    )
-        .as_str()
-        .with(())
-        .map(&mut *conn_db, |res: Row| res)
-        .await;
+    let opts = OptsBuilder::default()
+        .ip_or_hostname(mysql.host.clone())
+        .tcp_port(mysql.port)
+        .user(Some(mysql.user.clone()))
+        .pass(Some(mysql.password.clone()))
+        .db_name(Some(mysql.database.clone()))
+        .pool_opts(PoolOpts::default().with_constraints(constraints));
 
-    drop(conn_db);
+    let pool = Pool::new(opts);
 
-    if let Err(error) = check_response(&result, 3424324) {
-        return Err(error);
+    // Проверяем что база доступна уже на старте
+    if let Err(error) = ping_database(&pool).await {
+        return Err((4586020, error.1));
     }
 
-    let result = result
-        .unwrap()
-        .into_iter()
-        .map(|element| element)
-        .collect::<Vec<Row>>();
-
-    Ok(result)
+    Ok(pool)
 }
 
-pub async fn get_last_id_from_table_name(conn: Data<Arc<TokioMutex<Conn>>>) -> Result<Vec<Row>, ResponseError> {
-    let mut conn_db = conn.lock().await;
-
-    let get_max_id = format!(
-        "SELECT MAX(id) from table_name",
-    ).as_str()
-        .with(())
-        .map(&mut *conn_db, |res: Row| res)
-        .await;
-
-    drop(conn_db);
-
-    if let Err(error) = check_response(&get_max_id, 3424325) {
-        return Err(error);
-    }
-
-    return Ok(get_max_id.unwrap());
+/// Берет соединение из пула
+pub async fn get_connection(pool: &Pool) -> Result<Conn, ResponseError> {
+    pool.get_conn()
+        .await
+        .map_err(|error| (4586022, format!("Не удалось получить подключение к базе данных: {}", error)))
 }
 
-pub fn check_response(is_err_response_db: &Result<Vec<Row>, MysqlError>, code: i32) -> Result<(), ResponseError> {
-    if let Err(error) = is_err_response_db {
-        return Err((
-            code,
-            format!("Ошибка базы данных: {}", error.to_string())
-        ));
-    }
+/// Пингует базу данных через соединение из пула
+pub async fn ping_database(pool: &Pool) -> Result<(), ResponseError> {
+    let mut conn = get_connection(pool).await?;
 
-    Ok(())
-}
\ No newline at end of file
+    conn.ping()
+        .await
+        .map_err(|error| (4586023, format!("Не удалось пропинговать базу данных: {}", error)))
+}
@@ -0,0 +1,227 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+use mysql_async::prelude::*;
+use mysql_async::{Params, Pool, Row, Value};
+
+use crate::args::Settings;
+use crate::db::connect::get_connection;
+use crate::r#type::types::ResponseError;
+
+/// Формат даты `от`-`до` который используется в ключе отчета и в имени файла
+pub const FILE_DATE_FORMAT: &str = "%Y-%m-%d %H-%M-%S";
+
+// Порядок колонок в запросе [FileRepository::get_by_ids]
+const FILE_PATH_COLUMN: usize = 0;
+const TYPE_COLUMN: usize = 1;
+const SEGMENT_COLUMN: usize = 2;
+const ID_COLUMN: usize = 3;
+const FROM_COLUMN: usize = 4;
+const TO_COLUMN: usize = 5;
+const USER_ID_COLUMN: usize = 6;
+
+/// [Segment] Состояние csv файла в таблице файлов
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSegment {
+    /// -1 Файл поврежден
+    Corrupted,
+    /// 0 Файл подготавливается к генерации
+    Preparing,
+    /// 1 Файл в процессе генерации
+    Generating,
+    /// 2 Файл готов, по нему можно генерировать отчет
+    Ready,
+}
+
+impl FileSegment {
+    pub fn from_code(code: i64) -> Option<FileSegment> {
+        match code {
+            -1 => Some(FileSegment::Corrupted),
+            0 => Some(FileSegment::Preparing),
+            1 => Some(FileSegment::Generating),
+            2 => Some(FileSegment::Ready),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> i64 {
+        match self {
+            FileSegment::Corrupted => -1,
+            FileSegment::Preparing => 0,
+            FileSegment::Generating => 1,
+            FileSegment::Ready => 2,
+        }
+    }
+
+    /// Можно ли генерировать отчет по файлу в этом состоянии
+    pub fn check_ready(&self) -> Result<(), ResponseError> {
+        match self {
+            FileSegment::Corrupted => Err((534653, "Сегмент файла -1, файла поврежден".to_string())),
+            FileSegment::Preparing => Err((534654, "Сегмент файла 0, файла подготавливается к генерации!".to_string())),
+            FileSegment::Generating => Err((
+                534655,
+                "Сегмент файла 1, файла по которому был сделан запрос на данный момент в процссе генерации!".to_string()
+            )),
+            FileSegment::Ready => Ok(()),
+        }
+    }
+}
+
+/// [File record] Запись о csv файле из таблицы файлов
+#[derive(Debug, Clone)]
+pub struct FileRecord {
+    /// [Id] Id файла
+    pub id: usize,
+    /// [File path] Путь до файла, после [crate::helper::handler_info_about_file_by_id::handle_info_about_file]
+    /// содержит полный путь вместе с папкой из настроек
+    pub file_path: String,
+    /// [File type] Тип файла по которому определяется тип отчета
+    pub file_type: isize,
+    /// [Segment] Состояние файла
+    pub segment: FileSegment,
+    /// [From] Отчет за период `от`
+    pub from: NaiveDateTime,
+    /// [To] Отчет за период `до`
+    pub to: NaiveDateTime,
+    /// [User id] Владелец файла
+    pub user_id: isize,
+}
+
+impl FileRecord {
+    /// Разбирает строку из базы данных.
+    /// Файлы которые еще не готовы к генерации сразу возвращаются как ошибка с кодом их сегмента
+    pub fn from_row(row: &Row) -> Result<FileRecord, ResponseError> {
+        let segment = match row.get_opt::<i64, _>(SEGMENT_COLUMN) {
+            Some(Ok(code)) => match FileSegment::from_code(code) {
+                Some(segment) => segment,
+                None => return Err((534656, "Поучен не известный segmet.".to_string())),
+            },
+            _ => return Err((432424, "Не удалось проучить Сегмент файла".to_string())),
+        };
+
+        if let Err(error) = segment.check_ready() {
+            return Err(error);
+        }
+
+        let file_path = match row.as_ref(FILE_PATH_COLUMN) {
+            Some(Value::Bytes(bytes)) => match String::from_utf8(bytes.clone()) {
+                Ok(file_path) => file_path,
+                Err(error) => return Err((534656, format!("Не удалось байты конвертировать в строку: {}", error))),
+            },
+            Some(Value::NULL) => return Err((
+                534656,
+                "Не удалось получить file name\nВозможно файла по которому вы генерируете отчет не является .csv".to_string()
+            )),
+            _ => return Err((534657, "Не удалочь получить путь к файла".to_string())),
+        };
+
+        let file_type = match row.get_opt::<isize, _>(TYPE_COLUMN) {
+            Some(Ok(file_type)) => file_type,
+            _ => return Err((534658, "Не удалось получить тип файла".to_string())),
+        };
+
+        let id = match row.get_opt::<usize, _>(ID_COLUMN) {
+            Some(Ok(id)) => id,
+            _ => return Err((534659, "Не удалось получить id файла".to_string())),
+        };
+
+        let from = decode_date(row.as_ref(FROM_COLUMN));
+        let to = decode_date(row.as_ref(TO_COLUMN));
+
+        let user_id = match row.get_opt::<isize, _>(USER_ID_COLUMN) {
+            Some(Ok(user_id)) if user_id != -1 => user_id,
+            _ => return Err((534656, "Не удалось получить корректный id".to_string())),
+        };
+
+        Ok(FileRecord {
+            id,
+            file_path,
+            file_type,
+            segment,
+            from: from?,
+            to: to?,
+            user_id,
+        })
+    }
+
+    /// Дата `от` в формате [FILE_DATE_FORMAT]
+    pub fn from_to_string(&self) -> String {
+        self.from.format(FILE_DATE_FORMAT).to_string()
+    }
+
+    /// Дата `до` в формате [FILE_DATE_FORMAT]
+    pub fn to_to_string(&self) -> String {
+        self.to.format(FILE_DATE_FORMAT).to_string()
+    }
+}
+
+/// Работа с таблицей csv файлов, все запросы идут через prepared statements
+pub struct FileRepository<'a> {
+    pool: &'a Pool,
+    table_name: &'a str,
+}
+
+impl<'a> FileRepository<'a> {
+    pub fn new(pool: &'a Pool, settings: &'a Settings) -> FileRepository<'a> {
+        FileRepository {
+            pool,
+            table_name: settings.files_table_name.as_str(),
+        }
+    }
+
+    /// Получает файлы по id, каждая строка разбирается отдельно,
+    /// поэтому ошибка в одном файле не мешает получить остальные
+    pub async fn get_by_ids(&self, ids: &[u128]) -> Result<Vec<Result<FileRecord, ResponseError>>, ResponseError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Имя таблицы нельзя передать параметром, оно проверяется при загрузке настроек
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let query = format!(
+            "SELECT `file_path`, `type`, `segment`, `id`, `from`, `to`, `user_id` FROM `{}` WHERE `id` IN ({})",
+            self.table_name,
+            placeholders
+        );
+        let params = Params::Positional(ids.iter().map(|id| Value::UInt(*id as u64)).collect());
+
+        let mut conn = get_connection(self.pool).await?;
+
+        let rows = conn.exec::<Row, _, _>(query, params)
+            .await
+            .map_err(|error| (3424324, format!("Ошибка базы данных: {}", error)))?;
+
+        Ok(rows.iter().map(FileRecord::from_row).collect())
+    }
+
+    /// Последний id в таблице файлов, [None] если таблица пустая
+    pub async fn get_last_id(&self) -> Result<Option<u64>, ResponseError> {
+        let query = format!("SELECT MAX(`id`) FROM `{}`", self.table_name);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        let last_id = conn.exec_first::<Option<u64>, _, _>(query, ())
+            .await
+            .map_err(|error| (3424325, format!("Ошибка базы данных: {}", error)))?;
+
+        Ok(last_id.flatten())
+    }
+}
+
+/// Имя таблицы подставляется в запрос напрямую, поэтому разрешаем только буквы, цифры и `_`
+pub fn is_valid_table_name(table_name: &str) -> bool {
+    !table_name.is_empty() && table_name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+pub fn decode_date(value: Option<&Value>) -> Result<NaiveDateTime, ResponseError> {
+    let error = (235434, "Не удалось получить дату `от`-`до`".to_string());
+
+    match value {
+        // year, month, day, hour, minutes, seconds, micro seconds
+        Some(Value::Date(year, month, day, hour, minutes, seconds, micro_seconds)) => {
+            NaiveDate::from_ymd_opt(*year as i32, *month as u32, *day as u32)
+                .and_then(|date| date.and_hms_micro_opt(*hour as u32, *minutes as u32, *seconds as u32, *micro_seconds))
+                .ok_or(error)
+        }
+        _ => Err(error),
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+use mysql_async::prelude::*;
+use mysql_async::{Params, Pool, Row, Value};
+
+use serde::{Deserialize, Serialize};
+
+use sodiumoxide::crypto::hash::sha256;
+
+use crate::args::Settings;
+use crate::db::connect::get_connection;
+use crate::db::file_repository::decode_date;
+use crate::helper::date::day_of_date;
+use crate::r#type::types::ResponseError;
+
+/// Формат периода отчета в каталоге
+pub const PERIOD_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Сколько отчетов на странице [GET /reports] по умолчанию и максимум
+pub const DEFAULT_PER_PAGE: u32 = 20;
+pub const MAX_PER_PAGE: u32 = 100;
+
+// Порядок колонок в запросе [ReportCatalog::list]
+const ID_COLUMN: usize = 0;
+const KEY_COLUMN: usize = 1;
+const FILE_NAME_COLUMN: usize = 2;
+const USER_ID_COLUMN: usize = 3;
+const REPORT_TYPE_COLUMN: usize = 4;
+const OUTPUT_FORMAT_COLUMN: usize = 5;
+const ORGANIZATION_COLUMN: usize = 6;
+const FILE_IDS_COLUMN: usize = 7;
+const FILTERS_COLUMN: usize = 8;
+const PERIOD_FROM_COLUMN: usize = 9;
+const PERIOD_TO_COLUMN: usize = 10;
+const CREATED_AT_COLUMN: usize = 11;
+const SIZE_COLUMN: usize = 12;
+const CHECKSUM_COLUMN: usize = 13;
+const PINNED_COLUMN: usize = 14;
+
+const COLUMNS: &str = "`id`, `report_key`, `file_name`, `user_id`, `report_type`, `output_format`, `organization`, \
+    `file_ids`, `filters`, `period_from`, `period_to`, `created_at`, `size`, `checksum`, `pinned`";
+
+/// [Catalog entry] Запись каталога о сгенерированном файле отчета
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CatalogEntry {
+    /// [Id] Id записи, при добавлении не используется
+    pub id: u64,
+    /// [Key] Ключ отчета, тот же что в задаче генерации
+    pub key: String,
+    /// [File name] Имя файла с расширением, по нему отчет скачивается через /download/{file_name}
+    pub file_name: String,
+    /// [User id] Владелец отчета
+    pub user_id: String,
+    /// [Report type] Вид отчета, к примеру Agent
+    pub report_type: String,
+    /// [Output format] xlsx, csv или pdf
+    pub output_format: String,
+    /// [Organization] Провайдер или мерчант, у пакетной генерации "batch:<id>,<id>" или "batch:all"
+    pub organization: String,
+    /// [File ids] Id csv файлов по которым сгенерирован отчет
+    pub file_ids: Vec<u32>,
+    /// [Filters] Фильтры запроса генерации
+    pub filters: serde_json::Value,
+    /// [Period] Отчет за период `от`-`до` в формате [PERIOD_FORMAT]
+    pub period_from: String,
+    pub period_to: String,
+    /// [Created at] Unix время генерации
+    pub created_at: i64,
+    /// [Size] Размер файла в байтах
+    pub size: u64,
+    /// [Checksum] sha256 файла в hex
+    pub checksum: String,
+    /// [Pinned] Закрепленный отчет не удаляется при очистке диска
+    pub pinned: bool,
+}
+
+/// Параметры [GET /reports]: страница и фильтры
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogQuery {
+    /// Номер страницы с 1
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub report_type: Option<String>,
+    pub organization: Option<String>,
+    pub output_format: Option<String>,
+    /// Отчеты период которых пересекается с `from`-`to`, даты в формате YYYY-MM-DD
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Страница каталога для ответа пользователю
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogPage {
+    pub items: Vec<CatalogEntry>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+}
+
+impl CatalogQuery {
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1)
+    }
+
+    pub fn per_page(&self) -> u32 {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE)
+    }
+
+    /// code @2217001
+    pub fn check(&self) -> Result<(), ResponseError> {
+        if self.page() == 0 {
+            return Err((2217001, "page начинается с 1".to_string()));
+        }
+
+        if self.per_page() == 0 || self.per_page() > MAX_PER_PAGE {
+            return Err((2217002, format!("per_page должен быть от 1 до {}", MAX_PER_PAGE)));
+        }
+
+        for date in self.from.iter().chain(self.to.iter()) {
+            if day_of_date(date).is_none() {
+                return Err((2217003, format!("Дата {} должна быть в формате YYYY-MM-DD", date)));
+            }
+        }
+
+        if let (Some(from), Some(to)) = (self.from.as_deref().and_then(day_of_date), self.to.as_deref().and_then(day_of_date)) {
+            if from > to {
+                return Err((2217004, "from не может быть позже to".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Условие WHERE без владельца и параметры к нему
+    fn conditions(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        if let Some(report_type) = self.report_type.as_ref() {
+            conditions.push(" AND `report_type` = ?");
+            params.push(Value::from(report_type.trim()));
+        }
+
+        if let Some(organization) = self.organization.as_ref() {
+            conditions.push(" AND `organization` = ?");
+            params.push(Value::from(organization.trim()));
+        }
+
+        if let Some(output_format) = self.output_format.as_ref() {
+            conditions.push(" AND `output_format` = ?");
+            params.push(Value::from(output_format.trim().to_lowercase()));
+        }
+
+        // Даты уже проверены в [CatalogQuery::check], период отчета должен пересекаться с `from`-`to`
+        if let Some(from) = self.from.as_deref().and_then(day_of_date) {
+            conditions.push(" AND `period_to` >= ?");
+            params.push(Value::from(format!("{} 00:00:00", from.format("%Y-%m-%d"))));
+        }
+
+        if let Some(to) = self.to.as_deref().and_then(day_of_date) {
+            conditions.push(" AND `period_from` <= ?");
+            params.push(Value::from(format!("{} 23:59:59", to.format("%Y-%m-%d"))));
+        }
+
+        (conditions.concat(), params)
+    }
+}
+
+impl CatalogEntry {
+    /// Заполняет имя, размер и sha256 файла отчета по пути [path].
+    /// Файл читается целиком, поэтому хэш считается в отдельном потоке и не занимает поток рантайма
+    pub async fn set_file(&mut self, path: &str) -> Result<(), ResponseError> {
+        let path = Path::new(path).to_path_buf();
+
+        self.file_name = match path.file_name() {
+            Some(file_name) => file_name.to_string_lossy().to_string(),
+            None => return Err((2217010, format!("Не корректный путь до отчета: {}", path.display()))),
+        };
+
+        let (size, checksum) = tokio::task::spawn_blocking(move || file_checksum(&path))
+            .await
+            .map_err(|error| (2217012, format!("Ошибка при обработке задачи: {:?}", error)))??;
+
+        self.size = size;
+        self.checksum = checksum;
+
+        Ok(())
+    }
+
+    pub fn from_row(row: &Row) -> Result<CatalogEntry, ResponseError> {
+        let error = |column: &str| (2217020, format!("Не удалось получить {} из каталога отчетов", column));
+
+        let file_ids = row.get_opt::<String, _>(FILE_IDS_COLUMN)
+            .and_then(|value| value.ok())
+            .and_then(|value| serde_json::from_str::<Vec<u32>>(&value).ok())
+            .ok_or_else(|| error("file_ids"))?;
+
+        let filters = row.get_opt::<String, _>(FILTERS_COLUMN)
+            .and_then(|value| value.ok())
+            .and_then(|value| serde_json::from_str::<serde_json::Value>(&value).ok())
+            .ok_or_else(|| error("filters"))?;
+
+        Ok(CatalogEntry {
+            id: row.get_opt(ID_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("id"))?,
+            key: row.get_opt(KEY_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("report_key"))?,
+            file_name: row.get_opt(FILE_NAME_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("file_name"))?,
+            user_id: row.get_opt(USER_ID_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("user_id"))?,
+            report_type: row.get_opt(REPORT_TYPE_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("report_type"))?,
+            output_format: row.get_opt(OUTPUT_FORMAT_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("output_format"))?,
+            organization: row.get_opt(ORGANIZATION_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("organization"))?,
+            file_ids,
+            filters,
+            period_from: decode_date(row.as_ref(PERIOD_FROM_COLUMN))?.format(PERIOD_FORMAT).to_string(),
+            period_to: decode_date(row.as_ref(PERIOD_TO_COLUMN))?.format(PERIOD_FORMAT).to_string(),
+            created_at: row.get_opt(CREATED_AT_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("created_at"))?,
+            size: row.get_opt(SIZE_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("size"))?,
+            checksum: row.get_opt(CHECKSUM_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("checksum"))?,
+            pinned: row.get_opt(PINNED_COLUMN).and_then(|value| value.ok()).ok_or_else(|| error("pinned"))?,
+        })
+    }
+}
+
+/// Размер и sha256 в hex файла [path]
+fn file_checksum(path: &Path) -> Result<(u64, String), ResponseError> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|error| (2217011, format!("Не удалось открыть отчет {}: {}", path.display(), error)))?;
+
+    let mut state = sha256::State::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut size = 0;
+
+    loop {
+        let read = file.read(&mut buffer)
+            .map_err(|error| (2217011, format!("Не удалось прочитать отчет {}: {}", path.display(), error)))?;
+
+        if read == 0 {
+            break;
+        }
+
+        state.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    let checksum = state.finalize().0.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().concat();
+
+    Ok((size, checksum))
+}
+
+/// Каталог сгенерированных отчетов. В отличие от share переживает перезапуск сервиса,
+/// по нему пользователь видит свои прошлые отчеты и скачивает их заново
+pub struct ReportCatalog<'a> {
+    pool: &'a Pool,
+    table_name: &'a str,
+}
+
+impl<'a> ReportCatalog<'a> {
+    pub fn new(pool: &'a Pool, settings: &'a Settings) -> ReportCatalog<'a> {
+        ReportCatalog {
+            pool,
+            table_name: settings.reports_table_name.as_str(),
+        }
+    }
+
+    /// Создает таблицу каталога если её еще нет, вызывается при старте
+    pub async fn create_table(&self) -> Result<(), ResponseError> {
+        // Имя таблицы нельзя передать параметром, оно проверяется при загрузке настроек
+        let query = format!(
+            "CREATE TABLE IF NOT EXISTS `{}` (
+                `id` BIGINT UNSIGNED NOT NULL AUTO_INCREMENT,
+                `report_key` VARCHAR(64) NOT NULL,
+                `file_name` VARCHAR(128) NOT NULL,
+                `user_id` VARCHAR(64) NOT NULL,
+                `report_type` VARCHAR(64) NOT NULL,
+                `output_format` VARCHAR(8) NOT NULL,
+                `organization` VARCHAR(1024) NOT NULL,
+                `file_ids` TEXT NOT NULL,
+                `filters` TEXT NOT NULL,
+                `period_from` DATETIME NOT NULL,
+                `period_to` DATETIME NOT NULL,
+                `created_at` BIGINT NOT NULL,
+                `size` BIGINT UNSIGNED NOT NULL,
+                `checksum` CHAR(64) NOT NULL,
+                `pinned` TINYINT(1) NOT NULL DEFAULT 0,
+                PRIMARY KEY (`id`),
+                UNIQUE KEY `user_file` (`user_id`, `file_name`),
+                KEY `user_created` (`user_id`, `created_at`)
+            ) DEFAULT CHARSET = utf8mb4",
+            self.table_name
+        );
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.query_drop(query)
+            .await
+            .map_err(|error| (2217030, format!("Не удалось создать таблицу каталога отчетов: {}", error)))
+    }
+
+    /// Добавляет отчет в каталог. Если файл с тем же именем у пользователя уже есть,
+    /// значит отчет сгенерирован заново и запись обновляется, закрепление при этом сохраняется
+    pub async fn upsert(&self, entry: &CatalogEntry) -> Result<(), ResponseError> {
+        let query = format!(
+            "INSERT INTO `{}` ({}) VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0)
+            ON DUPLICATE KEY UPDATE `report_key` = VALUES(`report_key`), `report_type` = VALUES(`report_type`),
+                `output_format` = VALUES(`output_format`), `organization` = VALUES(`organization`),
+                `file_ids` = VALUES(`file_ids`), `filters` = VALUES(`filters`), `period_from` = VALUES(`period_from`),
+                `period_to` = VALUES(`period_to`), `created_at` = VALUES(`created_at`), `size` = VALUES(`size`),
+                `checksum` = VALUES(`checksum`)",
+            self.table_name,
+            COLUMNS
+        );
+
+        let params = Params::Positional(vec![
+            Value::from(entry.key.as_str()),
+            Value::from(entry.file_name.as_str()),
+            Value::from(entry.user_id.as_str()),
+            Value::from(entry.report_type.as_str()),
+            Value::from(entry.output_format.as_str()),
+            Value::from(entry.organization.as_str()),
+            Value::from(serde_json::to_string(&entry.file_ids).unwrap_or("[]".to_string())),
+            Value::from(entry.filters.to_string()),
+            Value::from(entry.period_from.as_str()),
+            Value::from(entry.period_to.as_str()),
+            Value::Int(entry.created_at),
+            Value::UInt(entry.size),
+            Value::from(entry.checksum.as_str()),
+        ]);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.exec_drop(query, params)
+            .await
+            .map_err(|error| (2217031, format!("Ошибка базы данных: {}", error)))
+    }
+
+    /// Страница отчетов пользователя [user_id], новые первыми
+    pub async fn list(&self, user_id: &str, catalog_query: &CatalogQuery) -> Result<CatalogPage, ResponseError> {
+        let (conditions, mut params) = catalog_query.conditions();
+        params.insert(0, Value::from(user_id));
+
+        let mut conn = get_connection(self.pool).await?;
+
+        let count_query = format!("SELECT COUNT(*) FROM `{}` WHERE `user_id` = ?{}", self.table_name, conditions);
+
+        let total = conn.exec_first::<u64, _, _>(count_query, Params::Positional(params.clone()))
+            .await
+            .map_err(|error| (2217032, format!("Ошибка базы данных: {}", error)))?
+            .unwrap_or(0);
+
+        let page = catalog_query.page();
+        let per_page = catalog_query.per_page();
+
+        let query = format!(
+            "SELECT {} FROM `{}` WHERE `user_id` = ?{} ORDER BY `created_at` DESC, `id` DESC LIMIT ? OFFSET ?",
+            COLUMNS,
+            self.table_name,
+            conditions
+        );
+        params.push(Value::UInt(per_page as u64));
+        params.push(Value::UInt((page as u64 - 1) * per_page as u64));
+
+        let rows = conn.exec::<Row, _, _>(query, Params::Positional(params))
+            .await
+            .map_err(|error| (2217033, format!("Ошибка базы данных: {}", error)))?;
+
+        let items = rows.iter().map(CatalogEntry::from_row).collect::<Result<Vec<CatalogEntry>, ResponseError>>()?;
+
+        Ok(CatalogPage {
+            items,
+            page,
+            per_page,
+            total,
+        })
+    }
+
+    /// Закрепляет или открепляет отчет [file_name] пользователя [user_id].
+    /// false если такого отчета нет в каталоге
+    pub async fn set_pinned(&self, user_id: &str, file_name: &str, pinned: bool) -> Result<bool, ResponseError> {
+        let query = format!("UPDATE `{}` SET `pinned` = ? WHERE `user_id` = ? AND `file_name` = ?", self.table_name);
+        let params = Params::Positional(vec![Value::from(pinned), Value::from(user_id), Value::from(file_name)]);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.exec_drop(query, params)
+            .await
+            .map_err(|error| (2217034, format!("Ошибка базы данных: {}", error)))?;
+
+        // affected_rows не учитывает строки в которых значение не поменялось, поэтому проверяем наличие отдельно
+        let exists_query = format!("SELECT COUNT(*) FROM `{}` WHERE `user_id` = ? AND `file_name` = ?", self.table_name);
+
+        let count = conn.exec_first::<u64, _, _>(exists_query, (user_id, file_name))
+            .await
+            .map_err(|error| (2217034, format!("Ошибка базы данных: {}", error)))?
+            .unwrap_or(0);
+
+        Ok(count > 0)
+    }
+
+    /// Закрепленные отчеты всех пользователей: id пользователя и имя файла
+    pub async fn pinned_files(&self) -> Result<HashSet<(String, String)>, ResponseError> {
+        let query = format!("SELECT `user_id`, `file_name` FROM `{}` WHERE `pinned` = 1", self.table_name);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        let rows = conn.exec::<(String, String), _, _>(query, ())
+            .await
+            .map_err(|error| (2217035, format!("Ошибка базы данных: {}", error)))?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Удаляет запись об отчете, файл которого удален с диска
+    pub async fn remove(&self, user_id: &str, file_name: &str) -> Result<(), ResponseError> {
+        let query = format!("DELETE FROM `{}` WHERE `user_id` = ? AND `file_name` = ?", self.table_name);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.exec_drop(query, (user_id, file_name))
+            .await
+            .map_err(|error| (2217036, format!("Ошибка базы данных: {}", error)))
+    }
+}
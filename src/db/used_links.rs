@@ -0,0 +1,69 @@
+use mysql_async::prelude::*;
+use mysql_async::Pool;
+
+use crate::args::Settings;
+use crate::db::connect::get_connection;
+use crate::r#type::types::ResponseError;
+
+/// Использованные одноразовые ссылки на скачивание. Хранятся в базе, чтобы после перезапуска сервиса
+/// ссылку нельзя было использовать снова. Запись нужна только до истечения срока ссылки
+pub struct UsedLinks<'a> {
+    pool: &'a Pool,
+    table_name: &'a str,
+}
+
+impl<'a> UsedLinks<'a> {
+    pub fn new(pool: &'a Pool, settings: &'a Settings) -> UsedLinks<'a> {
+        UsedLinks {
+            pool,
+            table_name: settings.used_links_table_name.as_str(),
+        }
+    }
+
+    /// Создает таблицу если её еще нет, вызывается при старте
+    pub async fn create_table(&self) -> Result<(), ResponseError> {
+        // Имя таблицы нельзя передать параметром, оно проверяется при загрузке настроек
+        let query = format!(
+            "CREATE TABLE IF NOT EXISTS `{}` (
+                `nonce` CHAR(32) NOT NULL,
+                `expires_at` BIGINT NOT NULL,
+                PRIMARY KEY (`nonce`),
+                KEY `expires_at` (`expires_at`)
+            ) DEFAULT CHARSET = utf8mb4",
+            self.table_name
+        );
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.query_drop(query)
+            .await
+            .map_err(|error| (6651210, format!("Не удалось создать таблицу одноразовых ссылок: {}", error)))
+    }
+
+    /// Отмечает одноразовую ссылку [nonce] использованной, false если она уже была использована.
+    /// Проверка и отметка делаются одним запросом, поэтому два одновременных скачивания ссылку не используют дважды
+    pub async fn use_once(&self, nonce: &str, expires_at: i64) -> Result<bool, ResponseError> {
+        let query = format!("INSERT IGNORE INTO `{}` (`nonce`, `expires_at`) VALUES (?, ?)", self.table_name);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.exec_drop(query, (nonce, expires_at))
+            .await
+            .map_err(|error| (6651211, format!("Ошибка базы данных: {}", error)))?;
+
+        Ok(conn.affected_rows() > 0)
+    }
+
+    /// Удаляет ссылки срок которых закончился к [now], возвращает сколько удалено
+    pub async fn remove_expired(&self, now: i64) -> Result<u64, ResponseError> {
+        let query = format!("DELETE FROM `{}` WHERE `expires_at` < ?", self.table_name);
+
+        let mut conn = get_connection(self.pool).await?;
+
+        conn.exec_drop(query, (now,))
+            .await
+            .map_err(|error| (6651212, format!("Ошибка базы данных: {}", error)))?;
+
+        Ok(conn.affected_rows())
+    }
+}
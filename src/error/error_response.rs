@@ -21,6 +21,10 @@ pub enum CustomError {
     NotFoundFile,
     #[error("The authorization token is invalid")]
     Unauthorized,
+    #[error("The encryption key must be 32 bytes in hex")]
+    InvalidEncryptionKey,
+    #[error("The download link has expired or was already used")]
+    LinkExpired,
     #[error("Unknown Internal Error")]
     Unknown,
 }
@@ -32,6 +36,8 @@ impl CustomError {
             Self::Forbidden => "Forbidden".to_string(),
             Self::NotFoundFile => "NotFoundFile".to_string(),
             Self::Unauthorized => "Unauthorized".to_string(),
+            Self::InvalidEncryptionKey => "InvalidEncryptionKey".to_string(),
+            Self::LinkExpired => "LinkExpired".to_string(),
             Self::Unknown => "Unknown".to_string(),
         }
     }
@@ -43,6 +49,8 @@ impl ResponseError for CustomError {
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::NotFoundFile => StatusCode::NOT_FOUND,
             Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::InvalidEncryptionKey => StatusCode::BAD_REQUEST,
+            Self::LinkExpired => StatusCode::GONE,
             Self::Forbidden => StatusCode::FORBIDDEN,
             Self::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -1,5 +1,5 @@
 pub mod err_utils {
-    use crate::r#type::types::{ChunksInReport, ResponseError};
+    use crate::r#type::types::ResponseError;
 
     /// Проверка на ошибки messages и codes
     pub fn is_check_on_errors_message_and_code(errors: &Vec<ResponseError>) -> bool {
@@ -20,8 +20,8 @@ pub mod err_utils {
         return errors.first().unwrap().clone();
     }
 
-    pub fn chunk_is_empty(unwrap_chunks: &ChunksInReport, id: u32) -> Result<(), ResponseError> {
-        if unwrap_chunks.is_empty() {
+    pub fn chunk_is_empty(number_of_chunks: usize, id: u32) -> Result<(), ResponseError> {
+        if number_of_chunks == 0 {
             let message = format!("Файл под id {} не содержит в себе нужных вам данных", id);
             return Err((4334304, message));
         } else {
@@ -12,23 +12,31 @@ pub mod cryptography {
         id: String,
         status: String,
         mode: String,
-        payments_system: String
+        payments_system: String,
+        output_format: &str,
+        expressions: String,
     ) -> Digest {
         let concat_date = from_to.iter().map(|(from, to)| format!(
             "{}_{}", from.clone(), to.clone())
         ).collect::<Vec<String>>().join("_");
 
-        let concatenation_report_info = format!(
-            "{}_{}_{}_{}_{}_{}_{}",
+        let mut concatenation_report_info = format!(
+            "{}_{}_{}_{}_{}_{}_{}_{}",
             organization_name,
             report_type.report_type_to_string(),
             concat_date,
             id,
             status,
             mode,
-            payments_system
+            payments_system,
+            output_format
         );
 
+        // Выражения добавляются только если они есть, чтобы ключи отчетов без выражений не поменялись
+        if !expressions.is_empty() {
+            concatenation_report_info.push_str(&format!("_{}", expressions));
+        }
+
         md5::compute(concatenation_report_info.as_bytes())
     }
 
@@ -0,0 +1,91 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpRequest, Responder};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::api_server::api_requests::RpcRequest;
+use crate::api_server::response_handlers::resp_user::handlers_user::handler_user_info;
+use crate::args::Settings;
+use crate::helper::download_link::{DownloadLinkView, SignedLink};
+use crate::helper::file_struct::FilePath;
+use crate::helper::user_info::user::UserInfo;
+use crate::r#trait::automated_report_response::Response;
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadLinkRequest {
+    /// Id отчета, как в /download/{path}
+    pub path: String,
+    /// Сколько секунд действует ссылка, по умолчанию DOWNLOAD_LINK_TTL_SECS
+    pub expires_in: Option<u64>,
+    /// Одноразовая ссылка, по ней можно скачать отчет только один раз
+    #[serde(default)]
+    pub single_use: bool,
+}
+
+/// [Post Request] Выдать подписанную ссылку на скачивание отчета пользователя.
+/// По ссылке отчет скачивается без токена, пока не истек срок
+pub async fn create_download_link(
+    req: HttpRequest,
+    body: web::Json<DownloadLinkRequest>,
+    settings: Data<Settings>,
+) -> impl Responder {
+    let links = &settings.download_links;
+
+    if links.secret.is_empty() {
+        return Json(Response::new::<DownloadLinkView>(
+            Some((6651201, "Подписанные ссылки не настроены: не задан DOWNLOAD_LINK_SECRET".to_string())),
+            None,
+            None
+        ));
+    }
+
+    let token = req.headers().get("token");
+
+    if let None = token {
+        return Json(Response::new::<DownloadLinkView>(
+            Some((6651202, "Токен не был получен".to_string())),
+            None,
+            None
+        ));
+    }
+
+    let user_info = handler_user_info(RpcRequest::get_userinfo_by_token(token).await);
+
+    let user_id = match user_info {
+        Ok(user) => UserInfo::get_pub_fields(&user.id),
+        Err(error) => return Json(Response::new::<DownloadLinkView>(Some(error), None, None)),
+    };
+
+    let expires_in = body.expires_in.unwrap_or(links.ttl_secs);
+
+    if expires_in == 0 || expires_in > links.max_ttl_secs {
+        return Json(Response::new::<DownloadLinkView>(
+            Some((6651203, format!("expires_in должен быть от 1 до {} секунд", links.max_ttl_secs))),
+            None,
+            None
+        ));
+    }
+
+    // Ссылка выдается только на существующий отчет из папки пользователя и подписывается на имя файла
+    let path = FilePath { path: body.path.clone() };
+
+    let report_file = match path.resolve(&settings, &user_id) {
+        Ok(report_file) => report_file,
+        Err(error) => {
+            return Json(Response::new::<DownloadLinkView>(
+                Some((6651204, format!("Отчет {} не найден: {}", body.path, error))),
+                None,
+                None
+            ));
+        }
+    };
+
+    let expires_at = Utc::now().timestamp() + expires_in as i64;
+    let link = SignedLink::new(links, &user_id, &report_file.file_name, expires_at, body.single_use);
+
+    Json(Response::new(
+        None,
+        Some(link.view(links, &report_file.file_name)),
+        Some("link")
+    ))
+}
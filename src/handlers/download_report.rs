@@ -1,154 +1,356 @@
-use std::path::Path;
+use std::io::{Seek, SeekFrom};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use actix_web::web::Data;
-use actix_web::{http::header, web, Error, HttpResponse, HttpRequest, ResponseError};
-use actix_web::body::BoxBody;
-use actix_web::http::StatusCode;
+use actix_web::http::header::{
+    Charset, ContentDisposition, ContentRange, ContentRangeSpec, DispositionParam, DispositionType, ETag, ExtendedValue,
+    Header, HttpDate, IfRange, LastModified, Range,
+};
+use actix_web::http::Method;
+use actix_web::{http::header, web, Error, HttpResponse, HttpRequest};
 
-use dotenv_codegen::dotenv;
 use pin_project::pin_project;
 
 use crate::args::Settings;
-use crate::download_report_chunks::DownloadReportChunks;
 use crate::error::error_response::{map_io_error, CustomError};
-use crate::r#trait::chunks_trait::IDownloadReportChunks;
-use sodiumoxide::crypto::secretstream::{self, Pull, Push, Stream, Tag};
+use sodiumoxide::crypto::secretstream::{self, Key, Push, Stream, Tag};
 use tokio::io::ReadBuf;
 use tokio::{fs::File, io::AsyncRead};
 use tokio_stream::Stream as TKStream;
-use tracing::info;
+use tracing::{error, info};
 use crate::api_server::api_requests::RpcRequest;
 use crate::api_server::response_handlers::resp_user::handlers_user::handler_user_info;
 
-use crate::helper::file_struct::FilePath;
+use crate::db::used_links::UsedLinks;
+use crate::helper::download_link::SignedLink;
+use crate::helper::file_struct::{FilePath, ReportFile};
 use crate::helper::get_token_from_header;
+use crate::helper::hex::{from_hex, to_hex};
 use crate::helper::user_info::user::UserInfo;
+use crate::r#type::types::DatabasePool;
 
+/// Заголовок запроса с ключом шифрования клиента: 32 байта в hex
+pub const ENCRYPTION_KEY_HEADER: &str = "encryption-key";
+/// Заголовок ответа с заголовком потока secretstream в hex, без него клиент не расшифрует файл
+pub const ENCRYPTION_HEADER: &str = "encryption-header";
+/// Заголовок ответа с размером чанка до шифрования
+pub const ENCRYPTION_CHUNK_SIZE_HEADER: &str = "encryption-chunk-size";
+
+/// Размер чанка при отдаче файла. При шифровании каждый чанк шифруется отдельно
+/// и становится больше на [secretstream::ABYTES], последний чанк может быть меньше
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// [Streamer] Отдает файл отчета по чанкам, ровно столько байт сколько было в файле при открытии.
+/// Если клиент передал ключ, то каждый чанк шифруется secretstream, последний с [Tag::Final]
 #[pin_project]
 pub struct Streamer {
-    crypto_stream: Stream<Pull>,
     #[pin]
     file: File,
-    stream_push: Stream<Push>,
-    chunk_size: usize,
-    current_chunk: DownloadReportChunks,
+    /// Сколько байт файла еще не отдано
+    remaining: u64,
+    /// Прочитанная часть текущего чанка
+    buffer: Vec<u8>,
+    /// Шифрование ключом клиента, None если файл отдается как есть
+    encryptor: Option<Stream<Push>>,
 }
 
-const CHUNK_SIZE: usize = 512;
-
 impl TKStream for Streamer {
     type Item = Result<web::Bytes, Error>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let this = self.project();
-
-        let mut buf: [u8; CHUNK_SIZE] = [0; CHUNK_SIZE];
-        let mut buffer = ReadBuf::new(&mut buf);
-
-        if this.crypto_stream.is_not_finalized() {
-            match this.file.poll_read(cx, &mut buffer) {
-                Poll::Ready(res) => match res {
-                    Ok(_) => {
-                        let mut fd: Vec<u8> = Vec::new();
-                        let mut _value: Result<(Vec<u8>, Tag), ()> = Ok((fd, Tag::Message));
-
-                        if this.chunk_size.clone() == this.current_chunk.chunk_num {
-                            fd = this.stream_push.push(&buf, None, Tag::Final).unwrap();
-                            _value = this.crypto_stream.pull(&fd, None);
-                            this.current_chunk.chunk_num = 0;
-                        } else {
-                            this.current_chunk.increment();
-                            fd = this.stream_push.push(&buf, None, Tag::Message).unwrap();
-                            _value = this.crypto_stream.pull(&fd, None);
-                        }
+        let mut this = self.project();
+
+        loop {
+            let chunk_len = (*this.remaining).min(CHUNK_SIZE as u64) as usize;
 
-                        match _value {
-                            Ok((decrypted, _tag)) => Poll::Ready(Some(Ok(decrypted.into()))),
-                            Err(_) => Poll::Ready(Some(Err(
-                                actix_web::error::ErrorInternalServerError("Incorrect"),
-                            ))),
+            // Дочитываем чанк целиком, у зашифрованных чанков должны быть одинаковые границы
+            if this.buffer.len() < chunk_len {
+                let mut buf = vec![0; chunk_len - this.buffer.len()];
+                let mut read_buf = ReadBuf::new(&mut buf);
+
+                match this.file.as_mut().poll_read(cx, &mut read_buf) {
+                    Poll::Ready(Ok(())) => {
+                        if read_buf.filled().is_empty() {
+                            return Poll::Ready(Some(Err(actix_web::error::ErrorInternalServerError(
+                                "Файл отчета изменился во время скачивания"
+                            ))));
                         }
+
+                        this.buffer.extend_from_slice(read_buf.filled());
+                        continue;
                     }
-                    Err(err) => {
-                        Poll::Ready(Some(Err(actix_web::error::ErrorInternalServerError(err))))
+                    Poll::Ready(Err(err)) => {
+                        return Poll::Ready(Some(Err(actix_web::error::ErrorInternalServerError(err))));
                     }
-                },
-                Poll::Pending => Poll::Pending,
+                    Poll::Pending => return Poll::Pending,
+                }
             }
-        } else {
-            Poll::Ready(None)
+
+            let chunk = std::mem::take(this.buffer);
+            *this.remaining -= chunk.len() as u64;
+
+            return match this.encryptor.as_mut() {
+                None if chunk.is_empty() => Poll::Ready(None),
+                None => Poll::Ready(Some(Ok(chunk.into()))),
+                Some(encryptor) if encryptor.is_finalized() => Poll::Ready(None),
+                Some(encryptor) => {
+                    // Пустой файл тоже отдается одним финальным чанком, иначе клиент не узнает что поток закончился
+                    let tag = if *this.remaining == 0 { Tag::Final } else { Tag::Message };
+
+                    match encryptor.push(&chunk, None, tag) {
+                        Ok(encrypted) => Poll::Ready(Some(Ok(encrypted.into()))),
+                        Err(_) => Poll::Ready(Some(Err(actix_web::error::ErrorInternalServerError(
+                            "Не удалось зашифровать чанк"
+                        )))),
+                    }
+                }
+            };
         }
     }
 }
 
 impl Streamer {
+    /// [Get/Head Request] Скачать отчет. Без шифрования поддерживает Range и If-Range,
+    /// HEAD отдает те же заголовки без тела: Content-Length, ETag, Last-Modified.
+    /// Вместо токена можно передать подписанную ссылку из POST /download/links
     pub async fn download_report(
         request: HttpRequest,
         path_to_file: web::Path<FilePath>,
         settings: Data<Settings>,
+        conn_db: DatabasePool,
     ) -> Result<HttpResponse, CustomError> {
-        let token = get_token_from_header(request.headers()).map_or_else(|_error| {
-            return Err(CustomError::Unauthorized.error_response());
-        }, |token| Ok(token));
+        let signed_link = if request.query_string().contains("signature=") {
+            let link = web::Query::<SignedLink>::from_query(request.query_string())
+                .map_err(|_| CustomError::Unauthorized)?;
 
-        if let Err(error) = token {
-            return Ok(error);
-        }
-
-        let user_id_result = match handler_user_info(RpcRequest::get_userinfo_by_token(Some(token.unwrap())).await) {
-            Ok(user) => Ok(UserInfo::get_pub_fields(&user.id)),
-            Err(error) => Err(error)
+            Some(link.into_inner())
+        } else {
+            None
         };
 
-        if let Err(error) = user_id_result {
-            return Ok(HttpResponse::with_body(StatusCode::NOT_FOUND, BoxBody::new(error.1)))
-        }
+        // По подписанной ссылке пользователь берется из ссылки, токен не проверяется.
+        // Подпись проверяется до поиска файла, ссылка выдается на полное имя файла
+        let user_id = match signed_link.as_ref() {
+            Some(link) => {
+                link.verify(&settings.download_links, path_to_file.path.trim())?;
+                link.user.clone()
+            }
+            None => {
+                let token = match get_token_from_header(request.headers()) {
+                    Ok(token) => token,
+                    Err(_) => return Err(CustomError::Unauthorized),
+                };
 
-        // Path to file
-        let report_dir = if settings.get_prod() {
-            dotenv!("PROD_REPORTS_DIR")
-        } else {
-            dotenv!("REPORTS_DIR")
+                match handler_user_info(RpcRequest::get_userinfo_by_token(Some(token)).await) {
+                    Ok(user) => UserInfo::get_pub_fields(&user.id),
+                    Err(_) => return Err(CustomError::Unauthorized),
+                }
+            }
         };
 
-        let file_path = format!("{}/reports{}/{}", report_dir, user_id_result.unwrap_or("-1".to_string()), path_to_file.path.clone());
-        info!("Путь до файла: {}", file_path);
-        let file_size = match std::fs::metadata(&file_path) {
-            Ok(file) => Ok(file.len()),
-            Err(error) => Err(map_io_error(error)),
+        // Отдается только файл из папки пользователя, id с путями до других папок не принимаются
+        let report_file = path_to_file.resolve(&settings, &user_id)?;
+        info!("Путь до файла: {}", report_file.path.display());
+
+        let key = match request.headers().get(ENCRYPTION_KEY_HEADER) {
+            Some(value) => Some(parse_encryption_key(value.to_str().unwrap_or(""))?),
+            None => None,
         };
 
-        if let Err(error_msg) = file_size {
-            return Err(error_msg);
+        let single_use = signed_link.as_ref().is_some_and(|link| link.nonce.is_some());
+
+        // Одноразовая ссылка расходуется только когда файл точно будет отдан.
+        // HEAD ее не расходует, чтобы клиент мог узнать размер перед скачиванием
+        if let Some(link) = signed_link.as_ref().filter(|_| request.method() != Method::HEAD) {
+            if let Some(nonce) = link.nonce.as_ref() {
+                let is_first_use = UsedLinks::new(&conn_db, &settings)
+                    .use_once(nonce, link.expires)
+                    .await
+                    .map_err(|error| {
+                        error!("code: {} message: {}", error.0, error.1);
+                        CustomError::Unknown
+                    })?;
+
+                if !is_first_use {
+                    return Err(CustomError::LinkExpired);
+                }
+            }
         }
 
-        let num_chunks = (file_size.unwrap() as usize + CHUNK_SIZE - 1) / CHUNK_SIZE;
-        // Generate key
-        let secret_key = secretstream::gen_key();
-        let (stream, header) = Stream::init_push(&secret_key).unwrap();
+        // Зашифрованный поток читается только целиком, поэтому Range для него не поддерживается.
+        // Одноразовая ссылка расходуется первым GET и докачать по ней нельзя, поэтому файл тоже отдается целиком
+        let whole_file_only = key.is_some() || single_use;
 
-        // [Streamer] This is necessary for saving files in parts, this object is used for streaming data.
-        // [Streamer] Эта структура необхадима для отправки файла по чанкам.
-        let streamer = Streamer {
-            crypto_stream: Stream::init_pull(&header, &secret_key).unwrap(),
-            file: File::open(&Path::new(&file_path)).await.unwrap(),
-            stream_push: stream,
-            chunk_size: num_chunks,
-            current_chunk: DownloadReportChunks { chunk_num: 0 },
+        let range = match whole_file_only {
+            true => None,
+            false => match requested_range(&request, &report_file) {
+                Ok(range) => range,
+                Err(()) => {
+                    return Ok(HttpResponse::RangeNotSatisfiable()
+                        .insert_header(ContentRange(ContentRangeSpec::Bytes {
+                            range: None,
+                            instance_length: Some(report_file.size),
+                        }))
+                        .finish());
+                }
+            },
         };
 
-        Ok(HttpResponse::Ok()
-            .content_type("application/vnd.openxmlformats-office document.spreadsheetml.sheet")
+        let (start, length) = match range {
+            Some((start, end)) => (start, end - start + 1),
+            None => (0, report_file.size),
+        };
+
+        let file = open_from(&report_file, start)?;
+
+        let mut response = match range {
+            Some((start, end)) => {
+                let mut response = HttpResponse::PartialContent();
+                response.insert_header(ContentRange(ContentRangeSpec::Bytes {
+                    range: Some((start, end)),
+                    instance_length: Some(report_file.size),
+                }));
+                response
+            }
+            None => HttpResponse::Ok(),
+        };
+
+        response
+            .content_type(report_file.content_type())
             .append_header((
                 header::ACCESS_CONTROL_ALLOW_ORIGIN,
                 "*"
             ))
-            .append_header((
-                header::CONTENT_DISPOSITION,
-                format!("attachment; filename={}", path_to_file.path).as_str(),
-            ))
-            .streaming(streamer))
+            .insert_header(content_disposition(&report_file.file_name))
+            .append_header((header::ACCEPT_RANGES, if whole_file_only { "none" } else { "bytes" }))
+            .insert_header(ETag(report_file.etag()))
+            .insert_header(LastModified(report_file.modified.into()));
+
+        let encryptor = match key {
+            Some(key) => {
+                let (encryptor, stream_header) = Stream::init_push(&key).map_err(|_| CustomError::Unknown)?;
+
+                response
+                    .append_header((ENCRYPTION_HEADER, to_hex(&stream_header.0)))
+                    .append_header((ENCRYPTION_CHUNK_SIZE_HEADER, CHUNK_SIZE.to_string()));
+
+                Some(encryptor)
+            }
+            None => None,
+        };
+
+        response.no_chunking(content_length(length, encryptor.is_some()));
+
+        // [Streamer] Эта структура необхадима для отправки файла по чанкам.
+        let streamer = Streamer {
+            file,
+            remaining: length,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            encryptor,
+        };
+
+        Ok(response.streaming(streamer))
+    }
+}
+
+/// Диапазон байт из Range, включительно. None значит отдать файл целиком:
+/// Range не передан, в нем несколько диапазонов или If-Range не совпал с текущим файлом.
+/// Err если диапазон за пределами файла
+fn requested_range(request: &HttpRequest, report_file: &ReportFile) -> Result<Option<(u64, u64)>, ()> {
+    if !request.headers().contains_key(header::RANGE) {
+        return Ok(None);
+    }
+
+    // If-Range сравнивается строго, с ETag или с датой изменения с точностью до секунды
+    if request.headers().contains_key(header::IF_RANGE) {
+        let is_same_file = match IfRange::parse(request) {
+            Ok(IfRange::EntityTag(etag)) => etag.strong_eq(&report_file.etag()),
+            Ok(IfRange::Date(date)) => date.to_string() == HttpDate::from(report_file.modified).to_string(),
+            Err(_) => false,
+        };
+
+        if !is_same_file {
+            return Ok(None);
+        }
+    }
+
+    match Range::parse(request) {
+        Ok(Range::Bytes(ranges)) if ranges.len() == 1 => ranges[0]
+            .to_satisfiable_range(report_file.size)
+            .map(Some)
+            .ok_or(()),
+        // Несколько диапазонов и неизвестные единицы не поддерживаются, отдается весь файл
+        _ => Ok(None),
+    }
+}
+
+/// Открывает файл отчета и переходит на байт [start]
+fn open_from(report_file: &ReportFile, start: u64) -> Result<File, CustomError> {
+    let mut file = std::fs::File::open(&report_file.path).map_err(map_io_error)?;
+
+    if start > 0 {
+        file.seek(SeekFrom::Start(start)).map_err(map_io_error)?;
+    }
+
+    Ok(File::from_std(file))
+}
+
+/// Длина ответа: [length] байт файла, а при шифровании еще [secretstream::ABYTES] на каждый чанк
+fn content_length(length: u64, is_encrypted: bool) -> u64 {
+    if !is_encrypted {
+        return length;
+    }
+
+    let chunks = ((length + CHUNK_SIZE as u64 - 1) / CHUNK_SIZE as u64).max(1);
+
+    length + chunks * secretstream::ABYTES as u64
+}
+
+/// Content-Disposition для скачивания: имя в кавычках, а для имени не из ASCII еще filename* в UTF-8
+fn content_disposition(file_name: &str) -> ContentDisposition {
+    let mut parameters = vec![DispositionParam::Filename(file_name.to_string())];
+
+    if !file_name.is_ascii() {
+        parameters.push(DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext("UTF-8".to_string()),
+            language_tag: None,
+            value: file_name.as_bytes().to_vec(),
+        }));
+    }
+
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters,
+    }
+}
+
+fn parse_encryption_key(value: &str) -> Result<Key, CustomError> {
+    // Key::from_slice проверяет что ключ ровно secretstream::KEYBYTES байт
+    from_hex(value.trim())
+        .and_then(|bytes| Key::from_slice(&bytes))
+        .ok_or(CustomError::InvalidEncryptionKey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_disposition_quotes_file_name() {
+        assert_eq!(content_disposition("report 1.xlsx").to_string(), "attachment; filename=\"report 1.xlsx\"");
+        assert_eq!(content_disposition("a\"b;.csv").to_string(), "attachment; filename=\"a\\\"b;.csv\"");
+        assert_eq!(
+            content_disposition("отчет.pdf").to_string(),
+            "attachment; filename=\"отчет.pdf\"; filename*=UTF-8''%D0%BE%D1%82%D1%87%D0%B5%D1%82.pdf"
+        );
+    }
+
+    #[test]
+    fn encryption_key_is_32_bytes_of_hex() {
+        assert!(parse_encryption_key(&"ab".repeat(secretstream::KEYBYTES)).is_ok());
+        assert!(parse_encryption_key(&format!(" {} ", "AB".repeat(secretstream::KEYBYTES))).is_ok());
+        assert!(parse_encryption_key(&"ab".repeat(secretstream::KEYBYTES - 1)).is_err());
+        assert!(parse_encryption_key(&"zz".repeat(secretstream::KEYBYTES)).is_err());
     }
 }
@@ -3,6 +3,8 @@ use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::Arc;
 
+use chrono::Utc;
+
 use lazy_static::lazy_static;
 
 use actix_web::web::{Data, Json};
@@ -10,26 +12,30 @@ use actix_web::{HttpRequest, Responder};
 
 use serde::Deserialize;
 
-use tokio::sync::{ Mutex as TokioMutex, RwLock as TokioRwLock };
+use tokio::sync::RwLock as TokioRwLock;
 
 use crate::args::Settings;
-use dotenv_codegen::dotenv;
-use mysql_async::Conn;
 use serde_json::Value;
 use tracing::{error, info};
 use crate::api_server::api_token::utils::format_utils::token_utils::{handle_token_error, token_format_to_string};
 
-use crate::db::connect::{get_info_about_files_by_id, get_last_id_from_table_name};
+use crate::db::file_repository::FileRepository;
+use crate::db::report_catalog::{CatalogEntry, ReportCatalog, PERIOD_FORMAT};
 use crate::error::errors_utils::err_utils::{chunk_is_empty, get_first_error_message_and_code, get_last_error_message_and_code};
+use crate::helper::generate_batch::{save_batch_archive, BatchEntry};
 use crate::helper::generate_xlsx::{generate_report_from_csv};
-use crate::helper::handler_info_about_file_by_id::{handle_info_about_file, handle_last_id};
+use crate::helper::handler_info_about_file_by_id::handle_info_about_file;
 use crate::helper::{compare_user_id, is_exist_file, type_report_that_generated};
-use crate::helper::chunks::chunk_manager::creator_of_chunks::create_chunks_by_types;
+use crate::helper::chunks::chunk_manager::creator_of_chunks::{create_chunk_sources_by_types, stream_chunks_for_share};
 use crate::helper::user_info::user::UserInfo;
+use crate::indexing_report_struct::column_mapping::ColumnMapping;
+use crate::money::Money;
 use crate::r#trait::automated_report_response::Response;
 use crate::r#trait::filter_report::{Filter, ReportType, Status};
-use crate::r#type::types::{InformationAboutFileMicroApiDB, InformationAboutFileMicroApiDBResult, ReportsDateRange, ReportsStorage, ResponseError};
+use crate::r#trait::report_kind::{Organization, ReportKind};
+use crate::r#type::types::{DatabasePool, InformationAboutFileMicroApiDB, InformationAboutFileMicroApiDBResult, ReportsDateRange, ReportsStorage, ResponseError};
 use crate::server::tokens_storage::TokensStorage;
+use crate::share::batch_reports::{BatchReports, BATCH_READ_PERCENT};
 use crate::share::{ArcMutexWrapper, Report, Share};
 
 // Временное хранилище для генерируемых хешей
@@ -43,10 +49,42 @@ pub async fn check_generated_hashes(share: ReportsStorage) {
     }
 }
 
-#[derive(Debug, Deserialize)]
-enum TypeGenerateReport {
+/// Абонентская плата по умолчанию, если она не передана в запросе
+pub const DEFAULT_MONTHLY_SUBSCRIPTION_FEE: i64 = 1_000_000;
+
+/// [Output format] В каком виде сохраняется сгенерированный отчет
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum TypeGenerateReport {
+    /// Каждый лист отчета отдельным csv файлом, все файлы упакованы в zip архив
+    #[serde(alias = "csv", alias = "CSV")]
     Csv,
+    /// Одна xlsx книга со всеми листами
+    #[default]
+    #[serde(alias = "xlsx", alias = "XLSX")]
     Xlsx,
+    /// Только лист "Общий отчет о платежах" в pdf
+    #[serde(alias = "pdf", alias = "PDF")]
+    Pdf,
+}
+
+impl TypeGenerateReport {
+    /// Расширение сохраненного файла отчета
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TypeGenerateReport::Csv => "zip",
+            TypeGenerateReport::Xlsx => "xlsx",
+            TypeGenerateReport::Pdf => "pdf",
+        }
+    }
+
+    /// Формат в ключе отчета, чтобы отчеты в разных форматах по одним фильтрам не пересекались
+    pub fn to_key_part(&self) -> &'static str {
+        match self {
+            TypeGenerateReport::Csv => "csv",
+            TypeGenerateReport::Xlsx => "xlsx",
+            TypeGenerateReport::Pdf => "pdf",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,11 +98,73 @@ pub struct GenerateFile {
     /// к примеру когда мы будем генерировать отчет для таксопарка. Для отчета таксопарка нужно 2 файла один переводы,
     /// другой платежи.
     pub filters: Vec<Filter>,
-    /// [Reporty type] Есть 3 вида отчета, [Agent, TaxiCompony, Merchant] пользователь должен ввести один из трех типов
+    /// [Reporty type] Вид отчета, один из зарегистрированных в [REPORT_KINDS] к примеру [Agent, TaxiCompany, Merchant]
+    ///
+    /// [REPORT_KINDS]: crate::helper::report_type::REPORT_KINDS
     pub report_type: Option<ReportType>,
     /// [Monthly subscription fee]
-    #[serde(default, deserialize_with = "deserialize_float_or_integer")]
-    pub monthly_subscription_fee: Option<f64>
+    #[serde(default, deserialize_with = "deserialize_money")]
+    pub monthly_subscription_fee: Option<Money>,
+    /// [Output format] Формат сохраненного отчета, по умолчанию xlsx
+    #[serde(default)]
+    pub output_format: TypeGenerateReport,
+    /// [Organizations] Пакетная генерация: список id организаций или "all" (все организации из файлов).
+    /// Передается вместо [GenerateFile::provider_id] и [GenerateFile::merchant_id]
+    #[serde(default, deserialize_with = "deserialize_organizations")]
+    pub organizations: Option<BatchOrganizations>,
+    /// [Compare filters] Фильтры файлов прошлого периода. Если переданы, то по ним собирается второй отчет
+    /// и в отчет добавляется сравнение периодов
+    #[serde(default)]
+    pub compare_filters: Option<Vec<Filter>>,
+    /// [Reconciliation] Сверка пополнений счета с суммой транзакций по дням. Если передана,
+    /// то в отчет добавляется лист расхождений, а сводка доступна через GET /reports/{key}/reconciliation
+    #[serde(default)]
+    pub reconciliation: Option<ReconciliationRequest>,
+}
+
+/// [Reconciliation request] Параметры сверки пополнений счета
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReconciliationRequest {
+    /// [Tolerance] Допустимое расхождение за день, по умолчанию 0
+    #[serde(default, deserialize_with = "deserialize_tolerance")]
+    pub tolerance: Option<Money>,
+}
+
+impl ReconciliationRequest {
+    pub fn tolerance(&self) -> Money {
+        self.tolerance.unwrap_or(Money::ZERO)
+    }
+}
+
+/// [Batch organizations] Организации для которых собирается отчет в пакетной генерации
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchOrganizations {
+    /// Отчет по каждой организации которая есть в файлах
+    All,
+    /// Отчет по каждой организации из списка
+    Ids(Vec<String>),
+}
+
+impl BatchOrganizations {
+    /// Список id из запроса, None значит все организации из файлов
+    pub fn ids(&self) -> Option<Vec<String>> {
+        match self {
+            BatchOrganizations::All => None,
+            BatchOrganizations::Ids(ids) => Some(ids.clone()),
+        }
+    }
+
+    /// Организации в ключе отчета, порядок id в запросе на ключ не влияет
+    pub fn to_key_part(&self) -> String {
+        match self {
+            BatchOrganizations::All => "batch:all".to_string(),
+            BatchOrganizations::Ids(ids) => {
+                let mut ids = ids.clone();
+                ids.sort();
+                format!("batch:{}", ids.join(","))
+            }
+        }
+    }
 }
 
 pub fn deserialize_string_or_integer<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
@@ -80,19 +180,73 @@ where
     }
 }
 
-fn deserialize_float_or_integer<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+fn deserialize_organizations<'de, D>(deserializer: D) -> Result<Option<BatchOrganizations>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Value = serde::Deserialize::deserialize(deserializer)?;
+    let error = || serde::de::Error::custom("organizations должен быть списком id организаций или \"all\"");
+
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s) if s.trim().eq_ignore_ascii_case("all") => Ok(Some(BatchOrganizations::All)),
+        Value::Array(values) => {
+            let mut ids: Vec<String> = Vec::new();
+
+            for value in values {
+                let id = match value {
+                    Value::String(s) => s.trim().to_string(),
+                    Value::Number(n) if n.is_i64() => n.to_string(),
+                    _ => return Err(error()),
+                };
+
+                // Повторы id не дают лишних отчетов
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+
+            Ok(Some(BatchOrganizations::Ids(ids)))
+        }
+        _ => Err(error()),
+    }
+}
+
+fn deserialize_money<'de, D>(deserializer: D) -> Result<Option<Money>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     let value: Value = serde::Deserialize::deserialize(deserializer)?;
+    let default_fee = Money::from_integer(DEFAULT_MONTHLY_SUBSCRIPTION_FEE);
 
     match value {
-        Value::String(s) => Ok(Some(s.parse::<f64>().unwrap_or(1_000_000.0))),
-        Value::Number(n) if n.is_i64() => Ok(Some(n.as_f64().unwrap_or(1_000_000.0))),
+        Value::String(s) => Ok(Some(Money::parse(&s).unwrap_or(default_fee))),
+        Value::Number(n) => Ok(Some(Money::parse(&n.to_string()).unwrap_or(default_fee))),
         _ => Ok(None), // Handle other cases as needed
     }
 }
 
+fn deserialize_tolerance<'de, D>(deserializer: D) -> Result<Option<Money>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Value = serde::Deserialize::deserialize(deserializer)?;
+    let error = || serde::de::Error::custom("tolerance должен быть неотрицательной суммой");
+
+    let tolerance = match value {
+        Value::Null => return Ok(None),
+        Value::String(s) => Money::parse(&s).map_err(|_| error())?,
+        Value::Number(n) => Money::parse(&n.to_string()).map_err(|_| error())?,
+        _ => return Err(error()),
+    };
+
+    if tolerance < Money::ZERO {
+        return Err(error());
+    }
+
+    Ok(Some(tolerance))
+}
+
 impl GenerateFile {
     pub fn filters_validation_for_uniqueness<T, F>(&self, field: F, field_name: &str) -> Result<(), Response>
     where
@@ -117,24 +271,24 @@ impl GenerateFile {
 
     pub fn set_default_monthly_subscription_fee(&mut self) {
         if let None = self.monthly_subscription_fee {
-            self.monthly_subscription_fee = Some(1_000_000.0)
+            self.monthly_subscription_fee = Some(Money::from_integer(DEFAULT_MONTHLY_SUBSCRIPTION_FEE))
         }
     }
 
-    pub async fn beyond_last_id(conn: Data<Arc<TokioMutex<Conn>>>, ids: &Vec<u128>) -> Result<(), ResponseError> {
-        let last_id = get_last_id_from_table_name(conn).await;
-        let handle_id = handle_last_id(last_id);
+    pub async fn beyond_last_id(repository: &FileRepository<'_>, ids: &Vec<u128>) -> Result<(), ResponseError> {
+        let last_id = repository.get_last_id().await;
 
-        if let Err(error) = handle_id {
+        if let Err(error) = last_id {
             return Err(error);
         }
 
         let mut errors: Vec<ResponseError> = Vec::new();
 
-        let handle_id = handle_id.unwrap();
+        // Пустая таблица, значит любой переданный id превышает максимальный
+        let last_id = last_id.unwrap().unwrap_or(0) as u128;
 
         ids.iter().for_each(|id| {
-            if handle_id < *id as isize {
+            if last_id < *id {
                 errors.push((6546534, format!("Id: {} превышает максимальный id", id)));
             }
         });
@@ -155,8 +309,8 @@ impl GenerateFile {
         }
     }
 
-    /// Получаем все статусы, моды, платежные системы в переданных фильрах
-    pub fn get_all_s_m_p(&self) -> (Vec<Status>, Vec<String>, Vec<Vec<String>>) {
+    /// Получаем все статусы, моды, платежные системы в переданных фильрах, по списку на каждый фильтр
+    pub fn get_all_s_m_p(&self) -> (Vec<Vec<Status>>, Vec<Vec<String>>, Vec<Vec<String>>) {
         // Для статусов
         let mut s = Vec::new();
         // Для модов
@@ -165,8 +319,23 @@ impl GenerateFile {
         let mut p = Vec::new();
 
         for filter in self.filters.iter() {
-            s.push(filter.status.clone().unwrap_or(Status::Unknown));
-            m.push(filter.mode.clone().unwrap_or("".to_string()));
+            // Фильтр без статуса и мода записывается как раньше, чтобы ключи уже собранных отчетов не поменялись
+            let mut f_s = filter.get_statuses();
+            if f_s.is_empty() {
+                f_s.push(Status::Unknown);
+            }
+            f_s.sort();
+            f_s.dedup();
+            s.push(f_s);
+
+            let mut f_m = filter.get_modes();
+            if f_m.is_empty() {
+                f_m.push("".to_string());
+            }
+            f_m.sort();
+            f_m.dedup();
+            m.push(f_m);
+
             let mut f_p_s = filter.payments_system.clone().unwrap_or(Vec::new());
             f_p_s.sort();
             p.push(f_p_s);
@@ -175,71 +344,242 @@ impl GenerateFile {
         (s, m, p)
     }
 
-    pub fn check_merchant_id_by_report_type(&self) -> Result<(), ResponseError>{
-        fn is_none_merchant_id(merchant_id: &Option<String>) -> bool {
-            if merchant_id.is_none() {
-                true
-            } else {
-                false
-            }
+    /// Каноничные выражения фильтров вместе с id файла, для ключа отчета
+    pub fn get_all_expressions(&self) -> Vec<String> {
+        self.filters
+            .iter()
+            .filter_map(|filter| filter.expression.as_ref().map(|expression| format!("{}={}", filter.id, expression.canonical())))
+            .collect()
+    }
+
+    /// Проверяем статусы и выражения в фильтрах до начала генерации
+    pub fn check_filters(&self) -> Result<(), ResponseError> {
+        for filter in self.filters.iter() {
+            filter.check()?;
         }
 
-        // Если тип отчета Merchant то все то поле merchant_id может быть быстым а может и не быть пустым
-        // Ну а если report_type не Merchnt и поле merchant_id не пустое, то в таком случае мы возвращаем ошибку
-        match self.report_type {
-            None => Err((7357542, "report type не был передан в запрос".to_string())),
-            Some(rp_type) => {
-                let error = Err((7357543, "merchant_id может быть передан только для отчета Merchant".to_string()));
-                match rp_type {
-                    ReportType::Merchant => {
-                        if !is_none_merchant_id(&self.merchant_id) {Ok(())} else {Ok(())}
-                    }
-                    _ => {
-                        if is_none_merchant_id(&self.merchant_id) {Ok(())} else {return error}
-                    }
-                }
+        Ok(())
+    }
+
+    /// Проверяем организации пакетной генерации, [max_organizations] из настроек
+    pub fn check_organizations(&self, max_organizations: usize) -> Result<(), ResponseError> {
+        let organizations = match self.organizations.as_ref() {
+            None => return Ok(()),
+            Some(organizations) => organizations,
+        };
+
+        if self.provider_id.is_some() || self.merchant_id.is_some() {
+            return Err((7357580, "organizations не может быть передан вместе с provider_id или merchant_id".to_string()));
+        }
+
+        if let BatchOrganizations::Ids(ids) = organizations {
+            if ids.is_empty() {
+                return Err((7357581, "Список organizations не может быть пустым".to_string()));
+            }
+            if ids.iter().any(|id| id.is_empty()) {
+                return Err((7357581, "id организации в organizations не может быть пустым".to_string()));
+            }
+            if ids.len() > max_organizations {
+                return Err((7357582, format!("В organizations не может быть больше {} организаций", max_organizations)));
             }
         }
 
+        Ok(())
     }
 
-    pub fn check_provider_id_by_report_type(&self) -> Result<(), ResponseError>{
-        fn is_none_provider_id(provider_id: &Option<String>) -> bool {
-            if provider_id.is_none() {
-                true
-            } else {
-                false
+    /// Проверяем фильтры прошлого периода для сравнения периодов
+    pub fn check_comparison(&self) -> Result<(), ResponseError> {
+        let compare_filters = match self.compare_filters.as_ref() {
+            None => return Ok(()),
+            Some(compare_filters) => compare_filters,
+        };
+
+        if self.organizations.is_some() {
+            return Err((7357590, "compare_filters не может быть передан вместе с organizations".to_string()));
+        }
+
+        if compare_filters.is_empty() {
+            return Err((7357591, "Список compare_filters не может быть пустым".to_string()));
+        }
+
+        if self.output_format == TypeGenerateReport::Pdf {
+            return Err((7357592, "Сравнение периодов не попадает в pdf, выберите xlsx или csv".to_string()));
+        }
+
+        let kind = self.report_kind()?;
+        if !kind.has_summary_sheets() {
+            return Err((7357593, format!("Для отчета {} нет сравнения периодов", kind.name())));
+        }
+
+        let mut ids = HashSet::new();
+        for filter in compare_filters.iter() {
+            if !ids.insert(filter.id) {
+                return Err((7357594, format!("В compare_filters не может быть нескольких фильтров с одним и тем же id: {}", filter.id)));
             }
+
+            filter.check()?;
+        }
+
+        Ok(())
+    }
+
+    /// Id файлов прошлого периода
+    pub fn compare_filters_id(&self) -> Vec<u128> {
+        self.compare_filters
+            .as_ref()
+            .map_or(Vec::new(), |filters| filters.iter().map(|filter| filter.id as u128).collect())
+    }
+
+    /// Фильтры прошлого периода в ключе отчета, порядок фильтров в запросе на ключ не влияет
+    pub fn comparison_key_part(&self) -> Option<String> {
+        let compare_filters = self.compare_filters.as_ref()?;
+
+        let mut parts = compare_filters
+            .iter()
+            .map(|filter| {
+                let mut statuses = filter.get_statuses().iter().map(|status| status.to_string()).collect::<Vec<String>>();
+                statuses.sort();
+                let mut modes = filter.get_modes();
+                modes.sort();
+                let mut payments_system = filter.payments_system.clone().unwrap_or_default();
+                payments_system.sort();
+
+                format!(
+                    "{}:{}:{}:{}:{}",
+                    filter.id,
+                    statuses.join("+"),
+                    modes.join("+"),
+                    payments_system.join("+"),
+                    filter.expression.as_ref().map(|expression| expression.canonical()).unwrap_or_default()
+                )
+            })
+            .collect::<Vec<String>>();
+        parts.sort();
+
+        Some(format!("compare={}", parts.join(" ")))
+    }
+
+    /// Проверяем что сверку пополнений можно собрать для вида отчета
+    pub fn check_reconciliation(&self) -> Result<(), ResponseError> {
+        if self.reconciliation.is_none() {
+            return Ok(());
+        }
+
+        let kind = self.report_kind()?;
+        if !kind.needs_refill() {
+            return Err((7357595, format!("Для отчета {} нет пополнений счета, сверка не собирается", kind.name())));
+        }
+
+        Ok(())
+    }
+
+    /// Допустимое расхождение сверки в ключе отчета
+    pub fn reconciliation_key_part(&self) -> Option<String> {
+        self.reconciliation
+            .as_ref()
+            .map(|reconciliation| format!("reconcile={}", reconciliation.tolerance()))
+    }
+
+    /// Выражения фильтров, фильтры прошлого периода и сверка для ключа отчета.
+    /// Отчет со сравнением или сверкой не пересекается с отчетом без них
+    pub fn key_expressions(&self) -> Vec<String> {
+        let mut key_expressions = self.get_all_expressions();
+        key_expressions.extend(self.comparison_key_part());
+        key_expressions.extend(self.reconciliation_key_part());
+
+        key_expressions
+    }
+
+    /// Расширение файла который получит пользователь, пакетная генерация всегда отдает zip архив
+    pub fn result_extension(&self) -> &'static str {
+        match self.organizations {
+            Some(_) => "zip",
+            None => self.output_format.extension(),
         }
+    }
+
+    /// Фильтры запроса для каталога отчетов, без путей до csv файлов
+    pub fn catalog_filters(&self) -> Value {
+        let filters = |filters: &Vec<Filter>| filters
+            .iter()
+            .map(|filter| serde_json::json!({
+                "id": filter.id,
+                "status": filter.status,
+                "mode": filter.mode,
+                "payments_system": filter.payments_system,
+                "expression": filter.expression,
+            }))
+            .collect::<Vec<Value>>();
+
+        serde_json::json!({
+            "filters": filters(&self.filters),
+            "compare_filters": self.compare_filters.as_ref().map(filters),
+            "reconciliation_tolerance": self.reconciliation.as_ref().map(|reconciliation| reconciliation.tolerance().to_string()),
+            "monthly_subscription_fee": self.monthly_subscription_fee.as_ref().map(|fee| fee.to_string()),
+        })
+    }
 
-        // Если тип отчета Merchant то все то поле merchant_id может быть быстым а может и не быть пустым
-        // Ну а если report_type не Merchnt и поле merchant_id не пустое, то в таком случае мы возвращаем ошибку
+    /// Вид отчета из запроса, ошибка если report_type не передан или не зарегистрирован
+    pub fn report_kind(&self) -> Result<&'static dyn ReportKind, ResponseError> {
         match self.report_type {
             None => Err((7357542, "report type не был передан в запрос".to_string())),
-            Some(rp_type) => {
-                let error = Err((8357543, "provider_id может быть передан только для отчета Agent/TaxiCompany".to_string()));
-                match rp_type {
-                    ReportType::Agent | ReportType::TaxiCompany => {
-                        if !is_none_provider_id(&self.provider_id) {Ok(())} else {Ok(())}
-                    }
-                    _ => {
-                        if is_none_provider_id(&self.provider_id) {Ok(())} else {return error}
-                    }
-                }
+            Some(rp_type) => rp_type.kind(),
+        }
+    }
+
+    pub fn check_merchant_id_by_report_type(&self) -> Result<(), ResponseError>{
+        // Если отчет строится по Merchant то поле merchant_id может быть пустым а может и не быть пустым
+        // Ну а если отчет строится не по Merchant и поле merchant_id не пустое, то в таком случае мы возвращаем ошибку
+        let kind = self.report_kind()?;
+
+        if kind.organization() != Organization::Merchant && self.merchant_id.is_some() {
+            return Err((7357543, format!("merchant_id не может быть передан для отчета {}", kind.name())));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_provider_id_by_report_type(&self) -> Result<(), ResponseError>{
+        // Если отчет строится по провайдеру то поле provider_id может быть пустым а может и не быть пустым
+        // Ну а если отчет строится не по провайдеру и поле provider_id не пустое, то в таком случае мы возвращаем ошибку
+        let kind = self.report_kind()?;
+
+        if kind.organization() != Organization::Provider && self.provider_id.is_some() {
+            return Err((8357543, format!("provider_id не может быть передан для отчета {}", kind.name())));
+        }
+
+        Ok(())
+    }
+
+    /// Проверяет что отчет можно собрать из переданных типов файлов
+    pub fn check_file_types_by_report_type(&self, file_types: &Vec<(&usize, String)>) -> Result<(), ResponseError> {
+        let kind = self.report_kind()?;
+
+        for (id, file_type) in file_types.iter() {
+            if !kind.file_types().iter().any(|kind_file_type| kind_file_type.eq_ignore_ascii_case(file_type)) {
+                return Err((
+                    7357544,
+                    format!("Отчет {} не может быть сгенерирован по файлу {} с типом {}", kind.name(), id, file_type)
+                ));
             }
         }
 
+        Ok(())
     }
 }
 /// [Генерация Отчетов с Фильтрами] [Post Request] Получить и сгенерировать отчет по фильтрам [impl Filter]
+/// Запрос не ждет окончания генерации, в ответ возвращается задача [ReportJobView],
+/// статус которой можно узнать через GET /jobs/{id}
 pub async fn generate_report(
     req: HttpRequest,
-    mut reqeust_generate: Json<GenerateFile>,
+    reqeust_generate: Json<GenerateFile>,
     token_storage: Data<TokioRwLock<TokensStorage>>,
     share: Data<TokioRwLock<Share>>,
-    conn_db: Data<Arc<TokioMutex<Conn>>>,
+    conn_db: DatabasePool,
     settings: Data<Settings>,
 ) -> impl Responder {
+    let mut reqeust_generate = reqeust_generate.into_inner();
+
     if let Err(error) = reqeust_generate.check_merchant_id_by_report_type() {
         return Json(Response::new::<String>(
             Some(error),
@@ -256,33 +596,60 @@ pub async fn generate_report(
         ))
     }
 
-    let organization_provider_id = match reqeust_generate.report_type {
-        None => "".to_string(),
-        Some(rp_type) => {
-            match rp_type {
-                ReportType::Agent => {
-                    reqeust_generate.provider_id = Some(GenerateFile::trim(&mut reqeust_generate.provider_id));
-                    GenerateFile::trim(&mut reqeust_generate.provider_id)
-                },
-                ReportType::TaxiCompany => {
-                    reqeust_generate.provider_id = Some(GenerateFile::trim(&mut reqeust_generate.provider_id));
-                    GenerateFile::trim(&mut reqeust_generate.provider_id)
-                },
-                ReportType::Merchant => {
-                    reqeust_generate.merchant_id = Some(GenerateFile::trim(&mut reqeust_generate.merchant_id));
-                    GenerateFile::trim(&mut reqeust_generate.merchant_id)
-                },
-                ReportType::Unknown => "".to_string()
-            }
+    if let Err(error) = reqeust_generate.check_organizations(settings.max_organizations_in_batch) {
+        return Json(Response::new::<String>(
+            Some(error),
+            None,
+            None
+        ))
+    }
+
+    if let Err(error) = reqeust_generate.check_comparison() {
+        return Json(Response::new::<String>(
+            Some(error),
+            None,
+            None
+        ))
+    }
+
+    if let Err(error) = reqeust_generate.check_reconciliation() {
+        return Json(Response::new::<String>(
+            Some(error),
+            None,
+            None
+        ))
+    }
+
+    let organization_provider_id = match reqeust_generate.report_kind() {
+        Err(_) => "".to_string(),
+        // В пакетной генерации организация берется из каждой строки файла
+        Ok(_) if reqeust_generate.organizations.is_some() => "".to_string(),
+        Ok(kind) => match kind.organization() {
+            Organization::Provider => {
+                reqeust_generate.provider_id = Some(GenerateFile::trim(&mut reqeust_generate.provider_id));
+                GenerateFile::trim(&mut reqeust_generate.provider_id)
+            },
+            Organization::Merchant => {
+                reqeust_generate.merchant_id = Some(GenerateFile::trim(&mut reqeust_generate.merchant_id));
+                GenerateFile::trim(&mut reqeust_generate.merchant_id)
+            },
         }
     };
     // Приводим значение Provider в порядок
 
     // Устанавливаем нижний регистр для фильтров платежных систем
-    for filter in reqeust_generate.filters.iter_mut() {
+    for filter in reqeust_generate.filters.iter_mut().chain(reqeust_generate.compare_filters.iter_mut().flatten()) {
         filter.set_to_lowercase_payments_system_field();
     }
 
+    if let Err(error) = reqeust_generate.check_filters() {
+        return Json(Response::new::<String>(
+            Some(error),
+            None,
+            None
+        ))
+    }
+
     let token_res = token_format_to_string(req.headers());
 
     if let Some(error) = handle_token_error(&token_res) {
@@ -366,8 +733,18 @@ pub async fn generate_report(
         .map(|file_info| file_info.id as u128)
         .collect::<Vec<u128>>();
 
+    // Файлы прошлого периода проверяются вместе с файлами отчета
+    let mut all_files_id = all_filters_id.clone();
+    for id in reqeust_generate.compare_filters_id() {
+        if !all_files_id.contains(&id) {
+            all_files_id.push(id);
+        }
+    }
+
+    let file_repository = FileRepository::new(&conn_db, &settings);
+
     // Делаем проверку переданных id, если вдруг переданные id превышают последний id по номеру в db, то мы возвращаем ошибку
-    let check_last_id = GenerateFile::beyond_last_id(Data::clone(&conn_db), &all_filters_id).await;
+    let check_last_id = GenerateFile::beyond_last_id(&file_repository, &all_files_id).await;
     if let Err(error) = check_last_id {
         return Json(Response::new::<String>(
             Some(error),
@@ -379,12 +756,7 @@ pub async fn generate_report(
     let mut errors: Vec<ResponseError> = Vec::new();
 
     // Даем запрос в базу данных на нужные данные по котором мы будем генерировать отчет
-    let info_about_files_by_id = get_info_about_files_by_id(all_filters_id.clone(), Data::clone(&conn_db))
-        .await
-        .map_or_else(
-            |error| Err(error),
-            |info_about_csv_file| Ok(info_about_csv_file),
-        );
+    let info_about_files_by_id = file_repository.get_by_ids(&all_files_id).await;
 
     // Return json error
     if let Err(error) = info_about_files_by_id {
@@ -392,7 +764,6 @@ pub async fn generate_report(
         return Json(Response::new::<String>(Some(error), None, None));
     }
 
-    // 1. index_file, 2. path_to_file, 3. file_type, 4. file_status, 5. from, 6. to, 7. user_id
     // Функция handle_info_about_file принимает &mut errors если в процессе обработки файловой информации возникнет ошибка
     // в errors передастся ошибка
     let files_info: InformationAboutFileMicroApiDBResult = handle_info_about_file(info_about_files_by_id, &mut errors, &settings);
@@ -416,285 +787,532 @@ pub async fn generate_report(
     let files_info = files_info.iter()
         .map(|file_data| file_data.clone().unwrap()).collect::<InformationAboutFileMicroApiDB>();
 
-    // id, from, to информация о дате по которой был сформирован отчет
+    // id, from, to информация о дате по которой был сформирован отчет, файлы прошлого периода сюда не попадают
     let full_date_from_to = files_info.iter()
-        .map(|info_file| (info_file.0, info_file.4.clone(), info_file.5.clone())).collect::<Vec<(usize, String, String)>>();
+        .filter(|info_file| all_filters_id.contains(&(info_file.id as u128)))
+        .map(|info_file| (info_file.id, info_file.from_to_string(), info_file.to_to_string())).collect::<Vec<(usize, String, String)>>();
 
     // Получаем user_id каждого запрошенного файла
-    let ids_of_files_owners = files_info.iter().map(|(_, _, _, _, _, _, user_id )| user_id).collect::<Vec<&isize>>();
+    let ids_of_files_owners = files_info.iter().map(|file_info| &file_info.user_id).collect::<Vec<&isize>>();
 
     // Проверяем, можно ли генерировать пользователю который запросил отчет, генерировать файл по запрошенным id файлов.
     // true значит можно
     // false значит файл принадлежит не текущему пользователю
-    if compare_user_id(&user_info.id, ids_of_files_owners) {
-        let rp_tp = files_info
-            .iter()
-            .map(|file_info| {
-                let (id_file, _, file_type, _, _, _, _) = &file_info;
-                (id_file, type_report_that_generated(*file_type as i8))
-            })
-        .collect::<Vec<(&usize, String)>>();
-
-        // В этих циклах мы записываем path до файла в фильтре.
-        for file in files_info.iter() {
-            for filter in reqeust_generate.filters.iter_mut() {
-                let fl = file;
-                if fl.0 == filter.id as usize {
-                    filter.set_path_to_file(fl.1.clone());
-                }
+    if !compare_user_id(&user_info.id, ids_of_files_owners) {
+        let error = (4324323, "По переданным id не возможно сгенерировать файл".to_string());
+        error!("code: {} message: {}", error.0, error.1);
+        return Json(Response::new::<String>(
+            Some(error),
+            None,
+            None
+        ))
+    }
+
+    let rp_tp = files_info
+        .iter()
+        .map(|file_info| {
+            (&file_info.id, type_report_that_generated(file_info.file_type as i8))
+        })
+    .collect::<Vec<(&usize, String)>>();
+
+    if let Err(error) = reqeust_generate.check_file_types_by_report_type(&rp_tp) {
+        error!("code: {} message: {}", error.0, error.1);
+        return Json(Response::new::<String>(
+            Some(error),
+            None,
+            None
+        ))
+    }
+
+    // В этих циклах мы записываем path до файла в фильтре.
+    for file in files_info.iter() {
+        for filter in reqeust_generate.filters.iter_mut().chain(reqeust_generate.compare_filters.iter_mut().flatten()) {
+            if file.id == filter.id as usize {
+                filter.set_path_to_file(file.file_path.clone());
             }
         }
+    }
 
-        let report_type = match reqeust_generate.report_type {
-            None => ReportType::Unknown,
-            Some(report_item) => report_item
-        };
+    let report_type = reqeust_generate.report_type.unwrap_or(ReportType::UNKNOWN);
 
-        rp_tp.iter().for_each(|(id, file_type)| {
-            reqeust_generate.filters.iter_mut().for_each(|filter| {
-                if filter.id == **id as u32 {
-                    filter.set_type_of_report_we_depend(file_type.clone());
-                }
-            })
+    rp_tp.iter().for_each(|(id, file_type)| {
+        reqeust_generate.filters.iter_mut().chain(reqeust_generate.compare_filters.iter_mut().flatten()).for_each(|filter| {
+            if filter.id == **id as u32 {
+                filter.set_type_of_report_we_depend(file_type.clone());
+            }
+        })
+    });
+
+    // Период отчета для каталога, файлы прошлого периода сюда не попадают
+    let report_files = files_info.iter().filter(|file| all_filters_id.contains(&(file.id as u128)));
+    let period_from = report_files.clone().map(|file| file.from).min()
+        .map(|from| from.format(PERIOD_FORMAT).to_string())
+        .unwrap_or_default();
+    let period_to = report_files.map(|file| file.to).max()
+        .map(|to| to.format(PERIOD_FORMAT).to_string())
+        .unwrap_or_default();
+
+    let mut path_to_files = Vec::new();
+    let mut from_to: ReportsDateRange = Vec::new();
+
+    for file in files_info.iter().filter(|file| all_filters_id.contains(&(file.id as u128))) {
+        path_to_files.push(file.file_path.clone());
+        from_to.push((file.from_to_string(), file.to_to_string()));
+    }
+
+    all_filters_id.sort();
+    let build_id_for_name = all_filters_id.iter().map(|id| id.to_string()).collect::<Vec<String>>().join("");
+
+    // У пакетной генерации в ключе вместо организации список организаций
+    let key_organization = match reqeust_generate.organizations.as_ref() {
+        Some(organizations) => organizations.to_key_part(),
+        None => organization_provider_id.clone(),
+    };
+
+    let key = share.read().await.reports.initial_key(
+        &report_type,
+        key_organization.as_str(),
+        &from_to,
+        reqeust_generate.get_all_s_m_p(),
+        build_id_for_name.clone(),
+        &reqeust_generate.output_format,
+        reqeust_generate.key_expressions(),
+    );
+
+    let job_user_id = user_id.clone().unwrap_or("-1".to_string());
+
+    let (is_exist_file, file_path) = share.read().await.is_exist_file_report(
+        &key,
+        user_id.clone().unwrap_or("".to_string()).as_ref(),
+        reqeust_generate.result_extension(),
+        &settings
+    );
+
+    // Если файл уже существует то сразу возвращаем завершенную задачу с путем до файла
+    if is_exist_file {
+        let share_reader = share.read().await;
+        let job_id = share_reader.jobs.create_done_job(key.clone(), job_user_id, file_path.unwrap_or_default()).await;
+
+        return Json(Response::new(
+            None,
+            share_reader.jobs.get_job_view(&job_id).await.map(|(_, job)| job),
+            Some("job")
+        ));
+    }
+
+    let is_exist_report = share.read().await.is_exist_report(&key).await;
+
+    if share.read().await.get_number_simultaneous_generations() >= settings.max_num_of_simultaneous_generations_csv_in_xlsx {
+        let (code, message): ResponseError = (4325437, "Лимит одновременных генераций был превышен".to_string());
+        error!("code: {} message: {}", code, message);
+        return Json(Response::new::<String>(
+            Some((code, message)),
+            None,
+            None
+        ))
+    }
+
+    share.write().await.add_generation();
+
+    let job_id = share.read().await.jobs.create_job(key.clone(), job_user_id.clone()).await;
+    let job_view = share.read().await.jobs.get_job_view(&job_id).await.map(|(_, job)| job);
+
+    // Имя файла, размер и sha256 заполняются когда файл уже сохранен
+    let catalog_entry = user_id.as_ref().ok().map(|id| CatalogEntry {
+        key: key.clone(),
+        user_id: id.clone(),
+        report_type: report_type.name().to_string(),
+        output_format: reqeust_generate.output_format.to_key_part().to_string(),
+        organization: key_organization.clone(),
+        file_ids: all_filters_id.iter().map(|id| *id as u32).collect(),
+        filters: reqeust_generate.catalog_filters(),
+        period_from,
+        period_to,
+        ..Default::default()
+    });
+
+    let share_task = Data::clone(&share);
+    let settings_task = Data::clone(&settings);
+    let conn_db_task = Data::clone(&conn_db);
+    let job_id_task = job_id.clone();
+
+    let job = GenerationJob {
+        share: Data::clone(&share_task),
+        settings: Data::clone(&settings_task),
+        token,
+        all_filters_id,
+        user_info,
+        full_date_from_to,
+        key,
+        job_id: job_id_task.clone(),
+    };
+
+    // Генерация идет в фоне, HTTP запрос не держим открытым
+    actix_web::rt::spawn(async move {
+        // Генерация в своей задаче: если она упадет с паникой, счетчик генераций
+        // все равно уменьшится, а задача получит статус failed
+        let generation = actix_web::rt::spawn(async move {
+            if reqeust_generate.organizations.is_some() {
+                run_batch_generation_job(&job, &mut reqeust_generate, (from_to, build_id_for_name)).await
+            } else {
+                run_generation_job(&job, &mut reqeust_generate, organization_provider_id, is_exist_report).await
+            }
         });
 
-        let mut path_to_files = Vec::new();
-        let mut from_to: ReportsDateRange = Vec::new();
+        let generation_result = match generation.await {
+            Ok(generation_result) => generation_result,
+            Err(error) => Err((4325438, format!("Генерация отчета завершилась аварийно: {:?}", error))),
+        };
+
+        share_task.write().await.take_away_generation();
+        check_generated_hashes(Data::clone(&share_task)).await;
+
+        match generation_result {
+            Ok(path) => {
+                info!("Генерация отчета окончена для пользователя: {}", job_user_id);
+
+                if let Some(catalog_entry) = catalog_entry {
+                    add_to_catalog(&conn_db_task, &settings_task, catalog_entry, &path).await;
+                }
 
-        for file in files_info.iter() {
-            path_to_files.push(file.clone().1);
-            from_to.push((file.clone().4, file.clone().5));
+                share_task.read().await.jobs.set_done(job_id_task.as_str(), path).await;
+            }
+            Err(error) => {
+                info!("Не удалось сгенерировать файл для пользователя: {}", job_user_id);
+                error!("Message: {}, code: {}", error.1, error.0);
+                share_task.read().await.jobs.set_failed(job_id_task.as_str(), error).await;
+            }
         }
+    });
 
-        all_filters_id.sort();
-        let build_id_for_name = all_filters_id.iter().map(|id| id.to_string()).collect::<Vec<String>>().join("");
+    Json(Response::new(
+        None,
+        job_view,
+        Some("job")
+    ))
+}
 
-        let key = share.read().await.reports.initial_key(
-            &report_type,
-            organization_provider_id.as_str(),
-            &from_to,
-            reqeust_generate.get_all_s_m_p(),
-            build_id_for_name
-        );
+/// [Generation job] Общие данные задачи генерации, которые нужны и одиночной и пакетной генерации
+struct GenerationJob {
+    share: Data<TokioRwLock<Share>>,
+    settings: Data<Settings>,
+    token: String,
+    all_filters_id: Vec<u128>,
+    user_info: UserInfo,
+    full_date_from_to: Vec<(usize, String, String)>,
+    /// Ключ отчета в share
+    key: String,
+    job_id: String,
+}
 
-        let (is_exist_file, file_path) = share.read().await.is_exist_file_report(&key, user_id.clone().unwrap_or("".to_string()).as_ref());
+/// Добавляет сохраненный файл [path] в каталог отчетов.
+/// Ошибка каталога не ломает генерацию, файл уже сохранен и доступен по задаче
+async fn add_to_catalog(conn_db: &DatabasePool, settings: &Settings, mut catalog_entry: CatalogEntry, path: &str) {
+    catalog_entry.created_at = Utc::now().timestamp();
 
-        // Если файл уже существует то мы возвращаем к нему путь
-        if is_exist_file {
-            return Json(Response::new(
-                None,
-                Some(file_path),
-                Some("path")
-            ));
-        } else {
-            let is_exist_report = share.read().await.is_exist_report(&key).await;
+    let result = match catalog_entry.set_file(path).await {
+        Ok(()) => ReportCatalog::new(conn_db, settings).upsert(&catalog_entry).await,
+        Err(error) => Err(error),
+    };
 
-            if share.read().await.get_number_simultaneous_generations() >= dotenv!("MAX_NUM_OF_SIMULTANEOUS_GENERATIONS_CSV_IN_XLSX").parse::<u16>().unwrap_or(1000) {
-                let (code, message): ResponseError = (4325437, "Лимит одновременных генераций был превышен".to_string());
-                error!("code: {} message: {}", code, message);
-                return Json(Response::new::<String>(
-                    Some((code, message)),
-                    None,
-                    None
-                ))
+    if let Err(error) = result {
+        error!("Не удалось добавить отчет {} в каталог, code: {} message: {}", catalog_entry.key, error.0, error.1);
+    }
+}
+
+/// Чтение csv, агрегация чанков и сохранение отчета в [GenerateFile::output_format] для задачи [job_id].
+/// Возвращает путь до сгенерированного файла
+async fn run_generation_job(
+    job: &GenerationJob,
+    reqeust_generate: &mut GenerateFile,
+    organization_provider_id: String,
+    is_exist_report: bool,
+) -> Result<String, ResponseError> {
+    let (share, settings, user_info, job_id) = (&job.share, &job.settings, &job.user_info, job.job_id.as_str());
+
+    // Проверяем есть ли report в share
+    if is_exist_report {
+        let report_opt = share.read().await.reports.get_report(job.key.as_str()).await;
+
+        let report = match report_opt {
+            Some(report) => report,
+            None => return Err((8564791, "Не удалось получить отчет из share".to_string()))
+        };
+
+        share.read().await.jobs.set_running(job_id, Arc::clone(&report)).await;
+        report.write().await.set_provider_id(organization_provider_id.clone());
+
+        let previous = read_comparison_report(reqeust_generate, settings, user_info, &organization_provider_id).await?;
+
+        return generate_report_from_csv(
+            reqeust_generate,
+            Arc::clone(&report),
+            settings,
+            job.token.clone(),
+            job.all_filters_id.clone(),
+            user_info,
+            job.full_date_from_to.clone(),
+            Arc::clone(&GENERATED_HASHES),
+            job.key.clone(),
+            previous,
+        ).await;
+    }
+
+    let report_type = reqeust_generate.report_type.unwrap_or(ReportType::UNKNOWN);
+    let report = Arc::new(TokioRwLock::new(Report::new(report_type, organization_provider_id.clone())));
+
+    share.read().await.jobs.set_running(job_id, Arc::clone(&report)).await;
+
+    read_files_into_report(
+        Arc::clone(&report),
+        &mut reqeust_generate.filters,
+        settings,
+        user_info,
+        &organization_provider_id,
+        report_type,
+    ).await?;
+
+    let previous = read_comparison_report(reqeust_generate, settings, user_info, &organization_provider_id).await?;
+
+    let path = generate_report_from_csv(
+        reqeust_generate,
+        Arc::clone(&report),
+        settings,
+        job.token.clone(),
+        job.all_filters_id.clone(),
+        user_info,
+        job.full_date_from_to.clone(),
+        Arc::clone(&GENERATED_HASHES),
+        job.key.clone(),
+        previous,
+    ).await;
+
+    if let Err(error) = path {
+        return Err(error);
+    }
+
+    share.read().await.reports.insert_new_report(job.key.clone(), ArcMutexWrapper::new_arc_mutex_wrapper(report)).await;
+
+    path
+}
+
+/// Отчет прошлого периода по [GenerateFile::compare_filters], None если сравнение не запрошено.
+/// Отчет нужен только для сравнения и в share не сохраняется
+async fn read_comparison_report(
+    reqeust_generate: &mut GenerateFile,
+    settings: &Data<Settings>,
+    user_info: &UserInfo,
+    organization_provider_id: &str,
+) -> Result<Option<Arc<TokioRwLock<Report>>>, ResponseError> {
+    let report_type = reqeust_generate.report_type.unwrap_or(ReportType::UNKNOWN);
+
+    let compare_filters = match reqeust_generate.compare_filters.as_mut() {
+        Some(compare_filters) => compare_filters,
+        None => return Ok(None),
+    };
+
+    let report = Arc::new(TokioRwLock::new(Report::new(report_type, organization_provider_id.to_string())));
+
+    read_files_into_report(Arc::clone(&report), compare_filters, settings, user_info, organization_provider_id, report_type).await?;
+
+    Ok(Some(report))
+}
+
+/// Читает файлы фильтров [filters] и собирает по ним [report]
+async fn read_files_into_report(
+    report: Arc<TokioRwLock<Report>>,
+    filters: &mut Vec<Filter>,
+    settings: &Data<Settings>,
+    user_info: &UserInfo,
+    organization_provider_id: &str,
+    report_type: ReportType,
+) -> Result<(), ResponseError> {
+    let mut Provider_name = String::from("");
+
+    let column_mapping = ColumnMapping::load(&settings.column_mapping_path)?;
+
+    let chunk_sources_res = create_chunk_sources_by_types(filters, &column_mapping);
+
+    let chunk_sources = match chunk_sources_res {
+        Ok(result) => result,
+        Err(errors) => {
+            for error in errors.iter() {
+                let file_id = filters.iter().map(|filter| filter.id).collect::<Vec<u32>>();
+                error!("user_id: {}\nfile_id: {:?}\nerror: {:?}", UserInfo::get_pub_fields(&user_info.id), file_id, error);
             }
 
-            share.write().await.add_generation();
+            return Err(get_first_error_message_and_code(&errors));
+        }
+    };
 
-            // Проверяем есть ли report в share
-            if is_exist_report {
-                let report_opt = share.read().await.reports.get_report(key.as_str()).await;
+    for chunk_source in chunk_sources {
+        // csv читается в отдельном потоке, чанки приходят по мере чтения файла
+        let chunks = stream_chunks_for_share(
+            chunk_source.reader,
+            organization_provider_id.to_string(),
+            chunk_source.filter.clone(),
+            chunk_source.index_collection.clone(),
+            report_type
+        );
 
-                let report_rw_lock = match report_opt {
-                    Some(report) => Ok(report),
-                    None => {
-                        share.write().await.take_away_generation();
-                        Err((8564791, "Не удалось получить отчет из share".to_string()))
-                    }
-                };
+        let number_of_chunks = Share::processing_chunks(
+            Arc::clone(&report),
+            chunks,
+            &mut Provider_name,
+            chunk_source.filter,
+            &chunk_source.index_collection,
+            &report_type
+        ).await;
 
-                if let Err(error) = report_rw_lock {
-                    error!("{}. {}", error.0, error.1);
-                    return Json(Response::new::<String>(
-                        Some(error),
-                        None,
-                        None
-                    ));
-                }
+        if let Err(error) = number_of_chunks {
+            return Err(error);
+        }
 
-                let report = report_rw_lock.unwrap();
-                report.write().await.set_provider_id(organization_provider_id);
-
-                match generate_report_from_csv(
-                    &mut reqeust_generate,
-                    Arc::clone(&report),
-                    &settings,
-                    token.clone(),
-                    all_filters_id,
-                    &user_info,
-                    full_date_from_to,
-                    Arc::clone(&GENERATED_HASHES),
-                    key.clone(),
-                ).await {
-                    Ok(path) => {
-                        share.write().await.take_away_generation();
-                        check_generated_hashes(Data::clone(&share)).await;
-
-                        if let Err(_) = &user_id {
-                            info!("Генерация отчета окончена для пользователя: -1");
-                        } else {
-                            let id = user_id.unwrap();
-                            info!("Генерация отчета окончена для пользователя: {}", id);
-                        }
-
-                        Json(Response::new(
-                            None,
-                            Some(path),
-                            Some("path")
-                        ))
-                    }
-                    Err(error) => {
-                        share.write().await.take_away_generation();
-                        Json(Response::new::<String>(
-                            Some(error),
-                            None,
-                            None
-                        ))
-                    }
-                }
-            } else {
-                let report = Arc::new(TokioRwLock::new(Report::new(report_type, organization_provider_id.clone())));
-                let mut Provider_name = String::from("");
-
-                let report_type = reqeust_generate.report_type.unwrap_or(ReportType::Unknown);
-
-                let link_filters_request = &mut reqeust_generate.filters;
-
-                let chunks_create_res = create_chunks_by_types(
-                    link_filters_request,
-                    organization_provider_id.clone(),
-                    report_type
-                );
-
-                let chunks = match chunks_create_res {
-                    Ok(result) => result,
-                    Err(errors) => {
-                        for error in errors.iter() {
-                            let file_id = reqeust_generate.filters.iter().map(|filter| filter.id).collect::<Vec<u32>>();
-                            error!("user_id: {}\nfile_id: {:?}\nerror: {:?}", user_id.clone().unwrap_or("-1".to_string()), file_id, error);
-                        }
-
-                        share.write().await.take_away_generation();
-                        return Json(Response::new::<String>(
-                            Some(get_first_error_message_and_code(&errors)),
-                            None,
-                            None
-                        ));
-                    }
-                };
+        // Проверяем есть ли в файле нужные данные
+        if let Err(error) = chunk_is_empty(number_of_chunks.unwrap(), chunk_source.filter.id) {
+            return Err(error);
+        }
+    }
 
-                for (_, chunks, filter, index_collection) in chunks {
-                    // Проверяем есть ли пустые чанки
-                    let error_checking_result = match chunk_is_empty(&chunks, filter.id) {
-                        Err(error) => {
-                            Err(Json(Response::new(
-                                None,
-                                Some(error),
-                                None
-                            )))
-                        }
-                        Ok(_) => Ok(())
-                    };
-
-                    if let Err(error) = error_checking_result {
-                        share.write().await.take_away_generation();
-                        return error;
-                    }
-
-                    let working_with_report = Share::processing_chunks(
-                        Arc::clone(&report),
-                        chunks,
-                        &mut Provider_name,
-                        filter,
-                        &index_collection,
-                        &report_type
-                    ).await;
-
-                    if let Err(error) = working_with_report {
-                        share.write().await.take_away_generation();
-
-                        return Json(Response::new::<String>(
-                            Some(error),
-                            None,
-                            None
-                        ));
-                    }
-                }
+    Ok(())
+}
 
-                match generate_report_from_csv(
-                    &mut reqeust_generate,
-                    Arc::clone(&report),
-                    &settings,
-                    token,
-                    all_filters_id,
-                    &user_info,
-                    full_date_from_to,
-                    Arc::clone(&GENERATED_HASHES),
-                    key.clone(),
-                ).await {
-                    Ok(path) => {
-                        share.write().await.take_away_generation();
-
-                        check_generated_hashes(Data::clone(&share)).await;
-
-                        if let Err(_) = &user_id {
-                            info!("Генерация отчета окончена для пользователя: -1");
-                        } else {
-                            let id = user_id.unwrap();
-                            info!("Генерация отчета окончена для пользователя: {}", id);
-                        }
-                        let share_writer = share.write().await;
-
-                        share_writer.reports.insert_new_report(key.clone(), ArcMutexWrapper::new_arc_mutex_wrapper(report)).await;
-                        // share_writer.take_away_generation();
-                        Json(Response::new(
-                            None,
-                            Some(path),
-                            Some("path")
-                        ))
-                    },
-                    Err(error) => {
-                        share.write().await.take_away_generation();
-
-                        for key in share.read().await.reports.get_keys().await.iter() {
-                            GENERATED_HASHES.write().await.retain(|item| item != key)
-                        }
-
-                        let id = match user_id.clone() {
-                            Ok(id) => id,
-                            Err(_) => "-1".to_string()
-                        };
-
-                        info!("Не удалось сгенерировать файл для пользователя: {}", id);
-                        error!("Message: {}, code: {}", error.1, error.0);
-
-                        // share.write().await.take_away_generation();
-                        Json(Response::new::<String>(
-                            Some(error),
-                            None,
-                            None
-                        ))
-                    }
-                }
+/// Пакетная генерация для задачи [job_id]: каждый csv читается один раз,
+/// строки раскладываются по организациям, для каждой организации сохраняется свой отчет
+/// с тем же ключом что и при генерации только по ней. [key_parts] это периоды файлов и id файлов для этих ключей.
+/// Возвращает путь до zip архива с отчетами организаций и индексной книгой
+async fn run_batch_generation_job(
+    job: &GenerationJob,
+    reqeust_generate: &mut GenerateFile,
+    key_parts: (ReportsDateRange, String),
+) -> Result<String, ResponseError> {
+    let (share, settings, user_info, job_id) = (&job.share, &job.settings, &job.user_info, job.job_id.as_str());
+
+    let report_type = reqeust_generate.report_type.unwrap_or(ReportType::UNKNOWN);
+    let kind = report_type.kind()?;
+    let (from_to, build_id_for_name) = key_parts;
+
+    let organizations = match reqeust_generate.organizations.as_ref() {
+        Some(organizations) => organizations.ids(),
+        None => return Err((7357584, "Для пакетной генерации не переданы организации".to_string())),
+    };
+
+    share.read().await.jobs.set_running_batch(job_id).await;
+
+    let mut batch = BatchReports::new(report_type, organizations, settings.max_organizations_in_batch);
+
+    let column_mapping = ColumnMapping::load(&settings.column_mapping_path)?;
+
+    let chunk_sources = match create_chunk_sources_by_types(&mut reqeust_generate.filters, &column_mapping) {
+        Ok(result) => result,
+        Err(errors) => {
+            for error in errors.iter() {
+                let file_id = reqeust_generate.filters.iter().map(|filter| filter.id).collect::<Vec<u32>>();
+                error!("user_id: {}\nfile_id: {:?}\nerror: {:?}", UserInfo::get_pub_fields(&user_info.id), file_id, error);
             }
+
+            return Err(get_first_error_message_and_code(&errors));
         }
-    } else {
-        let error = (4324323, "По переданным id не возможно сгенерировать файл".to_string());
-        error!("code: {} message: {}", error.0, error.1);
-        return Json(Response::new::<String>(
-            Some(error),
+    };
+
+    let files_count = chunk_sources.len();
+
+    for (file_index, chunk_source) in chunk_sources.into_iter().enumerate() {
+        // Фильтр по организации не нужен, строки всех организаций раскладываются в [BatchReports]
+        let chunks = stream_chunks_for_share(
+            chunk_source.reader,
+            "".to_string(),
+            chunk_source.filter.clone(),
+            chunk_source.index_collection.clone(),
+            report_type
+        );
+
+        let number_of_chunks = batch.processing_chunks(
+            &share,
+            job_id,
+            chunks,
+            chunk_source.filter,
+            &chunk_source.index_collection,
+            (file_index, files_count)
+        ).await?;
+
+        // Проверяем есть ли в файле нужные данные
+        chunk_is_empty(number_of_chunks, chunk_source.filter.id)?;
+    }
+
+    if batch.is_empty() {
+        return Err((7357585, "В файлах нет транзакций ни одной из переданных организаций".to_string()));
+    }
+
+    let organization_ids = batch.organization_ids();
+    let mut entries: Vec<BatchEntry> = Vec::new();
+
+    for (index, organization_id) in organization_ids.iter().enumerate() {
+        let report = match batch.get_report(organization_id) {
+            Some(report) => report,
+            None => {
+                entries.push(BatchEntry::not_found(organization_id));
+                continue;
+            }
+        };
+
+        // Ключ такой же как у отчета только по этой организации
+        let organization_key = share.read().await.reports.initial_key(
+            &report_type,
+            organization_id.as_str(),
+            &from_to,
+            reqeust_generate.get_all_s_m_p(),
+            build_id_for_name.clone(),
+            &reqeust_generate.output_format,
+            reqeust_generate.key_expressions(),
+        );
+
+        let path = generate_report_from_csv(
+            reqeust_generate,
+            Arc::clone(&report),
+            settings,
+            job.token.clone(),
+            job.all_filters_id.clone(),
+            user_info,
+            job.full_date_from_to.clone(),
+            Arc::clone(&GENERATED_HASHES),
+            organization_key.clone(),
             None,
-            None
-        ))
+        ).await;
+
+        // Ошибка одной организации не останавливает генерацию, она попадает в индекс
+        match path.as_ref() {
+            Ok(_) => {
+                share.read().await.reports.insert_new_report(
+                    organization_key,
+                    ArcMutexWrapper::new_arc_mutex_wrapper(Arc::clone(&report))
+                ).await;
+            }
+            Err(error) => error!("Организация: {} code: {} message: {}", organization_id, error.0, error.1),
+        }
+
+        entries.push(BatchEntry::from_report(organization_id, &report, kind, path).await);
+
+        let percent = BATCH_READ_PERCENT + (index + 1) as f64 / organization_ids.len() as f64 * (100.0 - BATCH_READ_PERCENT);
+        share.read().await.jobs.set_percent_load(job_id, percent).await;
     }
-}
\ No newline at end of file
+
+    // Если не собрался ни один отчет, то архив не нужен
+    if entries.iter().all(|entry| entry.path.is_none()) {
+        return Err(entries
+            .into_iter()
+            .find_map(|entry| entry.error)
+            .unwrap_or((7357585, "В файлах нет транзакций ни одной из переданных организаций".to_string())));
+    }
+
+    let user_id = UserInfo::check_on_error(UserInfo::get_pub_fields(&user_info.id))?;
+
+    save_batch_archive(
+        job.key.as_str(),
+        &entries,
+        &reqeust_generate.output_format,
+        settings.get_currency_rule(),
+        user_id,
+        settings,
+    )
+}
@@ -1,14 +1,14 @@
 use crate::args::Settings;
-use crate::error::error_response::map_io_error;
 use crate::helper::file_struct::FilePath;
 use actix_web::web::{Data, Json};
 use actix_web::{web, Responder, HttpRequest, ResponseError};
-use std::fs;
 use crate::api_server::api_requests::RpcRequest;
 use crate::api_server::response_handlers::resp_user::handlers_user::handler_user_info;
 use crate::helper::user_info::user::UserInfo;
 use crate::r#trait::automated_report_response::Response;
 
+/// [Get Request] Размер отчета в json. Оставлен для старых клиентов,
+/// тот же размер приходит в Content-Length на HEAD /download/{path}
 pub async fn get_file_weight(
     req: HttpRequest,
     path_to_file: web::Path<FilePath>,
@@ -38,21 +38,15 @@ pub async fn get_file_weight(
 
     let user_id = UserInfo::get_pub_fields(&user_info.unwrap().id);
 
-    let file_path = FilePath::get_path(&path_to_file.path, settings, user_id);
-
-    let metadata = match fs::metadata(file_path) {
-        Ok(file) => Ok(file.len()),
-        Err(error) => Err(map_io_error(error)),
-    };
+    // Размер отдается только для файла из папки пользователя, как и при скачивании
+    let metadata = path_to_file.resolve(&settings, &user_id).map(|report_file| report_file.size);
 
     return if let Err(error) = metadata {
-        let error_response = error.error_response().status();
-
-        let status = error_response.to_string();
+        let status = error.error_response().status();
 
         Json(
             Response::new::<String>(
-                Some((status.parse::<i32>().unwrap_or(404), error_response.to_string())),
+                Some((status.as_u16() as i32, status.to_string())),
                 None,
                 None
             )
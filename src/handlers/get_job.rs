@@ -0,0 +1,60 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpRequest, Responder};
+
+use serde::Deserialize;
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use crate::api_server::api_token::utils::format_utils::token_utils::{handle_token_error, token_format_to_string};
+use crate::helper::user_info::user::UserInfo;
+use crate::r#trait::automated_report_response::Response;
+use crate::server::tokens_storage::TokensStorage;
+use crate::share::report_job::ReportJobView;
+use crate::share::Share;
+
+#[derive(Debug, Deserialize)]
+pub struct JobPath {
+    pub id: String,
+}
+
+/// [Get Request] Получить статус задачи генерации отчета
+pub async fn get_job(
+    req: HttpRequest,
+    job_path: web::Path<JobPath>,
+    share: Data<TokioRwLock<Share>>,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+) -> impl Responder {
+    let token_res = token_format_to_string(req.headers());
+
+    if let Some(error) = handle_token_error(&token_res) {
+        return error;
+    }
+
+    let user_info = token_storage.read().await.request_is_exist_token(&token_res.unwrap()).await.1;
+
+    if let Err(error) = user_info {
+        return Json(Response::new::<ReportJobView>(
+            Some(error),
+            None,
+            None
+        ));
+    }
+
+    let user_id = UserInfo::get_pub_fields(&user_info.unwrap().id);
+
+    let job = share.read().await.jobs.get_job_view(job_path.id.as_str()).await;
+
+    match job {
+        // Задачу видит только тот пользователь, который её запустил
+        Some((owner_id, job)) if owner_id == user_id => Json(Response::new(
+            None,
+            Some(job),
+            Some("job")
+        )),
+        _ => Json(Response::new::<ReportJobView>(
+            Some((1334310, format!("Задача {} не найдена", job_path.id))),
+            None,
+            None
+        )),
+    }
+}
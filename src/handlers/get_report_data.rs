@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpRequest, Responder};
+
+use serde::Deserialize;
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use crate::api_server::api_token::utils::format_utils::token_utils::{handle_token_error, token_format_to_string};
+use crate::args::Settings;
+use crate::helper::user_info::user::UserInfo;
+use crate::r#trait::automated_report_response::Response;
+use crate::r#type::types::ResponseError;
+use crate::server::tokens_storage::TokensStorage;
+use crate::share::report_data::{ReconciliationData, ReportData};
+use crate::share::{Report, Share};
+
+#[derive(Debug, Deserialize)]
+pub struct ReportDataPath {
+    pub key: String,
+}
+
+/// [Get Request] Получить агрегированные данные отчета в json, без генерации файла
+pub async fn get_report_data(
+    req: HttpRequest,
+    report_path: web::Path<ReportDataPath>,
+    share: Data<TokioRwLock<Share>>,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+    settings: Data<Settings>,
+) -> impl Responder {
+    let token_res = token_format_to_string(req.headers());
+
+    if let Some(error) = handle_token_error(&token_res) {
+        return error;
+    }
+
+    let key = report_path.key.as_str();
+
+    let report = match get_owned_report(token_res.unwrap(), key, &share, &token_storage).await {
+        Ok(report) => report,
+        Err(error) => return Json(Response::new::<ReportData>(
+            Some(error),
+            None,
+            None
+        )),
+    };
+
+    let report_reader = report.read().await;
+
+    let report_data = ReportData::from_report(key, &report_reader, settings.get_currency_rule());
+
+    Json(Response::new(
+        None,
+        Some(report_data),
+        Some("report")
+    ))
+}
+
+/// [Get Request] Получить сводку сверки пополнений счета с суммой транзакций в json
+pub async fn get_report_reconciliation(
+    req: HttpRequest,
+    report_path: web::Path<ReportDataPath>,
+    share: Data<TokioRwLock<Share>>,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+    settings: Data<Settings>,
+) -> impl Responder {
+    let token_res = token_format_to_string(req.headers());
+
+    if let Some(error) = handle_token_error(&token_res) {
+        return error;
+    }
+
+    let key = report_path.key.as_str();
+
+    let report = match get_owned_report(token_res.unwrap(), key, &share, &token_storage).await {
+        Ok(report) => report,
+        Err(error) => return Json(Response::new::<ReconciliationData>(
+            Some(error),
+            None,
+            None
+        )),
+    };
+
+    let report_reader = report.read().await;
+
+    let reconciliation = match report_reader.get_reconciliation() {
+        Some(reconciliation) => ReconciliationData::from_reconciliation(reconciliation, settings.get_currency_rule()),
+        None => return Json(Response::new::<ReconciliationData>(
+            Some((1334322, format!("Для отчета {} не запрашивалась сверка пополнений", key))),
+            None,
+            None
+        )),
+    };
+
+    Json(Response::new(
+        None,
+        Some(reconciliation),
+        Some("reconciliation")
+    ))
+}
+
+/// Собранный отчет [key], если его генерацию запускал владелец токена [token]
+async fn get_owned_report(
+    token: String,
+    key: &str,
+    share: &Data<TokioRwLock<Share>>,
+    token_storage: &Data<TokioRwLock<TokensStorage>>,
+) -> Result<Arc<TokioRwLock<Report>>, ResponseError> {
+    let user_info = token_storage.read().await.request_is_exist_token(&token).await.1?;
+
+    let user_id = UserInfo::get_pub_fields(&user_info.id);
+
+    let share_reader = share.read().await;
+
+    // Данные видит только тот пользователь, который запускал генерацию этого отчета
+    if !share_reader.jobs.is_key_owner(key, &user_id).await {
+        return Err((1334320, format!("Отчет {} не найден", key)));
+    }
+
+    let report = share_reader.get_processed_report(key).await?;
+
+    drop(share_reader);
+
+    if !report.read().await.is_report_read {
+        return Err((1334321, format!("Отчет {} еще собирается", key)));
+    }
+
+    Ok(report)
+}
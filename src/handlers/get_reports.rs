@@ -0,0 +1,47 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpRequest, Responder};
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use crate::api_server::api_token::utils::format_utils::token_utils::{handle_token_error, token_format_to_string};
+use crate::args::Settings;
+use crate::db::report_catalog::{CatalogPage, CatalogQuery, ReportCatalog};
+use crate::helper::user_info::user::UserInfo;
+use crate::r#trait::automated_report_response::Response;
+use crate::r#type::types::DatabasePool;
+use crate::server::tokens_storage::TokensStorage;
+
+/// [Get Request] Сгенерированные отчеты пользователя из каталога, новые первыми.
+/// Каталог хранится в базе данных, поэтому отчеты видны и после перезапуска сервиса
+pub async fn get_reports(
+    req: HttpRequest,
+    catalog_query: web::Query<CatalogQuery>,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+    conn_db: DatabasePool,
+    settings: Data<Settings>,
+) -> impl Responder {
+    let token_res = token_format_to_string(req.headers());
+
+    if let Some(error) = handle_token_error(&token_res) {
+        return error;
+    }
+
+    if let Err(error) = catalog_query.check() {
+        return Json(Response::new::<CatalogPage>(Some(error), None, None));
+    }
+
+    let user_info = match token_storage.read().await.request_is_exist_token(&token_res.unwrap()).await.1 {
+        Ok(user_info) => user_info,
+        Err(error) => return Json(Response::new::<CatalogPage>(Some(error), None, None)),
+    };
+
+    let user_id = match UserInfo::check_on_error(UserInfo::get_pub_fields(&user_info.id)) {
+        Ok(user_id) => user_id,
+        Err(error) => return Json(Response::new::<CatalogPage>(Some(error), None, None)),
+    };
+
+    match ReportCatalog::new(&conn_db, &settings).list(&user_id, &catalog_query).await {
+        Ok(page) => Json(Response::new(None, Some(page), Some("reports"))),
+        Err(error) => Json(Response::new::<CatalogPage>(Some(error), None, None)),
+    }
+}
@@ -1,7 +1,12 @@
+pub mod download_link;
 pub mod download_report;
 pub mod generate_report;
 pub mod get_file_weight;
 pub mod get_share;
 pub mod not_found;
+pub mod pin_report;
 pub mod cryptography;
-pub mod get_generated_hashes;
\ No newline at end of file
+pub mod get_generated_hashes;
+pub mod get_job;
+pub mod get_report_data;
+pub mod get_reports;
\ No newline at end of file
@@ -0,0 +1,88 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, HttpRequest, Responder};
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use crate::api_server::api_token::utils::format_utils::token_utils::{handle_token_error, token_format_to_string};
+use crate::args::Settings;
+use crate::db::report_catalog::ReportCatalog;
+use crate::helper::user_info::user::UserInfo;
+use crate::r#trait::automated_report_response::Response;
+use crate::r#type::types::DatabasePool;
+use crate::server::tokens_storage::TokensStorage;
+
+#[derive(Debug, Deserialize)]
+pub struct PinPath {
+    /// Имя файла отчета из каталога, `file_name` в GET /reports
+    pub file_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PinView {
+    pub file_name: String,
+    pub pinned: bool,
+}
+
+/// [Post Request] Закрепить отчет, закрепленный отчет не удаляется при очистке диска
+pub async fn pin_report(
+    req: HttpRequest,
+    pin_path: web::Path<PinPath>,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+    conn_db: DatabasePool,
+    settings: Data<Settings>,
+) -> impl Responder {
+    set_pinned(req, pin_path.into_inner(), true, token_storage, conn_db, settings).await
+}
+
+/// [Delete Request] Открепить отчет, после этого он удаляется по общим правилам
+pub async fn unpin_report(
+    req: HttpRequest,
+    pin_path: web::Path<PinPath>,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+    conn_db: DatabasePool,
+    settings: Data<Settings>,
+) -> impl Responder {
+    set_pinned(req, pin_path.into_inner(), false, token_storage, conn_db, settings).await
+}
+
+async fn set_pinned(
+    req: HttpRequest,
+    pin_path: PinPath,
+    pinned: bool,
+    token_storage: Data<TokioRwLock<TokensStorage>>,
+    conn_db: DatabasePool,
+    settings: Data<Settings>,
+) -> Json<Response> {
+    let token_res = token_format_to_string(req.headers());
+
+    if let Some(error) = handle_token_error(&token_res) {
+        return error;
+    }
+
+    let user_info = match token_storage.read().await.request_is_exist_token(&token_res.unwrap()).await.1 {
+        Ok(user_info) => user_info,
+        Err(error) => return Json(Response::new::<PinView>(Some(error), None, None)),
+    };
+
+    let user_id = match UserInfo::check_on_error(UserInfo::get_pub_fields(&user_info.id)) {
+        Ok(user_id) => user_id,
+        Err(error) => return Json(Response::new::<PinView>(Some(error), None, None)),
+    };
+
+    // Закрепить можно только свой отчет из каталога
+    match ReportCatalog::new(&conn_db, &settings).set_pinned(&user_id, &pin_path.file_name, pinned).await {
+        Ok(true) => Json(Response::new(
+            None,
+            Some(PinView { file_name: pin_path.file_name, pinned }),
+            Some("report")
+        )),
+        Ok(false) => Json(Response::new::<PinView>(
+            Some((2217040, format!("Отчет {} не найден в каталоге", pin_path.file_name))),
+            None,
+            None
+        )),
+        Err(error) => Json(Response::new::<PinView>(Some(error), None, None)),
+    }
+}
@@ -3,7 +3,6 @@ use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, SystemTime};
 
 use csv::StringRecord;
-use dotenv_codegen::dotenv;
 use actix_web::http::header::{HeaderMap, HeaderValue};
 use actix_web::web::Data;
 use crate::api_server::api_requests::RpcRequest;
@@ -14,11 +13,18 @@ use crate::r#type::types::ResponseError;
 
 pub mod create_file;
 pub mod date;
+pub mod download_link;
 pub mod file_struct;
+pub mod generate_batch;
+pub mod generate_csv;
+pub mod generate_pdf;
 pub mod generate_xlsx;
 pub mod handler_info_about_file_by_id;
-pub mod xlsx_help_fun;
-pub mod build_tasks;
+pub mod hex;
+pub mod period_comparison;
+pub mod reconciliation;
+pub mod report_template;
+pub mod retention;
 pub mod working_with_xlsx_list;
 pub mod chunks;
 pub mod user_info;
@@ -61,7 +67,7 @@ pub fn type_report_that_generated(tp: i8) -> String {
         9 => "c2cuzcard",
         _ => "null",
     }
-    .to_lowercase()
+    .to_string()
 }
 
 pub fn from_string_record_to_vec<'a>(record: &'a StringRecord) -> Vec<&'a str> {
@@ -99,11 +105,7 @@ pub fn compare_user_id(user_id: &Option<String>, id_of_file_owner: Vec<&isize>)
 }
 
 pub fn is_exist_file(keys: &Vec<String>, user_id: isize, settings: Data<Settings>) -> Option<String> {
-    let report_dir = if settings.get_prod() {
-        dotenv!("PROD_REPORTS_DIR")
-    } else {
-        dotenv!("REPORTS_DIR")
-    };
+    let report_dir = settings.get_reports_dir();
 
     for key in keys {
         let full_path = format!("{}/reports/{}/{key}", report_dir, user_id);
@@ -2,39 +2,98 @@ pub mod creator_of_chunks {
     use std::fs::File;
     use actix_web::web::Json;
     use csv::Reader;
+    use tokio::sync::mpsc::{channel, Receiver};
     use tracing::{error, warn};
     use crate::error::errors_utils::err_utils::get_first_error_message_and_code;
     use crate::helper::from_string_record_to_vec;
     use crate::helper::generate_xlsx::create_workbook;
+    use crate::indexing_report_struct::column_mapping::ColumnMapping;
     use crate::indexing_report_struct::IndexingReport;
     use crate::r#trait::automated_report_response::Response;
     use crate::r#trait::filter_report::{Filter, ReportItemType, ReportType};
-    use crate::r#type::types::{ChunksInReport, ResponseError};
+    use crate::r#type::types::{RecordStrings, ResponseError};
 
     const CHUNK_SIZE: usize = 256;
+    /// Сколько готовых чанков может ждать агрегации.
+    /// В памяти одновременно держится не больше CHUNK_CHANNEL_CAPACITY + 2 чанков, независимо от размера файла
+    const CHUNK_CHANNEL_CAPACITY: usize = 4;
+
+    /// Чанк отфильтрованных строк из csv
+    #[derive(Debug)]
+    pub struct RecordsChunk {
+        /// [Index] Порядковый номер чанка в файле
+        pub index: usize,
+        /// [Records] Строки которые прошли [Filter::filter_validation]
+        pub records: Vec<RecordStrings>,
+        /// [Percent] Сколько процентов файла прочитано к моменту отправки чанка
+        pub percent: f64,
+    }
+
+    /// Канал из которого агрегатор забирает чанки
+    pub type ChunkReceiver = Receiver<Result<RecordsChunk, ResponseError>>;
+
+    /// Открытый csv файл с картой индексов, по которому будут читаться чанки
+    pub struct ChunkSource<'a> {
+        pub report_item_type: ReportItemType,
+        pub reader: Reader<File>,
+        pub filter: &'a Filter,
+        pub index_collection: IndexingReport,
+    }
 
-    pub fn build_chunks_for_share(
+    /// Запускает чтение csv в отдельном потоке и возвращает канал с чанками.
+    /// Канал ограничен, поэтому чтение ждет пока агрегатор не заберет очередной чанк
+    pub fn stream_chunks_for_share(
+        mut rdr: Reader<File>,
+        organization_provider_id: String,
+        filter: Filter,
+        collect_indexing: IndexingReport,
+        report_type: ReportType
+    ) -> ChunkReceiver {
+        let (sender, receiver) = channel(CHUNK_CHANNEL_CAPACITY);
+
+        tokio::task::spawn_blocking(move || {
+            let result = build_chunks_for_share(
+                &mut rdr,
+                organization_provider_id.as_str(),
+                &filter,
+                &collect_indexing,
+                &report_type,
+                // Если агрегатор перестал принимать чанки то и читать файл дальше нет смысла
+                |chunk| sender.blocking_send(Ok(chunk)).is_ok()
+            );
+
+            if let Err(error) = result {
+                let _ = sender.blocking_send(Err(error));
+            }
+        });
+
+        receiver
+    }
+
+    /// Читает csv построчно и отдает в [on_chunk] чанки по [CHUNK_SIZE] строк.
+    /// Возвращает количество отданных чанков
+    pub fn build_chunks_for_share<F>(
         rdr: &mut Reader<File>,
         organization_provider_id: &str,
         filter: &Filter,
         collect_indexing: &IndexingReport,
-        report_type: &ReportType
-    ) -> Result<ChunksInReport, ResponseError> {
+        report_type: &ReportType,
+        mut on_chunk: F
+    ) -> Result<usize, ResponseError>
+        where F: FnMut(RecordsChunk) -> bool
+    {
         let mut record_index: u128 = 0;
-        let mut record_for_share: Vec<String> = Vec::new();
+        let mut chunk_index: usize = 0;
 
-        let mut chunks: ChunksInReport = Vec::new();
         let mut chunk = Vec::with_capacity(CHUNK_SIZE);
 
-        let header = rdr
-            .headers()
-            .unwrap()
-            .iter()
-            .collect::<Vec<&str>>();
+        let header_len = match rdr.headers() {
+            Ok(header) => header.len(),
+            Err(error) => return Err((5435446, format!("Не удалось прочитать заголовок файла: {}", error)))
+        };
 
-        for _ in 0..header.len() {
-            record_for_share.push("".to_string());
-        }
+        // Размер файла нужен только для процента загруженности
+        let file_size = rdr.get_ref().metadata().map(|metadata| metadata.len()).unwrap_or(0);
 
         for result in rdr.records() {
             if let Ok(record) = result {
@@ -44,7 +103,6 @@ pub mod creator_of_chunks {
                     continue;
                 }
 
-                let last_index = record.len();
                 // Проверяем подходит ли нам строка по фильтрам
                 let filter_validation = filter.filter_validation(&record, &collect_indexing, organization_provider_id, report_type);
 
@@ -56,45 +114,53 @@ pub mod creator_of_chunks {
                     continue;
                 }
 
-                for (i, field) in record.iter().enumerate() {
-                    // Если это последний элемент в страке
-                    if i == last_index - 1 {
-                        record_for_share[i] = field.into();
-
-                        // ============================================================
-                        // Собираем chunks
-                        chunk.push(record_for_share.clone());
-
-                        // Если chunk достиг нужного размера,
-                        // То мы его пушим в "chunks"
-                        // И очищаем "chunk" для того что-бы заполнить его заново
-                        if chunk.len() == CHUNK_SIZE {
-                            chunks.push(std::mem::take(&mut chunk));
-                            chunk = Vec::with_capacity(CHUNK_SIZE);
-                        }
-                        // Очищаем каждое поле в "record_for_share"
-                        for j in 0..record_for_share.len() {
-                            record_for_share[j].clear();
-                        }
-                    } else {
-                        record_for_share[i] = field.into();
+                let mut record_for_share = record.iter().map(|field| field.to_string()).collect::<RecordStrings>();
+                // Короткие строки дополняем пустыми полями до длины заголовка
+                if record_for_share.len() < header_len {
+                    record_for_share.resize(header_len, "".to_string());
+                }
+
+                chunk.push(record_for_share);
+
+                // Если chunk достиг нужного размера,
+                // То мы его отдаем агрегатору
+                // И начинаем заполнять "chunk" заново
+                if chunk.len() == CHUNK_SIZE {
+                    let percent = read_percent(record.position().map(|position| position.byte()), file_size);
+
+                    if !on_chunk(RecordsChunk { index: chunk_index, records: std::mem::take(&mut chunk), percent }) {
+                        return Ok(chunk_index + 1);
                     }
+
+                    chunk_index += 1;
+                    chunk = Vec::with_capacity(CHUNK_SIZE);
                 }
             }
         }
 
-        is_empty_chunk(&mut chunks, &mut chunk);
+        if !chunk.is_empty() {
+            on_chunk(RecordsChunk { index: chunk_index, records: chunk, percent: 100.0 });
+            chunk_index += 1;
+        }
 
-        Ok(chunks)
+        Ok(chunk_index)
     }
 
-    pub fn create_chunks_by_types(
-        filters: &mut Vec<Filter>,
-        organization_provider_id: String,
-        generation_type: ReportType
-    ) -> Result<Vec<(ReportItemType, ChunksInReport, &Filter, IndexingReport)>, Vec<(i32, String)>> {
+    fn read_percent(position: Option<u64>, file_size: u64) -> f64 {
+        match position {
+            Some(position) if file_size > 0 => (position as f64 / file_size as f64 * 100.0).min(100.0),
+            _ => 0.0
+        }
+    }
+
+    /// Открывает csv файлы по фильтрам и собирает для каждого карту индексов.
+    /// Сами строки здесь не читаются, это делает [stream_chunks_for_share]
+    pub fn create_chunk_sources_by_types<'a>(
+        filters: &'a mut Vec<Filter>,
+        column_mapping: &ColumnMapping,
+    ) -> Result<Vec<ChunkSource<'a>>, Vec<(i32, String)>> {
         let mut errors = Vec::new();
-        let mut chunks_by_type_file: Vec<(ReportItemType, ChunksInReport, &Filter, IndexingReport)> = Vec::new();
+        let mut chunk_sources: Vec<ChunkSource> = Vec::new();
 
         for filter in filters.iter_mut() {
             if let Err(error) = filter.set_type_report_that_generated() {
@@ -112,109 +178,54 @@ pub mod creator_of_chunks {
 
             let mut index_collection = IndexingReport::new();
 
-            match filter.get_type_report_that_generated() {
-                None => {}
-                Some(rp) => {
-                    match rp {
-                        ReportItemType::Remittance => {
-                            let type_of_report_we_depend_opt = filter.type_of_report_we_depend.clone();
-                            let type_of_report_we_depend = type_of_report_we_depend_opt.unwrap_or("".to_string());
-
-                            match type_of_report_we_depend.as_str() {
-                                "c2card" | "c2cCOMANYNAME" => {
-                                    index_collection.find_index_by_name(
-                                        from_string_record_to_vec(rdr_chunks.headers().unwrap()),
-                                        ReportItemType::Remittance
-                                    );
-
-                                    // Проверка отсутвующих полей
-                                    if let Err(error) = index_collection.check_which_fields_not_found(ReportItemType::Remittance) {
-                                        errors.push(error);
-                                        warn!("Remittance не будут добавленны в отчет");
-                                        continue;
-                                    }
-
-                                    let chunks = build_chunks_for_share(
-                                        &mut rdr_chunks, organization_provider_id.as_str(),
-                                        filter, &index_collection,
-                                        &generation_type
-                                    );
-
-                                    if let Ok(chunk) = chunks {
-                                        chunks_by_type_file.push((ReportItemType::Remittance, chunk, filter, index_collection));
-                                    } else {
-                                        if let Err(error) = chunks {
-                                            errors.push(error);
-                                        }
-                                    }
-                                }
-                                _ => {
-                                    errors.push((
-                                        2321331,
-                                        format!("Тип файла {} не может быть обработан", type_of_report_we_depend)
-                                    ));
-                                }
-                            }
-                        }
-                        ReportItemType::Payments => {
-                            let type_of_report_we_depend_opt = filter.type_of_report_we_depend.clone();
-                            let type_of_report_we_depend = type_of_report_we_depend_opt.unwrap_or("".to_string());
-
-                            match type_of_report_we_depend.as_str() {
-                                "pay" | "pay_f" => {
-                                    index_collection.find_index_by_name(
-                                        from_string_record_to_vec(rdr_chunks.headers().unwrap()),
-                                        ReportItemType::Payments
-                                    );
-
-                                    // Проверка отсутвующих полей
-                                    if let Err(error) = index_collection.check_which_fields_not_found(ReportItemType::Payments) {
-                                        errors.push(error);
-                                        warn!("Payments не будут добавленны в отчет");
-                                        continue;
-                                    }
-
-                                    let chunks = build_chunks_for_share(
-                                        &mut rdr_chunks, organization_provider_id.as_str(),
-                                        filter, &index_collection,
-                                        &generation_type
-                                    );
-
-                                    if let Ok(chunk) = chunks {
-                                        chunks_by_type_file.push((ReportItemType::Payments, chunk, filter, index_collection));
-                                    } else {
-                                        if let Err(error) = chunks {
-                                            errors.push(error);
-                                        }
-                                    }
-                                }
-                                _ => {
-                                    errors.push((
-                                        2321331,
-                                        format!("Тип файла {} не может быть обработан", type_of_report_we_depend)
-                                    ));
-                                }
-                            }
-                        }
-                        _ => {
-                            errors.push((
-                                2321331,
-                                "Переданы данные с неизвестным типом транзакций, передайте пожалуйста платежи или переводы".to_string()
-                            ));
-                        }
+            let report_item_type = filter.get_type_report_that_generated().cloned().unwrap_or(ReportItemType::Unknown);
+
+            match report_item_type {
+                ReportItemType::Remittance | ReportItemType::Payments | ReportItemType::Terminal => {
+                    let type_of_report_we_depend = filter.type_of_report_we_depend.clone().unwrap_or("".to_string());
+
+                    // Колонки ищутся по типу файла, у каждого типа свой набор заголовков
+                    index_collection.find_index_by_name(
+                        from_string_record_to_vec(rdr_chunks.headers().unwrap()),
+                        type_of_report_we_depend.as_str(),
+                        column_mapping
+                    );
+
+                    // Проверка отсутвующих полей
+                    if let Err(error) = index_collection.check_which_fields_not_found(
+                        report_item_type.clone(),
+                        type_of_report_we_depend.as_str(),
+                        column_mapping
+                    ) {
+                        errors.push(error);
+                        warn!("{:?} ({}) не будут добавленны в отчет", report_item_type, type_of_report_we_depend);
+                        continue;
                     }
+
+                    chunk_sources.push(ChunkSource {
+                        report_item_type,
+                        reader: rdr_chunks,
+                        filter,
+                        index_collection
+                    });
+                }
+                _ => {
+                    errors.push((
+                        2321331,
+                        "Переданы данные с неизвестным типом транзакций, передайте пожалуйста платежи, переводы или терминалы".to_string()
+                    ));
                 }
             }
         }
 
-        return if !errors.is_empty() { Err(errors) } else { Ok(chunks_by_type_file) }
+        return if !errors.is_empty() { Err(errors) } else { Ok(chunk_sources) }
     }
 
     pub fn chunk_processing<'a>(
-        chunks_result: Result<Vec<(ReportItemType, ChunksInReport, &'a Filter, IndexingReport)>, Vec<(i32, String)>>,
+        chunks_result: Result<Vec<ChunkSource<'a>>, Vec<(i32, String)>>,
         filters: &mut Vec<Filter>,
         user_id: &str
-    ) -> Result<Vec<(ReportItemType, ChunksInReport, &'a Filter, IndexingReport)>, Json<Response>> {
+    ) -> Result<Vec<ChunkSource<'a>>, Json<Response>> {
         return match chunks_result {
             Ok(result) => Ok(result),
             Err(errors) => {
@@ -231,10 +242,4 @@ pub mod creator_of_chunks {
             }
         };
     }
-
-    pub fn is_empty_chunk(chunks: &mut ChunksInReport, chunk: &mut Vec<Vec<String>>) {
-        if !chunk.is_empty() {
-            chunks.push(std::mem::take(chunk));
-        }
-    }
 }
\ No newline at end of file
@@ -3,18 +3,14 @@ pub mod create_fs {
     use crate::r#trait::filter_report::{ReportType, Status};
     use crate::r#type::types::ReportsDateRange;
     use actix_web::web::Data;
-    use dotenv_codegen::dotenv;
     use std::path::Path;
     use tracing::info;
     use crate::handlers::cryptography::cryptography::generate_hash_key_for_report;
+    use crate::handlers::generate_report::TypeGenerateReport;
     use crate::helper::build_payment_filter_name;
 
     pub fn create_dir(settings: Data<Settings>, user_id: &String) -> Result<String, std::io::Error> {
-        let report_dir = if settings.get_prod() {
-            dotenv!("PROD_REPORTS_DIR")
-        } else {
-            dotenv!("REPORTS_DIR")
-        };
+        let report_dir = settings.get_reports_dir();
         let path_string = format!("{}/reports/{}", report_dir, user_id);
         let path = Path::new(&path_string);
 
@@ -32,28 +28,35 @@ pub mod create_fs {
         }
     }
 
-    pub fn create_file(path_to_dir: String, file_name: &str) -> String {
-        format!("{}/{}.xlsx", path_to_dir, file_name)
+    pub fn create_file(path_to_dir: String, file_name: &str, output_format: &TypeGenerateReport) -> String {
+        format!("{}/{}.{}", path_to_dir, file_name, output_format.extension())
     }
 
     pub fn create_file_name(
         report_type: &ReportType,
         organization_provider_id: &str,
         from_to: &ReportsDateRange,
-        status: &Vec<Status>,
-        modes: &Vec<String>,
+        status: &Vec<Vec<Status>>,
+        modes: &Vec<Vec<String>>,
         id: String,
-        payments_system: &Vec<Vec<String>>
+        payments_system: &Vec<Vec<String>>,
+        output_format: &TypeGenerateReport,
+        expressions: &Vec<String>,
     ) -> String {
-        let status_string_build = status.iter().map(|stat| stat.to_string()).collect::<Vec<String>>().join(" ");
-        let mode_build = modes.join(" ");
+        // Статусы и моды одного фильтра склеиваются через "+", фильтры через пробел
+        let status_string_build = status.iter()
+            .map(|statuses| statuses.iter().map(|stat| stat.to_string()).collect::<Vec<String>>().join("+"))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let mode_build = modes.iter().map(|modes| modes.join("+")).collect::<Vec<String>>().join(" ");
         let payments_system_build = build_payment_filter_name(payments_system);
 
         let get_hash_name = generate_hash_key_for_report(
             report_type, organization_provider_id,
             from_to, id,
             status_string_build, mode_build,
-            payments_system_build
+            payments_system_build, output_format.to_key_part(),
+            expressions.join(" "),
         );
 
         let hash_to_string = get_hash_name.0.iter()
@@ -1,31 +1,79 @@
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
 use crate::helper::generate_xlsx::MOUNTS_NUMBER;
+use crate::money::Money;
 use crate::share::ReportItem;
 
-/// EN generation of the month by day
-/// RU генерация месяца по дням
-pub fn days_in_month(year: i32, month: u32, day: u32) -> Option<Vec<NaiveDate>> {
-    if month > 12 {
-        return None;
+/// EN all months of the period
+/// RU Все месяцы (год, месяц) с месяца даты [from] по месяц даты [to] включительно.
+/// Даты в виде 2023-01-15 или 2023-01-15 12:30:00
+pub fn months_in_period(from: &str, to: &str) -> Vec<(i32, u32)> {
+    let (first, last) = match (month_of_date(from), month_of_date(to)) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+
+    let mut months = Vec::new();
+    let mut current = first;
+
+    while current <= last {
+        months.push(current);
+
+        current = match current {
+            (year, 12) => (year + 1, 1),
+            (year, month) => (year, month + 1),
+        };
     }
 
-    let mut days_in_month: Vec<NaiveDate> = Vec::new();
-
-    let first_day = NaiveDate::from_ymd_opt(year, month, day);
-    for day in first_day.unwrap().iter_days() {
-        if let Ok(mnt) = day.to_string().split("-").collect::<Vec<&str>>()[1].parse::<u32>() {
-            if month == 12 && mnt == 1 {
-                break;
-            }
-            if mnt == month + 1 {
-                break;
-            }
-        }
+    months
+}
+
+/// Год и месяц из даты 2023-01-15 (время после даты не учитывается)
+pub fn month_of_date(date: &str) -> Option<(i32, u32)> {
+    day_of_date(date).map(|date| (date.year(), date.month()))
+}
+
+/// День из даты 2023-01-15 или 2023-01-15 12:30:00
+pub fn day_of_date(date: &str) -> Option<NaiveDate> {
+    let date = date.trim().split(' ').next().unwrap_or("");
+
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Сумма за день [day] из сумм по дням (days_amount, commission_by_day ...), ноль если в этот день сумм нет
+pub(crate) fn find_day_money(days: &[(String, Money)], day: &str) -> Money {
+    days.iter()
+        .find(|(date, _)| date == day)
+        .map_or(Money::ZERO, |(_, money)| *money)
+}
+
+/// Название месяца по номеру: 1 - Январь
+pub fn month_name(month: u32) -> &'static str {
+    MOUNTS_NUMBER.iter()
+        .find(|mount| mount.0 == month as i32)
+        .map_or("", |mount| mount.1)
+}
 
-        days_in_month.push(day);
+/// Месяц словами: Январь 2023
+pub fn format_month(month: (i32, u32)) -> String {
+    format!("{} {}", month_name(month.1), month.0)
+}
+
+/// Период отчета словами: "Январь 2023", "Январь - Март 2023", "Декабрь 2022 - Февраль 2023"
+pub fn format_period(months: &[(i32, u32)]) -> String {
+    let (first, last) = match (months.first(), months.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return "".to_string(),
+    };
+
+    if first == last {
+        return format_month(first);
     }
 
-    Some(days_in_month)
+    if first.0 == last.0 {
+        return format!("{} - {}", month_name(first.1), format_month(last));
+    }
+
+    format!("{} - {}", format_month(first), format_month(last))
 }
 
 /// EN Get date from - to
@@ -95,4 +143,66 @@ pub fn get_date_for_general_taxi_compony_list(date: Vec<(usize, String, String)>
     };
 
     full_date
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_of_date_accepts_date_with_time() {
+        let day = NaiveDate::from_ymd_opt(2023, 1, 15);
+
+        assert_eq!(day_of_date("2023-01-15"), day);
+        assert_eq!(day_of_date("2023-01-15 12:30:00"), day);
+        assert_eq!(day_of_date(" 2023-01-15 "), day);
+        assert_eq!(month_of_date("2023-01-15 23:59:59"), Some((2023, 1)));
+    }
+
+    #[test]
+    fn day_of_date_rejects_malformed_dates() {
+        assert_eq!(day_of_date(""), None);
+        assert_eq!(day_of_date("15.01.2023"), None);
+        assert_eq!(day_of_date("2023-13-01"), None);
+        assert_eq!(day_of_date("2023-02-30"), None);
+        assert_eq!(day_of_date("январь"), None);
+        assert_eq!(month_of_date("2023-01"), None);
+    }
+
+    #[test]
+    fn months_in_single_month() {
+        assert_eq!(months_in_period("2023-01-01", "2023-01-31 23:59:59"), [(2023, 1)]);
+        assert_eq!(months_in_period("2023-01-15", "2023-01-15"), [(2023, 1)]);
+    }
+
+    #[test]
+    fn months_roll_over_december() {
+        assert_eq!(months_in_period("2022-11-20", "2023-02-01"), [(2022, 11), (2022, 12), (2023, 1), (2023, 2)]);
+    }
+
+    #[test]
+    fn months_over_several_years() {
+        let months = months_in_period("2021-12-01", "2024-01-31");
+
+        assert_eq!(months.len(), 26);
+        assert_eq!(months.first(), Some(&(2021, 12)));
+        assert_eq!(months[1], (2022, 1));
+        assert_eq!(months[13], (2023, 1));
+        assert_eq!(months.last(), Some(&(2024, 1)));
+    }
+
+    #[test]
+    fn months_of_reversed_or_malformed_period_are_empty() {
+        assert!(months_in_period("2023-03-01", "2023-01-01").is_empty());
+        assert!(months_in_period("2023-01-01", "not a date").is_empty());
+        assert!(months_in_period("", "2023-01-01").is_empty());
+    }
+
+    #[test]
+    fn format_period_in_words() {
+        assert_eq!(format_period(&[]), "");
+        assert_eq!(format_period(&[(2023, 1)]), "Январь 2023");
+        assert_eq!(format_period(&months_in_period("2023-01-01", "2023-03-31")), "Январь - Март 2023");
+        assert_eq!(format_period(&months_in_period("2022-12-01", "2023-02-28")), "Декабрь 2022 - Февраль 2023");
+        assert_eq!(format_period(&months_in_period("2021-05-01", "2024-05-01")), "Май 2021 - Май 2024");
+    }
+}
@@ -0,0 +1,212 @@
+use chrono::Utc;
+
+use serde::{Deserialize, Serialize};
+
+use sodiumoxide::crypto::auth::{self, Key, Tag};
+use sodiumoxide::crypto::hash::sha256;
+use sodiumoxide::randombytes::randombytes;
+
+use crate::args::DownloadLinkSettings;
+use crate::error::error_response::CustomError;
+use crate::helper::hex::{from_hex, to_hex};
+
+/// [Signed link] Параметры подписанной ссылки из query: /download/{path}?user=..&expires=..&nonce=..&signature=..
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignedLink {
+    /// [User] Id пользователя, в папке которого лежит отчет
+    pub user: String,
+    /// [Expires] Unix время до которого действует ссылка
+    pub expires: i64,
+    /// [Nonce] Есть только у одноразовой ссылки
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// [Signature] HMAC-SHA512-256 в hex
+    pub signature: String,
+}
+
+/// [Download link view] Выданная ссылка, то что отдаем пользователю
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadLinkView {
+    pub url: String,
+    pub expires_at: i64,
+    pub single_use: bool,
+}
+
+impl SignedLink {
+    /// Подписывает ссылку на файл [file_name] пользователя [user_id], одноразовая ссылка получает случайный nonce
+    pub fn new(settings: &DownloadLinkSettings, user_id: &str, file_name: &str, expires: i64, single_use: bool) -> SignedLink {
+        let nonce = if single_use { Some(to_hex(&randombytes(16))) } else { None };
+
+        let tag = auth::authenticate(
+            message(user_id, file_name, expires, nonce.as_deref()).as_bytes(),
+            &signing_key(&settings.secret),
+        );
+
+        SignedLink {
+            user: user_id.to_string(),
+            expires,
+            nonce,
+            signature: to_hex(&tag.0),
+        }
+    }
+
+    /// Ссылка для пользователя, с адресом сервиса если он задан в настройках
+    pub fn view(&self, settings: &DownloadLinkSettings, file_name: &str) -> DownloadLinkView {
+        let mut url = format!(
+            "{}/download/{}?user={}&expires={}",
+            settings.base_url.trim_end_matches('/'), file_name, self.user, self.expires
+        );
+
+        if let Some(nonce) = self.nonce.as_ref() {
+            url.push_str(&format!("&nonce={}", nonce));
+        }
+        url.push_str(&format!("&signature={}", self.signature));
+
+        DownloadLinkView {
+            url,
+            expires_at: self.expires,
+            single_use: self.nonce.is_some(),
+        }
+    }
+
+    /// Проверяет подпись и срок ссылки на файл [file_name].
+    /// Ссылка подписана на конкретный файл, с другим {path} подпись не совпадет
+    pub fn verify(&self, settings: &DownloadLinkSettings, file_name: &str) -> Result<(), CustomError> {
+        if settings.secret.is_empty() {
+            return Err(CustomError::Unauthorized);
+        }
+
+        if self.expires < Utc::now().timestamp() {
+            return Err(CustomError::LinkExpired);
+        }
+
+        let tag = from_hex(&self.signature)
+            .and_then(|bytes| Tag::from_slice(&bytes))
+            .ok_or(CustomError::Unauthorized)?;
+
+        let message = message(&self.user, file_name, self.expires, self.nonce.as_deref());
+
+        // verify сравнивает подпись за постоянное время
+        if !auth::verify(&tag, message.as_bytes(), &signing_key(&settings.secret)) {
+            return Err(CustomError::Unauthorized);
+        }
+
+        Ok(())
+    }
+}
+
+/// Ключ HMAC из ключа подписи в настройках
+fn signing_key(secret: &str) -> Key {
+    let digest = sha256::hash(secret.as_bytes());
+
+    Key::from_slice(&digest.0).expect("sha256 и ключ auth одной длины")
+}
+
+fn message(user_id: &str, file_name: &str, expires: i64, nonce: Option<&str>) -> String {
+    format!("{}\n{}\n{}\n{}", user_id, file_name, expires, nonce.unwrap_or(""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE_NAME: &str = "report_1.xlsx";
+
+    fn settings() -> DownloadLinkSettings {
+        DownloadLinkSettings {
+            secret: "secret".to_string(),
+            base_url: "https://reports.example".to_string(),
+            ttl_secs: 3600,
+            max_ttl_secs: 86400,
+        }
+    }
+
+    fn link(single_use: bool) -> SignedLink {
+        SignedLink::new(&settings(), "42", FILE_NAME, Utc::now().timestamp() + 60, single_use)
+    }
+
+    fn is_unauthorized(result: Result<(), CustomError>) -> bool {
+        matches!(result, Err(CustomError::Unauthorized))
+    }
+
+    #[test]
+    fn signed_link_is_valid() {
+        assert!(link(false).verify(&settings(), FILE_NAME).is_ok());
+        assert!(link(true).verify(&settings(), FILE_NAME).is_ok());
+    }
+
+    #[test]
+    fn tampered_user_is_rejected() {
+        let mut link = link(false);
+        link.user = "43".to_string();
+
+        assert!(is_unauthorized(link.verify(&settings(), FILE_NAME)));
+    }
+
+    #[test]
+    fn expired_link_is_rejected() {
+        let link = SignedLink::new(&settings(), "42", FILE_NAME, Utc::now().timestamp() - 1, false);
+
+        assert!(matches!(link.verify(&settings(), FILE_NAME), Err(CustomError::LinkExpired)));
+    }
+
+    #[test]
+    fn prolonged_link_is_rejected() {
+        let mut link = link(false);
+        link.expires += 3600;
+
+        assert!(is_unauthorized(link.verify(&settings(), FILE_NAME)));
+    }
+
+    #[test]
+    fn other_file_name_is_rejected() {
+        assert!(is_unauthorized(link(false).verify(&settings(), "report_2.xlsx")));
+    }
+
+    #[test]
+    fn other_secret_or_empty_secret_is_rejected() {
+        let mut other = settings();
+        other.secret = "other".to_string();
+        assert!(is_unauthorized(link(false).verify(&other, FILE_NAME)));
+
+        other.secret = String::new();
+        assert!(is_unauthorized(link(false).verify(&other, FILE_NAME)));
+    }
+
+    #[test]
+    fn malformed_signature_is_rejected() {
+        let mut link = link(false);
+        link.signature = "zz".to_string();
+
+        assert!(is_unauthorized(link.verify(&settings(), FILE_NAME)));
+    }
+
+    /// Повтор того же nonce отсекает UsedLinks::use_once, а подпись не дает обойти его
+    /// заменой или удалением nonce у уже использованной ссылки
+    #[test]
+    fn used_nonce_cannot_be_replaced() {
+        let used = link(true);
+        let fresh = link(true);
+        assert_ne!(used.nonce, fresh.nonce);
+
+        let mut swapped = used.clone();
+        swapped.nonce = fresh.nonce.clone();
+        assert!(is_unauthorized(swapped.verify(&settings(), FILE_NAME)));
+
+        let mut without_nonce = used.clone();
+        without_nonce.nonce = None;
+        assert!(is_unauthorized(without_nonce.verify(&settings(), FILE_NAME)));
+    }
+
+    #[test]
+    fn view_contains_signed_query() {
+        let link = link(true);
+        let view = link.view(&settings(), FILE_NAME);
+
+        assert_eq!(view.url, format!(
+            "https://reports.example/download/{}?user=42&expires={}&nonce={}&signature={}",
+            FILE_NAME, link.expires, link.nonce.as_ref().unwrap(), link.signature
+        ));
+        assert!(view.single_use);
+    }
+}
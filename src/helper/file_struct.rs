@@ -1,20 +1,110 @@
-use crate::args::Settings;
-use actix_web::web::Data;
-use dotenv_codegen::dotenv;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix_web::http::header::EntityTag;
+
 use serde::Deserialize;
 
+use crate::args::Settings;
+use crate::error::error_response::{map_io_error, CustomError};
+
+/// Расширения сохраненных отчетов: xlsx, csv (zip архив) и pdf
+pub const REPORT_EXTENSIONS: [&str; 3] = ["xlsx", "zip", "pdf"];
+
 #[derive(Debug, Deserialize)]
 pub struct FilePath {
+    /// Id отчета (ключ из задачи генерации) с расширением или без него
     pub path: String,
 }
 
+/// [Report file] Файл отчета пользователя, путь до которого уже проверен
+#[derive(Debug, Clone)]
+pub struct ReportFile {
+    pub path: PathBuf,
+    /// Имя файла для Content-Disposition
+    pub file_name: String,
+    pub size: u64,
+    /// Время последнего изменения файла, для Last-Modified и If-Range
+    pub modified: SystemTime,
+}
+
+impl ReportFile {
+    pub fn content_type(&self) -> &'static str {
+        match self.path.extension().and_then(|extension| extension.to_str()) {
+            // csv отчеты сохраняются zip архивом
+            Some("zip") => "application/zip",
+            Some("pdf") => "application/pdf",
+            _ => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    /// ETag из ключа отчета, размера и времени изменения файла.
+    /// Если отчет с тем же ключом сгенерирован заново, ETag поменяется и докачка начнется сначала
+    pub fn etag(&self) -> EntityTag {
+        let modified = self.modified.duration_since(UNIX_EPOCH).map_or(0, |modified| modified.as_secs());
+
+        EntityTag::new_strong(format!("{}-{:x}-{:x}", self.file_name, self.size, modified))
+    }
+}
+
 impl FilePath {
-    pub fn get_path(path_to_file: &String, settings: Data<Settings>, user_id: String) -> String {
-        let reports_dir = if settings.get_prod() {
-            dotenv!("PROD_REPORTS_DIR")
-        } else {
-            dotenv!("REPORTS_DIR")
+    /// Находит файл отчета [FilePath::path] в папке пользователя [user_id].
+    /// Id это имя файла без папок: буквы, цифры, "-" и "_", расширение из [REPORT_EXTENSIONS].
+    /// Если расширение не передано, берется первый существующий файл с этим id.
+    /// Любой другой id, в том числе с "..", "/" и "\", считается не найденным
+    pub fn resolve(&self, settings: &Settings, user_id: &str) -> Result<ReportFile, CustomError> {
+        if !is_safe_name(user_id) {
+            return Err(CustomError::Forbidden);
+        }
+
+        let (id, extension) = match self.path.trim().split_once('.') {
+            Some((id, extension)) => (id, Some(extension)),
+            None => (self.path.trim(), None),
         };
-        format!("{}/reports/{}/{}", reports_dir, user_id, path_to_file)
+
+        if !is_safe_name(id) {
+            return Err(CustomError::NotFound);
+        }
+
+        let user_dir = Path::new(settings.get_reports_dir()).join("reports").join(user_id);
+
+        let path = match extension {
+            Some(extension) if REPORT_EXTENSIONS.contains(&extension) => user_dir.join(format!("{}.{}", id, extension)),
+            Some(_) => return Err(CustomError::NotFound),
+            None => REPORT_EXTENSIONS
+                .iter()
+                .map(|extension| user_dir.join(format!("{}.{}", id, extension)))
+                .find(|path| path.is_file())
+                .ok_or(CustomError::NotFound)?,
+        };
+
+        // Ссылка внутри папки пользователя не должна вести за её пределы
+        let path = path.canonicalize().map_err(map_io_error)?;
+        let user_dir = user_dir.canonicalize().map_err(map_io_error)?;
+
+        if !path.starts_with(&user_dir) {
+            return Err(CustomError::Forbidden);
+        }
+
+        let metadata = std::fs::metadata(&path).map_err(map_io_error)?;
+
+        if !metadata.is_file() {
+            return Err(CustomError::NotFound);
+        }
+
+        let file_name = path
+            .file_name()
+            .map_or(id.to_string(), |file_name| file_name.to_string_lossy().to_string());
+
+        Ok(ReportFile {
+            path,
+            file_name,
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+        })
     }
 }
+
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|symbol| symbol.is_ascii_alphanumeric() || symbol == '-' || symbol == '_')
+}
@@ -0,0 +1,217 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use actix_web::web::Data;
+
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use tracing::error;
+
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::args::Settings;
+use crate::handlers::generate_report::TypeGenerateReport;
+use crate::helper::create_file::create_fs::{create_dir, create_file};
+use crate::money::{CurrencyRule, Money};
+use crate::r#trait::report_kind::ReportKind;
+use crate::r#type::types::ResponseError;
+use crate::share::Report;
+
+/// Имя индексной книги внутри архива пакетной генерации
+pub const BATCH_INDEX_FILE_NAME: &str = "index.xlsx";
+
+const INDEX_SHEET_NAME: &str = "Организации";
+
+/// [Batch entry] Строка индексной книги: отчет одной организации из пакетной генерации
+#[derive(Debug, Clone)]
+pub struct BatchEntry {
+    pub organization_id: String,
+    pub organization_name: String,
+    pub transactions: u128,
+    pub amount: Money,
+    /// [Path] Путь до сохраненного отчета организации, None если отчет не собран
+    pub path: Option<String>,
+    /// [Error] Почему отчет организации не собран
+    pub error: Option<ResponseError>,
+}
+
+impl BatchEntry {
+    /// Организация из запроса, строк которой нет ни в одном файле
+    pub fn not_found(organization_id: &str) -> BatchEntry {
+        BatchEntry {
+            organization_id: organization_id.to_string(),
+            organization_name: "".to_string(),
+            transactions: 0,
+            amount: Money::ZERO,
+            path: None,
+            error: Some((7357586, "В файлах нет транзакций организации".to_string())),
+        }
+    }
+
+    /// Итоги берутся из той части отчета, по которой строятся сводные листы
+    pub async fn from_report(
+        organization_id: &str,
+        report: &Arc<TokioRwLock<Report>>,
+        kind: &dyn ReportKind,
+        result: Result<String, ResponseError>,
+    ) -> BatchEntry {
+        let mut report_writer = report.write().await;
+        let organization_name = report_writer.get_provider_name();
+
+        let (transactions, amount) = report_writer
+            .get_report_item(&kind.required_item())
+            .map_or((0, Money::ZERO), |report_item| (report_item.len_transactions, report_item.amount));
+
+        let (path, error) = match result {
+            Ok(path) => (Some(path), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        BatchEntry {
+            organization_id: organization_id.to_string(),
+            organization_name,
+            transactions,
+            amount,
+            path,
+            error,
+        }
+    }
+
+    /// Имя файла отчета организации внутри архива
+    fn file_name(&self, output_format: &TypeGenerateReport) -> String {
+        let organization_id = self.organization_id
+            .chars()
+            .map(|symbol| if symbol.is_alphanumeric() || symbol == '-' || symbol == '_' { symbol } else { '_' })
+            .collect::<String>();
+
+        format!("{}.{}", organization_id, output_format.extension())
+    }
+}
+
+/// Сохраняет архив пакетной генерации: отчет каждой организации и индексная книга [BATCH_INDEX_FILE_NAME].
+/// Возвращает путь до архива
+pub fn save_batch_archive(
+    key: &str,
+    entries: &[BatchEntry],
+    output_format: &TypeGenerateReport,
+    currency: &CurrencyRule,
+    user_id: String,
+    settings: &Data<Settings>
+) -> Result<String, ResponseError> {
+    let path_to_dir = match create_dir(Data::clone(settings), &user_id) {
+        Ok(path_to_dir) => path_to_dir,
+        Err(error) => {
+            error!("Create error - failed to create dir for batch archive: {}", error);
+            return Err((2354538, "Не удалось создать папку для отчета".to_string()));
+        }
+    };
+
+    // Архив всегда zip, даже если отчеты организаций в xlsx или pdf
+    let path_to_file = create_file(path_to_dir, key, &TypeGenerateReport::Csv);
+
+    let index = batch_index(entries, output_format, currency)
+        .map_err(|error| (3234258, format!("Не удалось собрать индекс пакетной генерации: {}", error)))?;
+
+    let archive = File::create(&path_to_file)
+        .map_err(|error| (3234259, format!("Не удалось создать архив пакетной генерации: {}", error)))?;
+
+    let mut zip = ZipWriter::new(archive);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    add_to_archive(&mut zip, options, BATCH_INDEX_FILE_NAME, &index)?;
+
+    for entry in entries.iter() {
+        if let Some(path) = entry.path.as_ref() {
+            let content = std::fs::read(Path::new(path))
+                .map_err(|error| (3234261, format!("Не удалось прочитать отчет организации {}: {}", entry.organization_id, error)))?;
+
+            add_to_archive(&mut zip, options, &entry.file_name(output_format), &content)?;
+        }
+    }
+
+    zip.finish()
+        .map_err(|error| (3234262, format!("Не удалось сохранить архив пакетной генерации: {}", error)))?;
+
+    Ok(path_to_file)
+}
+
+fn add_to_archive(zip: &mut ZipWriter<File>, options: FileOptions, name: &str, content: &[u8]) -> Result<(), ResponseError> {
+    let error = |error: String| (3234260, format!("Не удалось добавить файл {} в архив: {}", name, error));
+
+    zip.start_file(name, options).map_err(|err| error(err.to_string()))?;
+    zip.write_all(content).map_err(|err| error(err.to_string()))
+}
+
+/// Индексная книга: по строке на организацию и итог по всем собранным отчетам
+fn batch_index(
+    entries: &[BatchEntry],
+    output_format: &TypeGenerateReport,
+    currency: &CurrencyRule,
+) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(INDEX_SHEET_NAME)?;
+
+    let header = ["ID организации", "Организация", "Количество транзакций", "Сумма", "Файл", "Ошибка"];
+    for (col, title) in header.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, title, &bold)?;
+    }
+
+    let mut total_transactions: u128 = 0;
+    let mut total_amount = Money::ZERO;
+
+    for (index, entry) in entries.iter().enumerate() {
+        let row = index as u32 + 1;
+
+        worksheet.write_string(row, 0, &entry.organization_id)?;
+        worksheet.write_string(row, 1, &entry.organization_name)?;
+        worksheet.write_number(row, 2, entry.transactions as f64)?;
+        worksheet.write_number(row, 3, entry.amount.to_f64(currency))?;
+
+        match (entry.path.as_ref(), entry.error.as_ref()) {
+            (Some(_), _) => {
+                worksheet.write_string(row, 4, &entry.file_name(output_format))?;
+
+                total_transactions += entry.transactions;
+                total_amount += entry.amount;
+            }
+            (None, Some((code, message))) => {
+                worksheet.write_string(row, 5, &format!("{}: {}", code, message))?;
+            }
+            (None, None) => {}
+        }
+    }
+
+    write_total(worksheet, entries.len() as u32 + 1, total_transactions, total_amount, currency, &bold)?;
+
+    worksheet.set_column_width(0, 18)?;
+    worksheet.set_column_width(1, 40)?;
+    worksheet.set_column_width(2, 22)?;
+    worksheet.set_column_width(3, 18)?;
+    worksheet.set_column_width(4, 24)?;
+    worksheet.set_column_width(5, 60)?;
+
+    workbook.save_to_buffer()
+}
+
+fn write_total(
+    worksheet: &mut Worksheet,
+    row: u32,
+    transactions: u128,
+    amount: Money,
+    currency: &CurrencyRule,
+    format: &Format,
+) -> Result<(), XlsxError> {
+    worksheet.write_string_with_format(row, 0, "Итого", format)?;
+    worksheet.write_number_with_format(row, 2, transactions as f64, format)?;
+    worksheet.write_number_with_format(row, 3, amount.to_f64(currency), format)?;
+
+    Ok(())
+}
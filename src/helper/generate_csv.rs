@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+
+use actix_web::web::Data;
+
+use tracing::error;
+
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::api_server::response_handlers::resp_user::handlers_user::AccountReplenishment;
+use crate::args::Settings;
+use crate::handlers::generate_report::{TypeGenerateReport, DEFAULT_MONTHLY_SUBSCRIPTION_FEE};
+use crate::helper::create_file::create_fs::{create_dir, create_file};
+use crate::helper::date::{find_day_money, format_month, month_of_date};
+use crate::helper::generate_xlsx::{filter_refill_by_period, month_comparison, refill_amount};
+use crate::helper::period_comparison::{
+    days_comparison, providers_comparison, totals_comparison, MetricComparison
+};
+use crate::helper::reconciliation::Reconciliation;
+use crate::helper::report_template::render::TemplateContext;
+use crate::helper::report_type::constants::{
+    COMPARISON_BY_DAY_NAME, COMPARISON_BY_PROVIDER_NAME, COMPARISON_NAME, RECONCILIATION_NAME,
+    SUMMARY_BY_DAY_NAME, SUMMARY_BY_MONTH_NAME, SUMMARY_BY_PROVIDER_NAME,
+    WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME, WORKSHEET_SUMMARY_BY_REFILL_NAME
+};
+use crate::money::{CurrencyRule, Money};
+use crate::r#trait::filter_report::Status;
+use crate::r#trait::report_kind::ReportKind;
+use crate::r#type::types::{RecordStrings, ResponseError};
+use crate::share::ReportItem;
+
+/// [Csv sheet] Лист отчета в виде строк csv файла
+pub struct CsvSheet {
+    /// [Name] Имя листа, оно же имя csv файла в архиве
+    pub name: &'static str,
+    /// [Rows] Строки вместе с заголовком
+    pub rows: Vec<RecordStrings>,
+}
+
+/// Собирает листы отчета в csv, данные те же что и в xlsx:
+/// "Общий отчет о платежах", "Сводная по провайдеру", "Сводная по дням", "Пополнение счета",
+/// для отчета за несколько месяцев еще "Сравнение по месяцам",
+/// если передан прошлый период то "Сравнение периодов", "Сравнение по провайдерам" и "Сравнение по дням",
+/// если запрошена сверка то "Сверка пополнений"
+pub fn csv_report(kind: &dyn ReportKind, context: &TemplateContext) -> Result<Vec<CsvSheet>, ResponseError> {
+    let currency = context.currency;
+
+    // Часть отчета по которой строятся сводные листы
+    let item_report_for_summary = kind.summary_item(context.report_item(&kind.required_item()))?;
+
+    let mut sheets = vec![CsvSheet {
+        name: WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME,
+        rows: kind.general_report_rows(context),
+    }];
+
+    if let Some(item_report) = item_report_for_summary {
+        sheets.push(CsvSheet {
+            name: SUMMARY_BY_PROVIDER_NAME,
+            rows: summary_by_provider_rows(item_report, currency),
+        });
+        sheets.push(CsvSheet {
+            name: SUMMARY_BY_DAY_NAME,
+            rows: summary_by_day_rows(item_report, currency),
+        });
+
+        if item_report.get_months_in_report().len() > 1 {
+            sheets.push(CsvSheet {
+                name: SUMMARY_BY_MONTH_NAME,
+                rows: summary_by_month_rows(item_report, context.refill, currency),
+            });
+        }
+
+        if let (Some(refill), Some(date_mask)) = (context.refill, context.date_mask.as_ref()) {
+            sheets.push(CsvSheet {
+                name: WORKSHEET_SUMMARY_BY_REFILL_NAME,
+                rows: refill_rows(date_mask, refill, currency),
+            });
+        }
+
+        if let Some(reconciliation) = context.reconciliation.as_ref() {
+            sheets.push(CsvSheet {
+                name: RECONCILIATION_NAME,
+                rows: reconciliation_rows(reconciliation, currency),
+            });
+        }
+
+        if context.has_comparison() {
+            let previous = kind.summary_item(context.previous_report_item(&kind.required_item()))?;
+
+            if let Some(previous) = previous {
+                sheets.extend(comparison_sheets(item_report, previous, currency));
+            }
+        }
+    }
+
+    Ok(sheets)
+}
+
+/// Лист "Сводная по дням", если в отчете несколько месяцев то после дней каждого месяца идет итог за месяц
+pub fn summary_by_day_rows(report: &ReportItem, currency: &CurrencyRule) -> Vec<RecordStrings> {
+    let statuses = breakdown_statuses(report);
+
+    let mut header = to_record(&["Дата", "Количество транзакций", "Сумма", "Комиссия"]);
+    header.extend(status_header(&statuses));
+    let mut rows = vec![header];
+
+    let mut days_in_report = report.days_in_report.iter().collect::<Vec<&String>>();
+    days_in_report.sort();
+
+    let months_totals = report.get_months_totals();
+    let with_subtotals = months_totals.len() > 1;
+    let mut current_month: Option<(i32, u32)> = None;
+
+    for day in days_in_report {
+        let month = month_of_date(day);
+
+        if with_subtotals && current_month.is_some() && month != current_month {
+            rows.push(month_subtotal_row(report, &months_totals, current_month, &statuses, currency));
+        }
+        current_month = month;
+
+        let transactions = report.days_len_transaction.iter()
+            .find(|(date, _)| date == day)
+            .map_or(0, |(_, count)| *count);
+        let amount = find_day_money(&report.days_amount, day);
+        let commission = find_day_money(&report.commission_by_day, day);
+
+        let mut row = vec![
+            day.clone(),
+            transactions.to_string(),
+            amount.format(currency),
+            commission.format(currency),
+        ];
+        row.extend(status_values(
+            &statuses,
+            report.days_by_status.iter()
+                .filter(|by_status| &by_status.0 == day)
+                .map(|by_status| (&by_status.1, by_status.2 as u128, by_status.3)),
+            currency,
+        ));
+        rows.push(row);
+    }
+
+    if with_subtotals {
+        rows.push(month_subtotal_row(report, &months_totals, current_month, &statuses, currency));
+    }
+
+    let mut totals = vec![
+        "Итог".to_string(),
+        report.len_transactions.to_string(),
+        report.amount.format(currency),
+        report.all_types_of_commissions.commission.format(currency),
+    ];
+    totals.extend(status_values(
+        &statuses,
+        report.days_by_status.iter().map(|by_status| (&by_status.1, by_status.2 as u128, by_status.3)),
+        currency,
+    ));
+    rows.push(totals);
+
+    rows
+}
+
+/// Строка "Итого за месяц" листа "Сводная по дням"
+fn month_subtotal_row(
+    report: &ReportItem,
+    months_totals: &[((i32, u32), u64, Money, Money)],
+    month: Option<(i32, u32)>,
+    statuses: &[Status],
+    currency: &CurrencyRule,
+) -> RecordStrings {
+    let (transactions, amount, commission) = months_totals.iter()
+        .find(|totals| Some(totals.0) == month)
+        .map_or((0, Money::ZERO, Money::ZERO), |totals| (totals.1, totals.2, totals.3));
+
+    let mut row = vec![
+        format!("Итого за {}", month.map(format_month).unwrap_or_default()),
+        transactions.to_string(),
+        amount.format(currency),
+        commission.format(currency),
+    ];
+    row.extend(status_values(
+        statuses,
+        report.days_by_status.iter()
+            .filter(|by_status| month_of_date(&by_status.0) == month)
+            .map(|by_status| (&by_status.1, by_status.2 as u128, by_status.3)),
+        currency,
+    ));
+
+    row
+}
+
+/// Лист "Сравнение по месяцам": итоги каждого месяца и изменение суммы к прошлому месяцу
+pub fn summary_by_month_rows(
+    report: &ReportItem,
+    refill: Option<&Vec<AccountReplenishment>>,
+    currency: &CurrencyRule,
+) -> Vec<RecordStrings> {
+    let mut header = to_record(&["Месяц", "Количество транзакций", "Сумма", "Комиссия"]);
+    if refill.is_some() {
+        header.push("Пополнения".to_string());
+    }
+    header.extend(to_record(&["Изменение суммы", "Изменение, %"]));
+    let mut rows = vec![header];
+
+    let mut transactions: u64 = 0;
+    let mut amount = Money::ZERO;
+    let mut commission = Money::ZERO;
+    let mut refill_amount = Money::ZERO;
+
+    for month in month_comparison(report, refill, currency) {
+        transactions += month.transactions;
+        amount += month.amount;
+        commission += month.commission;
+
+        let mut row = vec![
+            format_month(month.month),
+            month.transactions.to_string(),
+            month.amount.format(currency),
+            month.commission.format(currency),
+        ];
+        if let Some(refill) = month.refill {
+            refill_amount += refill;
+            row.push(refill.format(currency));
+        }
+        row.push(month.amount_change.map_or("".to_string(), |change| change.format(currency)));
+        row.push(month.amount_change_percent.map_or("".to_string(), |percent| format!("{:+.2}", percent)));
+
+        rows.push(row);
+    }
+
+    let mut totals = vec![
+        "Итог".to_string(),
+        transactions.to_string(),
+        amount.format(currency),
+        commission.format(currency),
+    ];
+    if refill.is_some() {
+        totals.push(refill_amount.format(currency));
+    }
+    totals.extend(to_record(&["", ""]));
+    rows.push(totals);
+
+    rows
+}
+
+/// Листы сравнения с прошлым периодом: итоги, по провайдерам и по дням
+pub fn comparison_sheets(current: &ReportItem, previous: &ReportItem, currency: &CurrencyRule) -> Vec<CsvSheet> {
+    let header = |keys: &[&str]| {
+        let mut header = to_record(keys);
+        header.extend(to_record(&["Показатель", "Текущий период", "Прошлый период", "Изменение", "Изменение, %"]));
+        header
+    };
+
+    let mut totals = vec![header(&[])];
+    totals.extend(totals_comparison(current, previous, currency)
+        .iter()
+        .map(|metric| metric_record(Vec::new(), metric, currency)));
+
+    let mut by_provider = vec![header(&["Поставщик"])];
+    for provider in providers_comparison(current, previous, currency) {
+        by_provider.extend(provider.metrics
+            .iter()
+            .map(|metric| metric_record(vec![provider.provider.clone()], metric, currency)));
+    }
+
+    let mut by_day = vec![header(&["День", "Дата", "Дата в прошлом периоде"])];
+    for day in days_comparison(current, previous, currency) {
+        let keys = vec![
+            day.day.to_string(),
+            day.current_date.clone().unwrap_or_default(),
+            day.previous_date.clone().unwrap_or_default(),
+        ];
+
+        by_day.extend(day.metrics
+            .iter()
+            .map(|metric| metric_record(keys.clone(), metric, currency)));
+    }
+
+    vec![
+        CsvSheet { name: COMPARISON_NAME, rows: totals },
+        CsvSheet { name: COMPARISON_BY_PROVIDER_NAME, rows: by_provider },
+        CsvSheet { name: COMPARISON_BY_DAY_NAME, rows: by_day },
+    ]
+}
+
+/// Строка одного показателя сравнения периодов после колонок [keys]
+fn metric_record(keys: RecordStrings, metric: &MetricComparison, currency: &CurrencyRule) -> RecordStrings {
+    let mut record = keys;
+    record.extend([
+        metric.metric.label().to_string(),
+        metric.current.format(currency),
+        metric.previous.format(currency),
+        metric.change.format(currency),
+        metric.change_percent.map_or("".to_string(), |percent| format!("{:+.2}", percent)),
+    ]);
+
+    record
+}
+
+/// Лист "Сводная по провайдеру"
+pub fn summary_by_provider_rows(report: &ReportItem, currency: &CurrencyRule) -> Vec<RecordStrings> {
+    let statuses = breakdown_statuses(report);
+
+    let mut header = to_record(&["Поставщик", "Количество транзакций", "Сумма", "Комиссия"]);
+    header.extend(status_header(&statuses));
+    let mut rows = vec![header];
+
+    let mut all_transactions: u32 = 0;
+    let mut all_amount = Money::ZERO;
+
+    for vendor in report.summary_by_Provider.iter() {
+        all_transactions += vendor.1;
+        all_amount += vendor.2;
+
+        let mut row = vec![
+            vendor.0.clone(),
+            vendor.1.to_string(),
+            vendor.2.format(currency),
+            vendor.3.format(currency),
+        ];
+        row.extend(status_values(
+            &statuses,
+            report.summary_by_Provider_status.iter()
+                .filter(|by_status| by_status.0 == vendor.0)
+                .map(|by_status| (&by_status.1, by_status.2 as u128, by_status.3)),
+            currency,
+        ));
+        rows.push(row);
+    }
+
+    let mut totals = vec![
+        "Общий итог".to_string(),
+        all_transactions.to_string(),
+        all_amount.format(currency),
+        report.commission.format(currency),
+    ];
+    totals.extend(status_values(
+        &statuses,
+        report.summary_by_Provider_status.iter().map(|by_status| (&by_status.1, by_status.2 as u128, by_status.3)),
+        currency,
+    ));
+    rows.push(totals);
+
+    rows
+}
+
+/// Статусы для колонок с разбивкой, пустой список если в фильтре один статус или ни одного
+fn breakdown_statuses(report: &ReportItem) -> Vec<Status> {
+    if report.filter.has_status_breakdown() {
+        report.filter.get_statuses()
+    } else {
+        Vec::new()
+    }
+}
+
+fn status_header(statuses: &[Status]) -> Vec<String> {
+    statuses.iter()
+        .flat_map(|status| [format!("{}: количество", status.label()), format!("{}: сумма", status.label())])
+        .collect()
+}
+
+/// Количество и сумма по каждому статусу из [statuses], [breakdown] это строки разбивки одной строки листа
+fn status_values<'a>(
+    statuses: &[Status],
+    breakdown: impl Iterator<Item = (&'a Status, u128, Money)>,
+    currency: &CurrencyRule,
+) -> Vec<String> {
+    let mut totals = statuses.iter().map(|status| (status, 0u128, Money::ZERO)).collect::<Vec<(&Status, u128, Money)>>();
+
+    for (status, transactions, amount) in breakdown {
+        if let Some(total) = totals.iter_mut().find(|total| total.0 == status) {
+            total.1 += transactions;
+            total.2 += amount;
+        }
+    }
+
+    totals.into_iter()
+        .flat_map(|(_, transactions, amount)| [transactions.to_string(), amount.format(currency)])
+        .collect()
+}
+
+/// Лист "Общий отчет о платежах" для отчета Agent
+pub fn general_payment_report_rows_agent(
+    item_report_c2card: Option<&ReportItem>,
+    item_report_pay: Option<&ReportItem>,
+    currency: &CurrencyRule,
+) -> Vec<RecordStrings> {
+    let mut rows = vec![to_record(&[
+        "Раздел", "Поставщик", "кол-во", "Сумма", "Сумма комиссии с Поставщика",
+        "Вознаграждение Банка", "Вознаграждение COMANYNAME", "Вознаграждение Агента",
+    ])];
+
+    if let Some(report) = item_report_pay {
+        for vendor in report.summary_by_Provider.iter() {
+            rows.push(vec![
+                "Платежи".to_string(),
+                vendor.0.clone(),
+                vendor.1.to_string(),
+                vendor.2.format(currency),
+                vendor.3.format(currency),
+                vendor.4.format(currency),
+                vendor.5.format(currency),
+                "".to_string(),
+            ]);
+        }
+
+        rows.push(vec![
+            "Платежи".to_string(),
+            "Общий итог".to_string(),
+            report.len_transactions.to_string(),
+            report.amount.format(currency),
+            report.all_types_of_commissions.commission.format(currency),
+            report.all_types_of_commissions.commission_bank.format(currency),
+            report.all_types_of_commissions.commission_pay_sys.format(currency),
+            "".to_string(),
+        ]);
+    }
+
+    if let Some(report) = item_report_c2card {
+        let mut total_transactions: u32 = 0;
+        let mut total_amount = Money::ZERO;
+        let mut total_commission = Money::ZERO;
+        let mut total_company_award = Money::ZERO;
+        let mut total_remuneration_of_agents = Money::ZERO;
+
+        for provider_info in report.general_report_on_remittance_agent.iter() {
+            total_transactions += provider_info.1;
+            total_amount += provider_info.2;
+            total_commission += provider_info.3;
+            total_company_award += provider_info.4;
+            total_remuneration_of_agents += provider_info.5;
+
+            rows.push(vec![
+                "Пополнение HUMO UZCARD".to_string(),
+                provider_info.0.clone(),
+                provider_info.1.to_string(),
+                provider_info.2.format(currency),
+                provider_info.3.format(currency),
+                "".to_string(),
+                provider_info.4.format(currency),
+                provider_info.5.format(currency),
+            ]);
+        }
+
+        rows.push(vec![
+            "Пополнение HUMO UZCARD".to_string(),
+            "Общий итог".to_string(),
+            total_transactions.to_string(),
+            total_amount.format(currency),
+            total_commission.format(currency),
+            "".to_string(),
+            total_company_award.format(currency),
+            total_remuneration_of_agents.format(currency),
+        ]);
+    }
+
+    rows
+}
+
+/// Лист "Общий отчет о платежах" для отчета TaxiCompany
+pub fn general_payment_report_rows_taxi_company(
+    item_report_c2card: Option<&ReportItem>,
+    item_report_pay: Option<&ReportItem>,
+    fee: Option<Money>,
+    currency: &CurrencyRule,
+) -> Vec<RecordStrings> {
+    let mut rows = vec![to_record(&["Раздел", "Поставщик", "кол-во", "Сумма", "Вознаграждение COMANYNAME"])];
+
+    let sections = [
+        ("Пополнение Яндекс баланса", item_report_pay, Money::ZERO),
+        (
+            "Пополнение карты",
+            item_report_c2card,
+            fee.unwrap_or(Money::from_integer(DEFAULT_MONTHLY_SUBSCRIPTION_FEE)),
+        ),
+    ];
+
+    for (section, report, fee) in sections {
+        let report = match report {
+            Some(report) => report,
+            None => continue,
+        };
+
+        let mut total_transactions: u128 = 0;
+        let mut total_amount = Money::ZERO;
+        let mut total_company_award = fee;
+
+        for vendor_info in report.general_report_on_payments_taxi_company.iter() {
+            total_transactions += vendor_info.1;
+            total_amount += vendor_info.2;
+            total_company_award += vendor_info.3;
+
+            rows.push(vec![
+                section.to_string(),
+                vendor_info.0.clone(),
+                vendor_info.1.to_string(),
+                vendor_info.2.format(currency),
+                vendor_info.3.format(currency),
+            ]);
+        }
+
+        if !fee.is_zero() {
+            rows.push(vec![
+                section.to_string(),
+                "Ежемесячная абонентская плата за обработку запросов телеграмм Бота \"YaPro2Card - COMANYNAME\"".to_string(),
+                "".to_string(),
+                "".to_string(),
+                fee.format(currency),
+            ]);
+        }
+
+        rows.push(vec![
+            section.to_string(),
+            "ИТОГО Вознаграждение COMANYNAME".to_string(),
+            total_transactions.to_string(),
+            total_amount.format(currency),
+            total_company_award.format(currency),
+        ]);
+    }
+
+    rows
+}
+
+/// Лист "Общий отчет о платежах" для отчета Merchant
+pub fn general_payment_report_rows_merchant(
+    item_report_pay: Option<&ReportItem>,
+    currency: &CurrencyRule,
+) -> Vec<RecordStrings> {
+    let mut rows = vec![to_record(&["Платёжная система", "Поставщик", "кол-во", "Сумма", "Вознаграждение COMANYNAME"])];
+
+    let report = match item_report_pay {
+        Some(report) => report,
+        None => return rows,
+    };
+
+    // Итог по платежной системе, порядок систем как в отчете
+    let mut payment_systems: Vec<String> = Vec::new();
+    let mut total_result_of_payments_system: HashMap<String, (u128, Money, Money)> = HashMap::new();
+
+    for merchant in report.general_report_on_payments_merchant.iter() {
+        rows.push(vec![
+            merchant.1.clone(),
+            merchant.0.clone(),
+            merchant.2.to_string(),
+            merchant.3.format(currency),
+            merchant.4.format(currency),
+        ]);
+
+        match total_result_of_payments_system.get_mut(&merchant.1) {
+            Some(total) => {
+                total.0 += merchant.2;
+                total.1 += merchant.3;
+                total.2 += merchant.4;
+            }
+            None => {
+                payment_systems.push(merchant.1.clone());
+                total_result_of_payments_system.insert(merchant.1.clone(), (merchant.2, merchant.3, merchant.4));
+            }
+        }
+    }
+
+    for payment_system in payment_systems {
+        let total = total_result_of_payments_system[&payment_system];
+
+        rows.push(vec![
+            payment_system,
+            "Итого".to_string(),
+            total.0.to_string(),
+            total.1.format(currency),
+            total.2.format(currency),
+        ]);
+    }
+
+    rows
+}
+
+/// "Общий отчет о платежах" по терминалам: строка на каждый терминал и итог
+pub fn general_payment_report_rows_terminal(
+    item_report_terminal: Option<&ReportItem>,
+    currency: &CurrencyRule,
+) -> Vec<RecordStrings> {
+    let mut rows = vec![to_record(&["Терминал", "кол-во", "Сумма", "Комиссия"])];
+
+    let report = match item_report_terminal {
+        Some(report) => report,
+        None => return rows,
+    };
+
+    for terminal in report.summary_by_Provider.iter() {
+        rows.push(vec![
+            terminal.0.clone(),
+            terminal.1.to_string(),
+            terminal.2.format(currency),
+            terminal.3.format(currency),
+        ]);
+    }
+
+    rows.push(vec![
+        "Итого".to_string(),
+        report.len_transactions.to_string(),
+        report.amount.format(currency),
+        report.commission.format(currency),
+    ]);
+
+    rows
+}
+
+/// Лист "Пополнение счета"
+pub fn refill_rows(
+    date_mask: &str,
+    refill: &Vec<AccountReplenishment>,
+    currency: &CurrencyRule,
+) -> Vec<RecordStrings> {
+    let mut rows = vec![to_record(&["ID", "Пользователь", "Сумма", "Комментарий", "Дата"])];
+    let mut refill_total = Money::ZERO;
+
+    for element in filter_refill_by_period(date_mask, refill) {
+        let amount = refill_amount(element);
+
+        refill_total += amount;
+
+        rows.push(vec![
+            element.id.clone().unwrap_or("None".to_string()),
+            format!(
+                "{}({} {})",
+                element.username.clone().unwrap_or_default(),
+                element.first_name.clone().unwrap_or_default(),
+                element.last_name.clone().unwrap_or_default()
+            ),
+            amount.format(currency),
+            element.comment.clone().unwrap_or_default(),
+            element.date.clone().unwrap_or("None".to_string()),
+        ]);
+    }
+
+    rows.push(vec![
+        "Итог".to_string(),
+        "".to_string(),
+        refill_total.format(currency),
+        "".to_string(),
+        "".to_string(),
+    ]);
+
+    rows
+}
+
+/// Лист "Сверка пополнений": пополнения и сумма транзакций по дням, дни с расхождением отмечены
+pub fn reconciliation_rows(reconciliation: &Reconciliation, currency: &CurrencyRule) -> Vec<RecordStrings> {
+    let mut rows = vec![to_record(&["Дата", "Пополнения", "Сумма транзакций", "Разница", "Расхождение"])];
+
+    for day in reconciliation.days.iter() {
+        rows.push(vec![
+            day.date.clone(),
+            day.refill.format(currency),
+            day.amount.format(currency),
+            day.difference.format(currency),
+            if day.is_discrepancy { "Расхождение".to_string() } else { "".to_string() },
+        ]);
+    }
+
+    rows.push(vec![
+        "Итог".to_string(),
+        reconciliation.refill.format(currency),
+        reconciliation.amount.format(currency),
+        reconciliation.difference.format(currency),
+        format!("Дней с расхождением: {}", reconciliation.discrepancy_days()),
+    ]);
+
+    rows
+}
+
+/// Сохраняет каждый лист отдельным csv файлом и упаковывает их в zip архив [key].zip
+pub fn save_csv_archive(
+    key: &str,
+    sheets: &Vec<CsvSheet>,
+    user_id: String,
+    settings: &Data<Settings>
+) -> Result<String, ResponseError> {
+    let path_to_dir = match create_dir(Data::clone(settings), &user_id) {
+        Ok(path_to_dir) => path_to_dir,
+        Err(error) => {
+            error!("Create error - failed to create dir for csv archive: {}", error);
+            return Err((2354537, "Не удалось создать папку для отчета".to_string()));
+        }
+    };
+
+    let path_to_file = create_file(path_to_dir, key, &TypeGenerateReport::Csv);
+
+    let archive = File::create(&path_to_file)
+        .map_err(|error| (3234254, format!("Не удалось создать архив отчета: {}", error)))?;
+
+    let mut zip = ZipWriter::new(archive);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for sheet in sheets {
+        let content = sheet_to_csv(sheet)?;
+
+        zip.start_file(format!("{}.csv", sheet.name), options)
+            .map_err(|error| (3234255, format!("Не удалось добавить лист {} в архив: {}", sheet.name, error)))?;
+        zip.write_all(&content)
+            .map_err(|error| (3234255, format!("Не удалось добавить лист {} в архив: {}", sheet.name, error)))?;
+    }
+
+    zip.finish()
+        .map_err(|error| (3234256, format!("Не удалось сохранить архив отчета: {}", error)))?;
+
+    Ok(path_to_file)
+}
+
+fn sheet_to_csv(sheet: &CsvSheet) -> Result<Vec<u8>, ResponseError> {
+    let error = |error: String| (3234257, format!("Не удалось записать лист {} в csv: {}", sheet.name, error));
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    for row in sheet.rows.iter() {
+        writer.write_record(row).map_err(|err| error(err.to_string()))?;
+    }
+
+    writer.into_inner().map_err(|err| error(err.to_string()))
+}
+
+fn to_record(fields: &[&str]) -> RecordStrings {
+    fields.iter().map(|field| field.to_string()).collect()
+}
@@ -0,0 +1,361 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use actix_web::web::Data;
+
+use printpdf::{
+    Color, IndirectFontRef, Line, Mm, PdfDocument,
+    PdfDocumentReference, PdfLayerReference, Point, Rgb
+};
+
+use tracing::error;
+
+use crate::args::Settings;
+use crate::handlers::generate_report::TypeGenerateReport;
+use crate::helper::create_file::create_fs::{create_dir, create_file};
+use crate::helper::date::{build_date_ymd, format_period, get_date_for_general_taxi_compony_list};
+use crate::helper::generate_xlsx::{get_mode, get_status};
+use crate::helper::report_template::render::TemplateContext;
+use crate::helper::report_type::constants::{
+    GENERAL_REPORT_COMPANY_FORM, GENERAL_REPORT_COMPANY_NAME,
+    GENERAL_REPORT_DIRECTOR_NAME, GENERAL_REPORT_SENDER_AND_RECEIVER,
+    WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME
+};
+use crate::r#trait::report_kind::ReportKind;
+use crate::r#type::types::{RecordStrings, ResponseError};
+use crate::share::ReportItem;
+
+// Шрифты встроены в бинарник, чтобы pdf собирался без доступа к системным шрифтам и сети.
+// DejaVu Sans поддерживает кириллицу
+const FONT_REGULAR: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const FONT_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+// A4 в альбомной ориентации, все размеры в миллиметрах
+const PAGE_WIDTH: f64 = 297.0;
+const PAGE_HEIGHT: f64 = 210.0;
+const MARGIN: f64 = 15.0;
+const ROW_HEIGHT: f64 = 7.0;
+const CELL_PADDING: f64 = 1.5;
+const TABLE_FONT_SIZE: f64 = 8.0;
+// Средняя ширина символа DejaVu Sans в долях кегля
+const CHAR_WIDTH_EM: f64 = 0.6;
+const PT_TO_MM: f64 = 0.3528;
+
+// Цвета как в xlsx листе "Общий отчет о платежах"
+const HEADER_COLOR: u32 = 0x5789bb;
+const DESCRIPTION_COLOR: u32 = 0x5b9bd5;
+const TOTAL_COLOR: u32 = 0xdce6f2;
+const WHITE: u32 = 0xffffff;
+const BLACK: u32 = 0x000000;
+
+/// [Pdf report] Данные листа "Общий отчет о платежах" для pdf.
+/// Собираются заранее, чтобы сам pdf рисовался без удержания блокировок
+pub struct PdfReport {
+    /// Левая колонка шапки: данные организации
+    pub header_left: Vec<String>,
+    /// Правая колонка шапки: название отчета, дата, период и ответственный
+    pub header_right: Vec<String>,
+    /// Первая строка это описание колонок, дальше тело таблицы вместе с итогами
+    pub rows: Vec<RecordStrings>,
+    /// Имя организации для подписи в футере
+    pub provider_name: String,
+}
+
+/// Собирает "Общий отчет о платежах" для pdf из тех же данных что и xlsx
+pub fn pdf_report(kind: &dyn ReportKind, context: &TemplateContext) -> Result<PdfReport, ResponseError> {
+    let (header_left, header_right) = kind.pdf_header(context)?;
+
+    let rows = kind.general_report_rows(context);
+
+    Ok(PdfReport {
+        header_left,
+        header_right,
+        rows,
+        provider_name: context.provider_name.clone(),
+    })
+}
+
+/// Шапка pdf отчета о платежах агента: период отчета (один или несколько месяцев) берется из [item_report]
+pub fn agent_pdf_header(context: &TemplateContext, item_report: &ReportItem) -> (Vec<String>, Vec<String>) {
+    // Отформатированный вид даты когда собрался отчет
+    let formatted_date_in_d_m_y = context.report_date.format("%d.%m.%y").to_string();
+    let new_formatted = formatted_date_in_d_m_y.split(".").collect::<Vec<&str>>();
+
+    let period = format_period(&item_report.get_months_in_report());
+
+    (
+        vec![
+            "Агент".to_string(),
+            context.provider_name.clone(),
+            "Номер договора: ___________".to_string(),
+        ],
+        vec![
+            "Отчет о ПЛАТЕЖАХ".to_string(),
+            format!("за {} года", period),
+            format!("Сформировано: {}", build_date_ymd(&new_formatted)),
+            format!("Ответственный: {} {}", context.creators_last_name, context.creators_first_name),
+        ],
+    )
+}
+
+/// Шапка финансового отчета: фильтры и период берутся из [item_report]
+pub fn financial_pdf_header(context: &TemplateContext, item_report: &ReportItem) -> (Vec<String>, Vec<String>) {
+    let formatted_date_in_d_m_y = context.report_date.format("%d.%m.%y").to_string();
+    let full_date = get_date_for_general_taxi_compony_list(context.full_date_from_to.clone(), item_report);
+
+    (
+        vec![
+            format!("Статус: {}", get_status(&item_report.filter).unwrap_or("None".to_string())),
+            format!("Режим: {}", get_mode(&item_report.filter).unwrap_or("None".to_string())),
+            "Договор № ___________".to_string(),
+        ],
+        vec![
+            "Фин отчет по услуге: Комплекс Платежных услуг".to_string(),
+            format!("Дата составления: {}", formatted_date_in_d_m_y),
+            format!("Период: {} {}", full_date.0, full_date.1),
+            format!("Ответственный: {} {}", context.creators_first_name, context.creators_last_name),
+        ],
+    )
+}
+
+/// Рисует [PdfReport] и сохраняет его в [key].pdf
+pub fn save_pdf(
+    key: &str,
+    pdf_report: &PdfReport,
+    user_id: String,
+    settings: &Data<Settings>
+) -> Result<String, ResponseError> {
+    let path_to_dir = match create_dir(Data::clone(settings), &user_id) {
+        Ok(path_to_dir) => path_to_dir,
+        Err(error) => {
+            error!("Create error - failed to create dir for pdf: {}", error);
+            return Err((2354538, "Не удалось создать папку для отчета".to_string()));
+        }
+    };
+
+    let path_to_file = create_file(path_to_dir, key, &TypeGenerateReport::Pdf);
+
+    let document = render_pdf(pdf_report)?;
+
+    let file = File::create(&path_to_file)
+        .map_err(|error| (3234260, format!("Не удалось создать pdf отчета: {}", error)))?;
+
+    document
+        .save(&mut BufWriter::new(file))
+        .map_err(|error| (3234261, format!("Не удалось сохранить pdf отчета: {}", error)))?;
+
+    Ok(path_to_file)
+}
+
+fn render_pdf(pdf_report: &PdfReport) -> Result<PdfDocumentReference, ResponseError> {
+    let (document, page, layer) = PdfDocument::new(
+        WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME,
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Layer 1",
+    );
+
+    let font_error = |error: printpdf::Error| (3234262, format!("Не удалось загрузить шрифт для pdf: {}", error));
+    let font = document.add_external_font(FONT_REGULAR).map_err(font_error)?;
+    let font_bold = document.add_external_font(FONT_BOLD).map_err(font_error)?;
+
+    let layer = document.get_page(page).get_layer(layer);
+
+    let mut canvas = PdfCanvas {
+        document,
+        layer,
+        font,
+        font_bold,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    // Заголовок
+    canvas.text(MARGIN, 18.0, true, BLACK, GENERAL_REPORT_COMPANY_NAME);
+    canvas.y -= 8.0;
+    canvas.text(MARGIN, 13.0, true, BLACK, GENERAL_REPORT_COMPANY_FORM);
+    canvas.y -= 6.0;
+
+    // Шапка
+    let header_lines = pdf_report.header_left.len().max(pdf_report.header_right.len());
+    let header_height = header_lines as f64 * ROW_HEIGHT + CELL_PADDING * 2.0;
+
+    canvas.fill_rect(MARGIN, canvas.y - header_height, PAGE_WIDTH - MARGIN * 2.0, header_height, HEADER_COLOR);
+
+    for line in 0..header_lines {
+        let y = canvas.y - CELL_PADDING - ROW_HEIGHT * (line as f64 + 1.0) + 2.0;
+
+        if let Some(text) = pdf_report.header_left.get(line) {
+            canvas.text_at(MARGIN + CELL_PADDING * 2.0, y, 11.0, true, WHITE, text);
+        }
+        if let Some(text) = pdf_report.header_right.get(line) {
+            canvas.text_at(PAGE_WIDTH / 2.0, y, 11.0, true, WHITE, text);
+        }
+    }
+
+    canvas.y -= header_height + ROW_HEIGHT;
+
+    // Таблица
+    let widths = column_widths(&pdf_report.rows, PAGE_WIDTH - MARGIN * 2.0);
+
+    if let Some((description, body)) = pdf_report.rows.split_first() {
+        canvas.row(&widths, description, true, Some(DESCRIPTION_COLOR), WHITE);
+
+        for row in body {
+            if canvas.y - ROW_HEIGHT < MARGIN {
+                canvas.new_page();
+                canvas.row(&widths, description, true, Some(DESCRIPTION_COLOR), WHITE);
+            }
+
+            if is_total_row(row) {
+                canvas.row(&widths, row, true, Some(TOTAL_COLOR), BLACK);
+            } else {
+                canvas.row(&widths, row, false, None, BLACK);
+            }
+        }
+    }
+
+    // Футер с подписями, переносим целиком если не помещается
+    let footer_height = ROW_HEIGHT * 5.0;
+    if canvas.y - footer_height < MARGIN {
+        canvas.new_page();
+    }
+
+    canvas.y -= ROW_HEIGHT;
+    let right_column = PAGE_WIDTH / 2.0 + MARGIN;
+
+    canvas.text(MARGIN, 10.0, true, BLACK, GENERAL_REPORT_SENDER_AND_RECEIVER[0]);
+    canvas.text(right_column, 10.0, true, BLACK, GENERAL_REPORT_SENDER_AND_RECEIVER[2]);
+    canvas.y -= ROW_HEIGHT;
+    canvas.text(MARGIN, 10.0, true, BLACK, GENERAL_REPORT_SENDER_AND_RECEIVER[1]);
+    canvas.text(right_column, 10.0, true, BLACK, &pdf_report.provider_name);
+    canvas.y -= ROW_HEIGHT * 2.0;
+    canvas.text(MARGIN, 10.0, true, BLACK, GENERAL_REPORT_DIRECTOR_NAME);
+    canvas.text(right_column, 10.0, false, BLACK, " __________________");
+
+    Ok(canvas.document)
+}
+
+/// Текущая страница pdf и позиция по вертикали, отсчет снизу как в pdf
+struct PdfCanvas {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    font_bold: IndirectFontRef,
+    y: f64,
+}
+
+impl PdfCanvas {
+    fn new_page(&mut self) {
+        let (page, layer) = self.document.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        self.layer = self.document.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn text(&self, x: f64, font_size: f64, bold: bool, color: u32, text: &str) {
+        self.text_at(x, self.y, font_size, bold, color, text);
+    }
+
+    fn text_at(&self, x: f64, y: f64, font_size: f64, bold: bool, color: u32, text: &str) {
+        let font = if bold { &self.font_bold } else { &self.font };
+
+        self.layer.set_fill_color(rgb(color));
+        self.layer.use_text(text, font_size, Mm(x), Mm(y), font);
+    }
+
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64, color: u32) {
+        self.layer.set_fill_color(rgb(color));
+        self.layer.add_shape(Line {
+            points: vec![
+                (Point::new(Mm(x), Mm(y)), false),
+                (Point::new(Mm(x + width), Mm(y)), false),
+                (Point::new(Mm(x + width), Mm(y + height)), false),
+                (Point::new(Mm(x), Mm(y + height)), false),
+            ],
+            is_closed: true,
+            has_fill: true,
+            has_stroke: false,
+            is_clipping_path: false,
+        });
+    }
+
+    /// Строка таблицы, числа выравниваются по правому краю ячейки
+    fn row(&mut self, widths: &[f64], row: &RecordStrings, bold: bool, background: Option<u32>, color: u32) {
+        let y = self.y - ROW_HEIGHT;
+
+        if let Some(background) = background {
+            self.fill_rect(MARGIN, y, widths.iter().sum::<f64>(), ROW_HEIGHT, background);
+        }
+
+        let mut x = MARGIN;
+        for (width, value) in widths.iter().zip(row.iter()) {
+            let text = fit_text(value, width - CELL_PADDING * 2.0);
+
+            let text_x = if is_number(value) {
+                x + width - CELL_PADDING - text_width(&text)
+            } else {
+                x + CELL_PADDING
+            };
+
+            self.text_at(text_x, y + 2.2, TABLE_FONT_SIZE, bold, color, &text);
+            x += width;
+        }
+
+        self.y = y;
+    }
+}
+
+fn rgb(color: u32) -> Color {
+    let channel = |shift: u32| ((color >> shift) & 0xff) as f64 / 255.0;
+
+    Color::Rgb(Rgb::new(channel(16), channel(8), channel(0), None))
+}
+
+/// Ширина колонок пропорционально самому длинному значению в колонке
+fn column_widths(rows: &[RecordStrings], table_width: f64) -> Vec<f64> {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let weights = (0..columns)
+        .map(|column| {
+            let longest = rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|value| value.chars().count())
+                .max()
+                .unwrap_or(0);
+
+            // Очень длинные названия переносить не умеем, поэтому ограничиваем и обрезаем
+            longest.clamp(8, 40) as f64
+        })
+        .collect::<Vec<f64>>();
+
+    let total_weight: f64 = weights.iter().sum();
+
+    weights.iter().map(|weight| weight / total_weight * table_width).collect()
+}
+
+fn text_width(text: &str) -> f64 {
+    text.chars().count() as f64 * TABLE_FONT_SIZE * CHAR_WIDTH_EM * PT_TO_MM
+}
+
+/// Обрезает текст до ширины ячейки
+fn fit_text(text: &str, width: f64) -> String {
+    let max_chars = (width / (TABLE_FONT_SIZE * CHAR_WIDTH_EM * PT_TO_MM)) as usize;
+
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut fitted = text.chars().take(max_chars.saturating_sub(1)).collect::<String>();
+    fitted.push('…');
+    fitted
+}
+
+fn is_number(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|character| character.is_ascii_digit() || character == '.' || character == '-')
+}
+
+fn is_total_row(row: &RecordStrings) -> bool {
+    row.iter().any(|value| {
+        let value = value.to_lowercase();
+        value == "общий итог" || value == "итого" || value.starts_with("итого ")
+    })
+}
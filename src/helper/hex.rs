@@ -0,0 +1,40 @@
+/// Байты в hex строку в нижнем регистре
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().concat()
+}
+
+/// Hex строку в байты, None если длина нечетная или есть символ кроме 0-9, a-f, A-F
+pub fn from_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&value[index..index + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = [0x00, 0x0f, 0xa5, 0xff];
+
+        assert_eq!(to_hex(&bytes), "000fa5ff");
+        assert_eq!(from_hex("000fa5ff"), Some(bytes.to_vec()));
+        assert_eq!(from_hex("000FA5FF"), Some(bytes.to_vec()));
+        assert_eq!(from_hex(""), Some(vec![]));
+    }
+
+    #[test]
+    fn rejects_malformed_hex() {
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+        // from_str_radix принимает знак, а в hex его быть не должно
+        assert_eq!(from_hex("+f"), None);
+        assert_eq!(from_hex("ёё"), None);
+    }
+}
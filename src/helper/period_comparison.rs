@@ -0,0 +1,274 @@
+use chrono::NaiveDate;
+
+use crate::helper::date::find_day_money;
+use crate::money::{CurrencyRule, Money};
+use crate::share::ReportItem;
+
+/// [Comparison metric] Показатель который сравнивается между текущим и прошлым периодом
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonMetric {
+    Transactions,
+    Amount,
+    /// Комиссии из [CommissionType]
+    ///
+    /// [CommissionType]: crate::share::CommissionType
+    Commission,
+    CommissionPaySys,
+    CommissionBank,
+    CommissionPayment,
+    CommissionEops,
+    CommissionPartner,
+    /// Вознаграждения из сводной по провайдеру
+    BankAward,
+    CompanyAward,
+}
+
+impl ComparisonMetric {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComparisonMetric::Transactions => "Количество транзакций",
+            ComparisonMetric::Amount => "Сумма",
+            ComparisonMetric::Commission => "Комиссия",
+            ComparisonMetric::CommissionPaySys => "Комиссия COMANYNAME",
+            ComparisonMetric::CommissionBank => "Комиссия Bank",
+            ComparisonMetric::CommissionPayment => "Комиссия Payment",
+            ComparisonMetric::CommissionEops => "Комиссия EOPS",
+            ComparisonMetric::CommissionPartner => "Комиссия Partner",
+            ComparisonMetric::BankAward => "Вознаграждение банка",
+            ComparisonMetric::CompanyAward => "Вознаграждение компании",
+        }
+    }
+}
+
+/// Значение показателя, изменение количества может быть отрицательным
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricValue {
+    Count(i128),
+    Money(Money),
+}
+
+impl MetricValue {
+    pub fn format(&self, currency: &CurrencyRule) -> String {
+        match self {
+            MetricValue::Count(count) => count.to_string(),
+            MetricValue::Money(money) => money.format(currency),
+        }
+    }
+}
+
+/// [Metric comparison] Показатель за текущий и прошлый период
+#[derive(Debug, Clone)]
+pub struct MetricComparison {
+    pub metric: ComparisonMetric,
+    pub current: MetricValue,
+    pub previous: MetricValue,
+    /// Текущий период минус прошлый
+    pub change: MetricValue,
+    /// Изменение в процентах от прошлого периода, нет если в прошлом периоде ноль
+    pub change_percent: Option<f64>,
+}
+
+impl MetricComparison {
+    fn count(metric: ComparisonMetric, current: u128, previous: u128) -> MetricComparison {
+        let (current, previous) = (current as i128, previous as i128);
+
+        MetricComparison {
+            metric,
+            current: MetricValue::Count(current),
+            previous: MetricValue::Count(previous),
+            change: MetricValue::Count(current - previous),
+            change_percent: match previous {
+                0 => None,
+                previous => Some((current - previous) as f64 / previous as f64 * 100.0),
+            },
+        }
+    }
+
+    fn money(metric: ComparisonMetric, current: Money, previous: Money, currency: &CurrencyRule) -> MetricComparison {
+        let change_percent = Some(previous)
+            .filter(|previous| !previous.is_zero())
+            .map(|previous| (current - previous).to_f64(currency) / previous.to_f64(currency) * 100.0);
+
+        MetricComparison {
+            metric,
+            current: MetricValue::Money(current),
+            previous: MetricValue::Money(previous),
+            change: MetricValue::Money(current - previous),
+            change_percent,
+        }
+    }
+
+    /// Изменение в процентах со знаком: +12.50%, пусто если процент не считается
+    pub fn format_change_percent(&self) -> String {
+        self.change_percent.map_or("".to_string(), |percent| format!("{:+.2}%", percent))
+    }
+}
+
+/// [Provider comparison] Показатели одного провайдера из сводной по провайдеру за оба периода
+#[derive(Debug, Clone)]
+pub struct ProviderComparison {
+    pub provider: String,
+    pub metrics: Vec<MetricComparison>,
+}
+
+/// [Day comparison] N-й день текущего периода против N-го дня прошлого периода
+#[derive(Debug, Clone)]
+pub struct DayComparison {
+    /// Номер дня периода, начиная с 1
+    pub day: u32,
+    /// Дата в текущем периоде, None если в этот день не было транзакций
+    pub current_date: Option<String>,
+    /// Дата в прошлом периоде, None если в этот день не было транзакций
+    pub previous_date: Option<String>,
+    pub metrics: Vec<MetricComparison>,
+}
+
+/// Итоги части отчета: количество транзакций, сумма и каждая комиссия из [CommissionType]
+///
+/// [CommissionType]: crate::share::CommissionType
+pub fn totals_comparison(current: &ReportItem, previous: &ReportItem, currency: &CurrencyRule) -> Vec<MetricComparison> {
+    let current_commissions = &current.all_types_of_commissions;
+    let previous_commissions = &previous.all_types_of_commissions;
+
+    vec![
+        MetricComparison::count(ComparisonMetric::Transactions, current.len_transactions, previous.len_transactions),
+        MetricComparison::money(ComparisonMetric::Amount, current.amount, previous.amount, currency),
+        MetricComparison::money(
+            ComparisonMetric::Commission,
+            current_commissions.commission, previous_commissions.commission, currency
+        ),
+        MetricComparison::money(
+            ComparisonMetric::CommissionPaySys,
+            current_commissions.commission_pay_sys, previous_commissions.commission_pay_sys, currency
+        ),
+        MetricComparison::money(
+            ComparisonMetric::CommissionBank,
+            current_commissions.commission_bank, previous_commissions.commission_bank, currency
+        ),
+        MetricComparison::money(
+            ComparisonMetric::CommissionPayment,
+            current_commissions.commission_payment, previous_commissions.commission_payment, currency
+        ),
+        MetricComparison::money(
+            ComparisonMetric::CommissionEops,
+            current_commissions.commission_eops, previous_commissions.commission_eops, currency
+        ),
+        MetricComparison::money(
+            ComparisonMetric::CommissionPartner,
+            current_commissions.commission_partner, previous_commissions.commission_partner, currency
+        ),
+    ]
+}
+
+/// Сравнение по провайдерам: сначала провайдеры текущего периода, потом те которые были только в прошлом
+pub fn providers_comparison(current: &ReportItem, previous: &ReportItem, currency: &CurrencyRule) -> Vec<ProviderComparison> {
+    let mut providers = current.summary_by_Provider.iter()
+        .map(|provider| provider.0.clone())
+        .collect::<Vec<String>>();
+
+    for provider in previous.summary_by_Provider.iter() {
+        if !providers.contains(&provider.0) {
+            providers.push(provider.0.clone());
+        }
+    }
+
+    let empty = |provider: &str| (provider.to_string(), 0, Money::ZERO, Money::ZERO, Money::ZERO, Money::ZERO);
+
+    providers
+        .into_iter()
+        .map(|provider| {
+            let find = |item: &ReportItem| item.summary_by_Provider.iter()
+                .find(|summary| summary.0 == provider)
+                .cloned()
+                .unwrap_or(empty(&provider));
+
+            let (current, previous) = (find(current), find(previous));
+
+            ProviderComparison {
+                metrics: vec![
+                    MetricComparison::count(ComparisonMetric::Transactions, current.1 as u128, previous.1 as u128),
+                    MetricComparison::money(ComparisonMetric::Amount, current.2, previous.2, currency),
+                    MetricComparison::money(ComparisonMetric::Commission, current.3, previous.3, currency),
+                    MetricComparison::money(ComparisonMetric::BankAward, current.4, previous.4, currency),
+                    MetricComparison::money(ComparisonMetric::CompanyAward, current.5, previous.5, currency),
+                ],
+                provider,
+            }
+        })
+        .collect()
+}
+
+/// Сравнение по дням: дни выравниваются по номеру дня от начала периода,
+/// первый день периода это первый день с транзакциями
+pub fn days_comparison(current: &ReportItem, previous: &ReportItem, currency: &CurrencyRule) -> Vec<DayComparison> {
+    let current_days = days_by_number(current);
+    let previous_days = days_by_number(previous);
+
+    let mut numbers = current_days.iter()
+        .chain(previous_days.iter())
+        .map(|(number, _)| *number)
+        .collect::<Vec<u32>>();
+    numbers.sort();
+    numbers.dedup();
+
+    numbers
+        .into_iter()
+        .map(|number| {
+            let find_date = |days: &[(u32, String)]| days.iter()
+                .find(|(day, _)| *day == number)
+                .map(|(_, date)| date.clone());
+
+            let current_date = find_date(&current_days);
+            let previous_date = find_date(&previous_days);
+
+            let current_totals = day_totals(current, current_date.as_deref());
+            let previous_totals = day_totals(previous, previous_date.as_deref());
+
+            DayComparison {
+                day: number,
+                metrics: vec![
+                    MetricComparison::count(ComparisonMetric::Transactions, current_totals.0 as u128, previous_totals.0 as u128),
+                    MetricComparison::money(ComparisonMetric::Amount, current_totals.1, previous_totals.1, currency),
+                    MetricComparison::money(ComparisonMetric::Commission, current_totals.2, previous_totals.2, currency),
+                ],
+                current_date,
+                previous_date,
+            }
+        })
+        .collect()
+}
+
+/// Дни части отчета с номером дня от первого дня с транзакциями
+fn days_by_number(item: &ReportItem) -> Vec<(u32, String)> {
+    let mut days = item.days_in_report.iter()
+        .filter_map(|day| {
+            NaiveDate::parse_from_str(day.trim().split(' ').next().unwrap_or(""), "%Y-%m-%d")
+                .ok()
+                .map(|date| (date, day.clone()))
+        })
+        .collect::<Vec<(NaiveDate, String)>>();
+    days.sort();
+
+    let first = match days.first() {
+        Some((first, _)) => *first,
+        None => return Vec::new(),
+    };
+
+    days.into_iter()
+        .map(|(date, day)| ((date - first).num_days() as u32 + 1, day))
+        .collect()
+}
+
+/// Количество транзакций, сумма и комиссия за день, нули если дня нет
+fn day_totals(item: &ReportItem, day: Option<&str>) -> (u64, Money, Money) {
+    let day = match day {
+        Some(day) => day,
+        None => return (0, Money::ZERO, Money::ZERO),
+    };
+
+    let transactions = item.days_len_transaction.iter()
+        .find(|(date, _)| date == day)
+        .map_or(0, |(_, count)| *count);
+
+    (transactions, find_day_money(&item.days_amount, day), find_day_money(&item.commission_by_day, day))
+}
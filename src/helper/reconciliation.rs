@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api_server::response_handlers::resp_user::handlers_user::AccountReplenishment;
+use crate::helper::date::{day_of_date, find_day_money};
+use crate::helper::generate_xlsx::{filter_refill_by_period, refill_amount};
+use crate::money::Money;
+use crate::share::ReportItem;
+
+/// [Reconciliation day] Пополнения счета и сумма транзакций за один день
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationDay {
+    /// День в виде 2023-01-15
+    pub date: String,
+    /// [Refill] Пополнения счета за день
+    pub refill: Money,
+    /// [Amount] Сумма транзакций части отчета за день
+    pub amount: Money,
+    /// [Difference] Пополнения минус сумма транзакций
+    pub difference: Money,
+    /// [Is discrepancy] Разница по модулю больше допустимого расхождения
+    pub is_discrepancy: bool,
+}
+
+/// [Reconciliation] Сверка пополнений счета с суммой транзакций по дням
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reconciliation {
+    /// [Tolerance] Допустимое расхождение за день
+    pub tolerance: Money,
+    pub refill: Money,
+    pub amount: Money,
+    pub difference: Money,
+    /// [Days] Каждый день периода в котором были пополнения или транзакции, по порядку
+    pub days: Vec<ReconciliationDay>,
+}
+
+impl Reconciliation {
+    /// Сверяет пополнения за период [date_mask] с суммой транзакций [item_report] по дням.
+    /// Пополнение без даты или с датой вне периода в сверку не попадает
+    pub fn new(
+        item_report: &ReportItem,
+        date_mask: &str,
+        refill: &Vec<AccountReplenishment>,
+        tolerance: Money,
+    ) -> Reconciliation {
+        let mut refill_by_day: Vec<(String, Money)> = Vec::new();
+
+        for element in filter_refill_by_period(date_mask, refill) {
+            let day = match element.date.as_ref().and_then(|date| day_of_date(date)) {
+                Some(day) => day.format("%Y-%m-%d").to_string(),
+                None => continue,
+            };
+
+            match refill_by_day.iter_mut().find(|(date, _)| *date == day) {
+                Some((_, amount)) => *amount += refill_amount(element),
+                None => refill_by_day.push((day, refill_amount(element))),
+            }
+        }
+
+        let mut dates = item_report.days_amount.iter()
+            .map(|(date, _)| date.clone())
+            .chain(refill_by_day.iter().map(|(date, _)| date.clone()))
+            .collect::<Vec<String>>();
+        dates.sort();
+        dates.dedup();
+
+        let days = dates
+            .into_iter()
+            .map(|date| {
+                let refill = find_day_money(&refill_by_day, &date);
+                let amount = find_day_money(&item_report.days_amount, &date);
+                let difference = refill - amount;
+
+                ReconciliationDay {
+                    date,
+                    refill,
+                    amount,
+                    difference,
+                    is_discrepancy: difference > tolerance || difference < -tolerance,
+                }
+            })
+            .collect::<Vec<ReconciliationDay>>();
+
+        let refill = days.iter().map(|day| day.refill).sum::<Money>();
+        let amount = days.iter().map(|day| day.amount).sum::<Money>();
+
+        Reconciliation {
+            tolerance,
+            refill,
+            amount,
+            difference: refill - amount,
+            days,
+        }
+    }
+
+    /// Сколько дней с расхождением больше допустимого
+    pub fn discrepancy_days(&self) -> usize {
+        self.days.iter().filter(|day| day.is_discrepancy).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::report_type::find_report_kind;
+    use crate::r#trait::filter_report::{Filter, ReportItemType};
+
+    fn money(value: &str) -> Money {
+        Money::parse(value).unwrap()
+    }
+
+    /// Пополнения: два за 3 января, одно за 5 января, одно без даты и одно за февраль
+    fn refill() -> Vec<AccountReplenishment> {
+        serde_json::from_str(
+            r#"[
+                {"acc": "1", "amount": "600", "date": "2024-01-03 10:00:00", "id": "1"},
+                {"acc": "1", "amount": "400.5", "date": "2024-01-03 18:00:00", "id": "2"},
+                {"acc": "1", "amount": "300", "date": "2024-01-05 09:00:00", "id": "3"},
+                {"acc": "1", "amount": "999", "date": null, "id": "4"},
+                {"acc": "1", "amount": "700", "date": "2024-02-02 09:00:00", "id": "5"}
+            ]"#
+        ).unwrap()
+    }
+
+    fn item_report() -> ReportItem {
+        let filter: Filter = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        let mut item_report = ReportItem::new(&filter);
+        item_report.days_amount = vec![
+            ("2024-01-03".to_string(), money("1000")),
+            ("2024-01-04".to_string(), money("50")),
+            ("2024-01-05".to_string(), money("300")),
+        ];
+        item_report
+    }
+
+    #[test]
+    fn reconciles_refill_with_amount_by_day() {
+        let reconciliation = Reconciliation::new(&item_report(), "2024-01-01#2024-01-31", &refill(), money("1"));
+
+        let days = reconciliation.days.iter()
+            .map(|day| (day.date.as_str(), day.refill, day.amount, day.difference, day.is_discrepancy))
+            .collect::<Vec<(&str, Money, Money, Money, bool)>>();
+
+        assert_eq!(days, [
+            ("2024-01-03", money("1000.5"), money("1000"), money("0.5"), false),
+            ("2024-01-04", Money::ZERO, money("50"), money("-50"), true),
+            ("2024-01-05", money("300"), money("300"), Money::ZERO, false),
+        ]);
+        assert_eq!(reconciliation.refill, money("1300.5"));
+        assert_eq!(reconciliation.amount, money("1350"));
+        assert_eq!(reconciliation.difference, money("-49.5"));
+        assert_eq!(reconciliation.discrepancy_days(), 1);
+    }
+
+    #[test]
+    fn tolerance_is_inclusive() {
+        let reconciliation = Reconciliation::new(&item_report(), "2024-01-01#2024-01-31", &refill(), money("50"));
+
+        assert_eq!(reconciliation.discrepancy_days(), 0);
+    }
+
+    /// Агент тратит пополнения на платежи, таксопарк на переводы водителям
+    #[test]
+    fn each_kind_reconciles_the_item_paid_from_account() {
+        let reconciliation_item = |name: &str| find_report_kind(name).unwrap().reconciliation_item();
+
+        assert_eq!(reconciliation_item("Agent"), ReportItemType::Payments);
+        assert_eq!(reconciliation_item("TaxiCompany"), ReportItemType::Remittance);
+    }
+}
@@ -0,0 +1,601 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use rust_xlsxwriter::{ColNum, Format, FormatAlign, FormatBorder, RowNum, XlsxColor};
+
+use serde::Deserialize;
+
+use crate::r#trait::filter_report::ReportItemType;
+use crate::r#trait::report_kind::ReportKind;
+use crate::r#type::types::ResponseError;
+
+pub mod render;
+
+/// [Report template] Описание xlsx отчета: листы, блоки ячеек, таблицы и форматы.
+/// Позиции ячеек и тексты берутся из шаблона, из кода приходят только данные отчета
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportTemplate {
+    /// [Formats] Именованные форматы, на них ссылаются ячейки и таблицы
+    #[serde(default)]
+    pub formats: HashMap<String, CellFormat>,
+    /// [Sheets] Листы в том порядке, в котором они создаются в книге
+    pub sheets: Vec<SheetTemplate>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SheetTemplate {
+    /// [Name] Имя листа
+    pub name: String,
+    /// [Columns] Ширина и формат колонок
+    #[serde(default)]
+    pub columns: Vec<ColumnTemplate>,
+    /// [Rows] Высота строк с абсолютными номерами
+    #[serde(default)]
+    pub rows: Vec<RowTemplate>,
+    /// [Blocks] Блоки листа, идут сверху вниз
+    pub blocks: Vec<BlockTemplate>,
+    /// [Multi month only] Лист создается только если период отчета больше одного месяца
+    #[serde(default)]
+    pub multi_month_only: bool,
+    /// [Comparison only] Лист создается только если в запросе переданы файлы прошлого периода (compare_filters)
+    #[serde(default)]
+    pub comparison_only: bool,
+    /// [Reconciliation only] Лист создается только если в запросе передан reconciliation
+    #[serde(default)]
+    pub reconciliation_only: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnTemplate {
+    /// Первая колонка, если [ColumnTemplate::to] не указан то только она
+    pub from: ColNum,
+    #[serde(default)]
+    pub to: Option<ColNum>,
+    #[serde(default)]
+    pub width: Option<f64>,
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RowTemplate {
+    pub row: RowNum,
+    pub height: f64,
+}
+
+/// [Block] Часть листа. Если у блока не указан [row] он начинается
+/// через [gap] пустых строк после предыдущего блока
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockTemplate {
+    /// Ячейки с текстом, к примеру заголовок, шапка или подписи
+    Cells(CellsBlock),
+    /// Таблица по данным отчета
+    Table(Box<TableBlock>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CellsBlock {
+    #[serde(default)]
+    pub row: Option<RowNum>,
+    #[serde(default)]
+    pub col: ColNum,
+    #[serde(default)]
+    pub gap: RowNum,
+    /// [Item] Часть отчета для плейсхолдеров фильтра и периода ({status}, {mode}, {period_from} ...).
+    /// Если этой части нет в отчете, блок пропускается
+    #[serde(default)]
+    pub item: Option<ItemKind>,
+    /// [Fill] Прямоугольник который заливается форматом до записи ячеек
+    #[serde(default)]
+    pub fill: Option<FillTemplate>,
+    /// [Height] Сколько строк занимает блок, по умолчанию до последней ячейки или заливки
+    #[serde(default)]
+    pub height: Option<RowNum>,
+    pub cells: Vec<CellTemplate>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FillTemplate {
+    pub rows: RowNum,
+    pub cols: ColNum,
+    pub format: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CellTemplate {
+    /// Строка и колонка относительно начала блока
+    #[serde(default)]
+    pub row: RowNum,
+    #[serde(default)]
+    pub col: ColNum,
+    /// Текст ячейки, может содержать плейсхолдеры в фигурных скобках
+    pub value: String,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Сколько колонок объединить начиная с этой
+    #[serde(default)]
+    pub merge: Option<ColNum>,
+    #[serde(default)]
+    pub height: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TableBlock {
+    #[serde(default)]
+    pub row: Option<RowNum>,
+    #[serde(default)]
+    pub col: ColNum,
+    #[serde(default)]
+    pub gap: RowNum,
+    /// [Item] Из какой части отчета берутся строки, если её нет то таблица пропускается
+    #[serde(default)]
+    pub item: Option<ItemKind>,
+    /// [Source] Набор строк таблицы
+    pub source: TableSource,
+    /// [Header] Заголовки колонок, пишутся первой строкой таблицы
+    #[serde(default)]
+    pub header: Vec<String>,
+    #[serde(default)]
+    pub header_format: Option<String>,
+    #[serde(default)]
+    pub header_height: Option<f64>,
+    /// [Columns] Что пишется в каждую колонку строки, по порядку начиная с [col]
+    pub columns: Vec<ColumnBinding>,
+    /// [Group by] Строки группируются по полю в порядке первого появления,
+    /// после каждой группы [group_gap] пустых строк
+    #[serde(default)]
+    pub group_by: Option<TableField>,
+    #[serde(default)]
+    pub group_gap: RowNum,
+    /// [Aggregate by] Вместо строк источника пишется одна строка с суммами на каждое значение поля
+    #[serde(default)]
+    pub aggregate_by: Option<TableField>,
+    /// [Fee] Строка с абонентской платой, пишется только если плата не нулевая
+    #[serde(default)]
+    pub fee: Option<FeeRowTemplate>,
+    /// [Totals] Итоговая строка
+    #[serde(default)]
+    pub totals: Option<TotalsTemplate>,
+    /// [Status columns] Колонки с разбивкой по статусам, пишутся после [columns]
+    /// если в фильтре части отчета больше одного статуса
+    #[serde(default)]
+    pub status_columns: Option<StatusColumnsTemplate>,
+    /// [Month subtotals] Строка итога после дней каждого месяца,
+    /// пишется только если в таблице дни больше чем одного месяца
+    #[serde(default)]
+    pub month_subtotals: Option<MonthSubtotalsTemplate>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonthSubtotalsTemplate {
+    /// Подпись в колонке даты, {month} заменяется на месяц (Январь 2023)
+    pub label: String,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Пустых строк после строки итога
+    #[serde(default)]
+    pub gap: RowNum,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusColumnsTemplate {
+    /// Строка заголовков, если не указана то заголовки пишутся в строку заголовка таблицы
+    #[serde(default)]
+    pub header_row: Option<RowNum>,
+    #[serde(default)]
+    pub header_format: Option<String>,
+    /// Колонки которые повторяются для каждого статуса фильтра
+    pub columns: Vec<StatusColumn>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusColumn {
+    /// Заголовок, {status} заменяется на статус
+    pub header: String,
+    /// Поле строки, для статусов есть только transactions и amount
+    pub field: TableField,
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnBinding {
+    /// Поле строки источника
+    #[serde(default)]
+    pub field: Option<TableField>,
+    /// Текст, если колонка не привязана к полю
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Писать только в первую строку таблицы или группы
+    #[serde(default)]
+    pub first_row_only: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeRowTemplate {
+    pub label: String,
+    /// Сколько колонок занимает подпись
+    #[serde(default)]
+    pub label_merge: Option<ColNum>,
+    /// Смещение колонки подписи относительно начала таблицы
+    #[serde(default)]
+    pub label_col: ColNum,
+    /// В колонку с этим полем пишется плата, к её итогу плата прибавляется
+    pub field: TableField,
+    #[serde(default)]
+    pub label_format: Option<String>,
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub height: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TotalsTemplate {
+    /// Пустых строк между таблицей и итогом
+    #[serde(default)]
+    pub gap: RowNum,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Ячейки итога по порядку начиная с начала таблицы, пустой объект это пропуск колонки
+    pub cells: Vec<TotalCell>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TotalCell {
+    /// Текст
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Сумма поля по всем строкам таблицы
+    #[serde(default)]
+    pub sum: Option<TableField>,
+    /// Итог из части отчета, а не сумма строк
+    #[serde(default)]
+    pub report: Option<ReportTotal>,
+    #[serde(default)]
+    pub merge: Option<ColNum>,
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// [Item kind] Часть отчета
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Remittance,
+    Payments,
+    Terminal,
+}
+
+impl ItemKind {
+    /// Ключ этой части в отчете
+    pub fn item_type(&self) -> ReportItemType {
+        match self {
+            ItemKind::Remittance => ReportItemType::Remittance,
+            ItemKind::Payments => ReportItemType::Payments,
+            ItemKind::Terminal => ReportItemType::Terminal,
+        }
+    }
+}
+
+/// [Table source] Откуда берутся строки таблицы
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableSource {
+    /// Сводная по провайдеру: provider, transactions, amount, commission, bank_award, company_award
+    SummaryByProvider,
+    /// Сводная по дням: date, transactions, amount, commission
+    Days,
+    /// Общий отчет агента: provider, transactions, amount, commission, company_award, agent_award
+    GeneralAgent,
+    /// Общий отчет таксопарка: provider, transactions, amount, company_award
+    GeneralTaxiCompany,
+    /// Общий отчет мерчанта: provider, payment_system, transactions, amount, company_award
+    GeneralMerchant,
+    /// Пополнения счета за период отчета: id, user, amount, comment, date
+    Refill,
+    /// Сравнение по месяцам: month, transactions, amount, commission, refill, amount_change, amount_change_percent
+    Months,
+    /// Сравнение итогов с прошлым периодом, строка на каждый показатель: metric, current, previous, change, change_percent
+    ComparisonTotals,
+    /// Сравнение с прошлым периодом по провайдерам, строка на каждый показатель провайдера:
+    /// provider, metric, current, previous, change, change_percent
+    ComparisonByProvider,
+    /// Сравнение с прошлым периодом по дням, строка на каждый показатель дня:
+    /// day, date, previous_date, metric, current, previous, change, change_percent
+    ComparisonByDay,
+    /// Сверка пополнений счета с суммой транзакций по дням: date, refill, amount, difference, discrepancy
+    Reconciliation,
+}
+
+/// [Table field] Поле строки таблицы
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableField {
+    Provider,
+    PaymentSystem,
+    Date,
+    Transactions,
+    Amount,
+    Commission,
+    BankAward,
+    CompanyAward,
+    AgentAward,
+    Id,
+    User,
+    Comment,
+    /// Месяц словами (Январь 2023)
+    Month,
+    /// Пополнения счета за месяц
+    Refill,
+    /// Изменение суммы к прошлому месяцу
+    AmountChange,
+    /// Изменение суммы к прошлому месяцу в процентах
+    AmountChangePercent,
+    /// Номер дня периода при сравнении периодов
+    Day,
+    /// Дата в прошлом периоде
+    PreviousDate,
+    /// Название показателя при сравнении периодов
+    Metric,
+    /// Значение показателя за текущий период
+    Current,
+    /// Значение показателя за прошлый период
+    Previous,
+    /// Текущий период минус прошлый
+    Change,
+    /// Изменение в процентах от прошлого периода
+    ChangePercent,
+    /// Пополнения минус сумма транзакций за день
+    Difference,
+    /// Отметка дня с расхождением больше допустимого
+    Discrepancy,
+}
+
+/// [Report total] Итоги которые считаются по всей части отчета
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTotal {
+    Transactions,
+    Amount,
+    /// Комиссия из всех видов комиссий
+    Commission,
+    /// Общая комиссия части отчета
+    TotalCommission,
+    CommissionBank,
+    CommissionPaySys,
+    RefillAmount,
+}
+
+/// [Cell format] Формат ячейки в шаблоне, цвета в виде "#5789bb"
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CellFormat {
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub text_wrap: bool,
+    #[serde(default)]
+    pub font_size: Option<f64>,
+    #[serde(default)]
+    pub font_color: Option<String>,
+    #[serde(default)]
+    pub background: Option<String>,
+    /// left, center, right
+    #[serde(default)]
+    pub align: Option<String>,
+    /// top, center, bottom
+    #[serde(default)]
+    pub valign: Option<String>,
+    /// thin, medium, thick, double, dashed, dotted
+    #[serde(default)]
+    pub border: Option<String>,
+    #[serde(default)]
+    pub border_top: Option<String>,
+    #[serde(default)]
+    pub border_bottom: Option<String>,
+    #[serde(default)]
+    pub border_left: Option<String>,
+    #[serde(default)]
+    pub border_right: Option<String>,
+    #[serde(default)]
+    pub border_color: Option<String>,
+}
+
+impl ReportTemplate {
+    /// Загружает шаблон вида отчета из [templates_dir], если там нет файла то берется встроенный шаблон
+    pub fn load<K: ReportKind + ?Sized>(kind: &K, templates_dir: &str) -> Result<ReportTemplate, ResponseError> {
+        let file_name = format!("{}.json", kind.slug());
+        let path = Path::new(templates_dir).join(&file_name);
+
+        let content = if !templates_dir.trim().is_empty() && path.exists() {
+            fs::read_to_string(&path)
+                .map_err(|error| (6543201, format!("Не удалось прочитать шаблон {}: {}", path.display(), error)))?
+        } else {
+            kind.default_template().to_string()
+        };
+
+        let template: ReportTemplate = serde_json::from_str(&content)
+            .map_err(|error| (6543202, format!("Не удалось разобрать шаблон {}: {}", file_name, error)))?;
+
+        template.validate().map_err(|error| (6543203, format!("Шаблон {} не корректный: {}", file_name, error)))?;
+
+        Ok(template)
+    }
+
+    /// Проверяет что все форматы, на которые ссылается шаблон, описаны и разбираются
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, format) in self.formats.iter() {
+            format.to_format().map_err(|error| format!("формат {}: {}", name, error))?;
+        }
+
+        let check = |format: &Option<String>| -> Result<(), String> {
+            match format {
+                Some(name) if !self.formats.contains_key(name) => Err(format!("формат {} не описан в formats", name)),
+                _ => Ok(()),
+            }
+        };
+
+        for sheet in self.sheets.iter() {
+            for column in sheet.columns.iter() {
+                check(&column.format)?;
+            }
+
+            for block in sheet.blocks.iter() {
+                match block {
+                    BlockTemplate::Cells(cells) => {
+                        check(&cells.fill.as_ref().map(|fill| fill.format.clone()))?;
+                        for cell in cells.cells.iter() {
+                            check(&cell.format)?;
+                        }
+                    }
+                    BlockTemplate::Table(table) => {
+                        if !matches!(table.source, TableSource::Refill | TableSource::Reconciliation) && table.item.is_none() {
+                            return Err(format!("у таблицы {:?} на листе {} не указан item", table.source, sheet.name));
+                        }
+
+                        check(&table.header_format)?;
+                        for column in table.columns.iter() {
+                            check(&column.format)?;
+                        }
+                        if let Some(fee) = table.fee.as_ref() {
+                            check(&fee.label_format)?;
+                            check(&fee.format)?;
+                        }
+                        if let Some(totals) = table.totals.as_ref() {
+                            check(&totals.format)?;
+                            for cell in totals.cells.iter() {
+                                check(&cell.format)?;
+                            }
+                        }
+                        if let Some(month_subtotals) = table.month_subtotals.as_ref() {
+                            if table.source != TableSource::Days || table.group_by.is_some() || table.aggregate_by.is_some() {
+                                return Err(format!("итоги по месяцам есть только у days без group_by и aggregate_by, а не у {:?}", table.source));
+                            }
+
+                            check(&month_subtotals.format)?;
+                        }
+                        if let Some(status_columns) = table.status_columns.as_ref() {
+                            if !matches!(table.source, TableSource::Days | TableSource::SummaryByProvider) {
+                                return Err(format!("колонки по статусам есть только у days и summary_by_provider, а не у {:?}", table.source));
+                            }
+
+                            check(&status_columns.header_format)?;
+                            for column in status_columns.columns.iter() {
+                                if !matches!(column.field, TableField::Transactions | TableField::Amount) {
+                                    return Err(format!("в колонках по статусам нет поля {:?}", column.field));
+                                }
+                                check(&column.format)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Все форматы шаблона в виде форматов xlsx
+    pub fn xlsx_formats(&self) -> Result<HashMap<String, Format>, ResponseError> {
+        self.formats
+            .iter()
+            .map(|(name, format)| {
+                format
+                    .to_format()
+                    .map(|xlsx_format| (name.clone(), xlsx_format))
+                    .map_err(|error| (6543203, format!("Формат {} не корректный: {}", name, error)))
+            })
+            .collect()
+    }
+}
+
+impl CellFormat {
+    pub fn to_format(&self) -> Result<Format, String> {
+        let mut format = Format::new();
+
+        if self.bold {
+            format = format.set_bold();
+        }
+        if self.text_wrap {
+            format = format.set_text_wrap();
+        }
+        if let Some(font_size) = self.font_size {
+            format = format.set_font_size(font_size);
+        }
+        if let Some(color) = self.font_color.as_ref() {
+            format = format.set_font_color(parse_color(color)?);
+        }
+        if let Some(color) = self.background.as_ref() {
+            format = format.set_background_color(parse_color(color)?);
+        }
+        if let Some(align) = self.align.as_ref() {
+            format = format.set_align(match align.as_str() {
+                "left" => FormatAlign::Left,
+                "center" => FormatAlign::Center,
+                "right" => FormatAlign::Right,
+                _ => return Err(format!("не известное выравнивание {}", align)),
+            });
+        }
+        if let Some(valign) = self.valign.as_ref() {
+            format = format.set_align(match valign.as_str() {
+                "top" => FormatAlign::Top,
+                "center" => FormatAlign::VerticalCenter,
+                "bottom" => FormatAlign::Bottom,
+                _ => return Err(format!("не известное вертикальное выравнивание {}", valign)),
+            });
+        }
+        if let Some(border) = self.border.as_ref() {
+            format = format.set_border(parse_border(border)?);
+        }
+        if let Some(border) = self.border_top.as_ref() {
+            format = format.set_border_top(parse_border(border)?);
+        }
+        if let Some(border) = self.border_bottom.as_ref() {
+            format = format.set_border_bottom(parse_border(border)?);
+        }
+        if let Some(border) = self.border_left.as_ref() {
+            format = format.set_border_left(parse_border(border)?);
+        }
+        if let Some(border) = self.border_right.as_ref() {
+            format = format.set_border_right(parse_border(border)?);
+        }
+        if let Some(color) = self.border_color.as_ref() {
+            format = format.set_border_color(parse_color(color)?);
+        }
+
+        Ok(format)
+    }
+}
+
+fn parse_color(color: &str) -> Result<XlsxColor, String> {
+    match color {
+        "white" => return Ok(XlsxColor::White),
+        "black" => return Ok(XlsxColor::Black),
+        "automatic" => return Ok(XlsxColor::Automatic),
+        _ => {}
+    }
+
+    color
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .map(XlsxColor::RGB)
+        .ok_or_else(|| format!("не корректный цвет {}", color))
+}
+
+fn parse_border(border: &str) -> Result<FormatBorder, String> {
+    match border {
+        "none" => Ok(FormatBorder::None),
+        "thin" => Ok(FormatBorder::Thin),
+        "medium" => Ok(FormatBorder::Medium),
+        "thick" => Ok(FormatBorder::Thick),
+        "double" => Ok(FormatBorder::Double),
+        "dashed" => Ok(FormatBorder::Dashed),
+        "dotted" => Ok(FormatBorder::Dotted),
+        _ => Err(format!("не известная граница {}", border)),
+    }
+}
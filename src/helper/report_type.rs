@@ -1,4 +1,28 @@
 pub mod agent;
 pub mod taxi_company;
 pub mod merchant;
+pub mod terminal;
 pub mod constants;
+
+use crate::helper::report_type::agent::agent_report::AgentReport;
+use crate::helper::report_type::merchant::merchant::MerchantReport;
+use crate::helper::report_type::taxi_company::taxi_company::TaxiCompanyReport;
+use crate::helper::report_type::terminal::terminal::TerminalReport;
+use crate::r#trait::report_kind::ReportKind;
+
+/// [Report kinds] Все виды отчетов которые можно сгенерировать.
+/// Новый вид отчета добавляется сюда, поле report_type запроса ищется по [ReportKind::name]
+pub static REPORT_KINDS: [&'static dyn ReportKind; 4] = [
+    &AgentReport,
+    &TaxiCompanyReport,
+    &MerchantReport,
+    &TerminalReport,
+];
+
+/// Ищет вид отчета по имени без учета регистра
+pub fn find_report_kind(name: &str) -> Option<&'static dyn ReportKind> {
+    REPORT_KINDS
+        .iter()
+        .find(|kind| kind.name().eq_ignore_ascii_case(name))
+        .copied()
+}
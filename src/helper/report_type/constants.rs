@@ -1,4 +1,19 @@
 pub const WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME: &'static str = "Общий отчет о платежах";
 pub const SUMMARY_BY_PROVIDER_NAME: &'static str = "Сводная по провайдеру";
 pub const SUMMARY_BY_DAY_NAME: &'static str = "Сводная по дням";
+pub const SUMMARY_BY_MONTH_NAME: &'static str = "Сравнение по месяцам";
+pub const COMPARISON_NAME: &'static str = "Сравнение периодов";
+pub const COMPARISON_BY_PROVIDER_NAME: &'static str = "Сравнение по провайдерам";
+pub const COMPARISON_BY_DAY_NAME: &'static str = "Сравнение по дням";
 pub const WORKSHEET_SUMMARY_BY_REFILL_NAME: &'static str = "Пополнение счета";
+pub const RECONCILIATION_NAME: &'static str = "Сверка пополнений";
+
+// Заголовок и подписи листа "Общий отчет о платежах" в pdf, в xlsx они задаются шаблоном (templates/)
+pub const GENERAL_REPORT_COMPANY_NAME: &'static str = "Payment System Platorm LLC";
+pub const GENERAL_REPORT_COMPANY_FORM: &'static str = "ОБЩЕСТВО С ОГРАНИЧЕННОЙ ОТВЕТСТВЕННОСТЬЮ";
+pub const GENERAL_REPORT_SENDER_AND_RECEIVER: [&'static str; 3] = [
+    "Платежная Организация",
+    "Payment System Platorm LLC",
+    "Платежный агент",
+];
+pub const GENERAL_REPORT_DIRECTOR_NAME: &'static str = "Яхтанигов А.М __________________ ";
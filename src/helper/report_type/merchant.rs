@@ -1,58 +1,68 @@
 pub mod merchant {
-    use std::sync::Arc;
-    use rust_xlsxwriter::Workbook;
-    use tokio::sync::RwLock as TokioRwLock;
-    use crate::helper::generate_xlsx::create_general_payment_report;
-    use crate::helper::report_type::constants::WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME;
-    use crate::helper::working_with_xlsx_list::sheet_creator::sheet_creator::create_worksheet;
-    use crate::r#type::types::ResponseError;
-    use crate::share::Report;
+    use crate::helper::generate_csv::general_payment_report_rows_merchant;
+    use crate::helper::generate_pdf::financial_pdf_header;
+    use crate::helper::report_template::render::TemplateContext;
+    use crate::indexing_report_struct::IndexingReport;
+    use crate::r#trait::filter_report::ReportItemType;
+    use crate::r#trait::report_kind::{Organization, ReportKind};
+    use crate::r#type::types::{RecordStrings, ResponseError};
+    use crate::share::ReportItem;
 
+    /// [Merchant report] Финансовый отчет по merchant_id, с разбивкой по платежным системам
+    pub struct MerchantReport;
 
-    pub async fn merchant_report(
-        workbook: &mut Workbook, report: Arc<TokioRwLock<Report>>,
-        creators_first_name: &String, creators_last_name: &String,
-        full_date_from_to: Vec<(usize, String, String)>
-    ) -> Result<(), ResponseError>{
+    impl ReportKind for MerchantReport {
+        fn name(&self) -> &'static str {
+            "Merchant"
+        }
 
-        let report_reader = report.read().await;
+        fn slug(&self) -> &'static str {
+            "merchant"
+        }
 
-        let report_type = report_reader.get_report_type().clone();
-        let provider_name = report_reader.get_provider_name();
-        let formatted_date = report_reader.get_formatted_date();
+        fn item_types(&self) -> &'static [ReportItemType] {
+            &[ReportItemType::Payments, ReportItemType::Remittance]
+        }
+
+        fn organization(&self) -> Organization {
+            Organization::Merchant
+        }
 
-        drop(report_reader);
+        fn required_item(&self) -> ReportItemType {
+            ReportItemType::Payments
+        }
 
-        let worksheet_generate_general_payment_report = create_worksheet(
-            workbook,
-            WORKSHEET_GENERATE_GENERAL_PAYMENT_REPORT_NAME,
-        );
+        fn missing_data_error(&self) -> ResponseError {
+            (4324243, "Не достаточно нуных данных для генерации отчета по Merchant".to_string())
+        }
 
-        if let Err(error) = worksheet_generate_general_payment_report {
-            return Err(error);
+        fn has_summary_sheets(&self) -> bool {
+            false
         }
 
-        let item_report_by_needed_type = report.write().await.get_remittance_and_payments();
+        fn default_template(&self) -> &'static str {
+            include_str!("../../../templates/merchant.json")
+        }
 
-        if let None = item_report_by_needed_type.1 {
-            return Err((4324243, "Не достаточно нуных данных для генерации отчета по Merchant".to_string()));
+        fn aggregate(
+            &self,
+            report_item: &mut ReportItem,
+            records: &Vec<RecordStrings>,
+            collect_indexing: &IndexingReport,
+            _item_type: &ReportItemType,
+        ) -> Result<(), ResponseError> {
+            report_item.build_general_report_merchant(records, collect_indexing)
         }
 
-        // Сделать запись в этом листе
-        if let Err(error) = create_general_payment_report(
-            worksheet_generate_general_payment_report.unwrap(),
-            item_report_by_needed_type,
-            &provider_name,
-            &report_type,
-            None,
-            creators_first_name,
-            creators_last_name,
-            full_date_from_to,
-            formatted_date
-        ) {
-            return Err(error);
-        };
+        fn general_report_rows(&self, context: &TemplateContext) -> Vec<RecordStrings> {
+            general_payment_report_rows_merchant(context.payments.as_ref(), context.currency)
+        }
 
-        Ok(())
+        fn pdf_header(&self, context: &TemplateContext) -> Result<(Vec<String>, Vec<String>), ResponseError> {
+            match context.payments.as_ref() {
+                Some(item_report) => Ok(financial_pdf_header(context, item_report)),
+                None => Err((4324244, "Не достаточно нуных данных для генерации отчета по Merchant".to_string())),
+            }
+        }
     }
 }
\ No newline at end of file
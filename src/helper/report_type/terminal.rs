@@ -0,0 +1,52 @@
+pub mod terminal {
+    use crate::helper::generate_csv::general_payment_report_rows_terminal;
+    use crate::helper::generate_pdf::financial_pdf_header;
+    use crate::helper::report_template::render::TemplateContext;
+    use crate::r#trait::filter_report::ReportItemType;
+    use crate::r#trait::report_kind::{Organization, ReportKind};
+    use crate::r#type::types::{RecordStrings, ResponseError};
+
+    /// [Terminal report] Отчет по терминалам вендора по merchant_id, строится из выгрузки terminal
+    pub struct TerminalReport;
+
+    impl ReportKind for TerminalReport {
+        fn name(&self) -> &'static str {
+            "Terminal"
+        }
+
+        fn slug(&self) -> &'static str {
+            "terminal"
+        }
+
+        fn item_types(&self) -> &'static [ReportItemType] {
+            &[ReportItemType::Terminal]
+        }
+
+        fn organization(&self) -> Organization {
+            Organization::Merchant
+        }
+
+        fn required_item(&self) -> ReportItemType {
+            ReportItemType::Terminal
+        }
+
+        fn missing_data_error(&self) -> ResponseError {
+            (4324245, "Не достаточно нуных данных для генерации отчета по Terminal".to_string())
+        }
+
+        fn default_template(&self) -> &'static str {
+            include_str!("../../../templates/terminal.json")
+        }
+
+        fn general_report_rows(&self, context: &TemplateContext) -> Vec<RecordStrings> {
+            general_payment_report_rows_terminal(context.terminal.as_ref(), context.currency)
+        }
+
+        fn pdf_header(&self, context: &TemplateContext) -> Result<(Vec<String>, Vec<String>), ResponseError> {
+            match context.terminal.as_ref() {
+                Some(item_report) => Ok(financial_pdf_header(context, item_report)),
+                None => Err(self.missing_data_error()),
+            }
+        }
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::Utc;
+
+use mysql_async::Pool;
+
+use serde::Serialize;
+
+use tracing::{error, info};
+
+use crate::args::{RetentionSettings, Settings};
+use crate::db::report_catalog::ReportCatalog;
+use crate::helper::file_struct::REPORT_EXTENSIONS;
+use crate::r#type::types::ResponseError;
+
+/// Файлы моложе не удаляются ни по одному ограничению: их могут сейчас дописывать или скачивать
+pub const RETENTION_GRACE_SECS: i64 = 1800;
+
+/// [Retention reason] По какому ограничению удален файл
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionReason {
+    /// Файл старше [RetentionSettings::max_age_secs]
+    MaxAge,
+    /// Отчеты пользователя заняли больше [RetentionSettings::user_quota_bytes]
+    UserQuota,
+    /// Все отчеты заняли больше [RetentionSettings::disk_budget_bytes]
+    DiskBudget,
+}
+
+/// [Stored report] Файл отчета на диске: {REPORTS_DIR}/reports/{user_id}/{file_name}
+#[derive(Debug, Clone)]
+pub struct StoredReport {
+    pub user_id: String,
+    pub file_name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// Unix время последнего изменения файла
+    pub modified: i64,
+}
+
+/// [Removed report] Удаленный файл, то что пишется в лог и в [RetentionSettings::export_path]
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedReport {
+    pub user_id: String,
+    pub file_name: String,
+    pub size: u64,
+    pub modified: i64,
+    pub removed_at: i64,
+    pub reason: RetentionReason,
+}
+
+/// Удаляет файлы отчетов по ограничениям из [Settings::retention] и возвращает что было удалено.
+/// Закрепленные отчеты берутся из каталога, если каталог недоступен то ничего не удаляется
+pub async fn apply_retention(settings: &Settings, pool: &Pool) -> Result<Vec<RemovedReport>, ResponseError> {
+    let retention = &settings.retention;
+
+    if !retention.is_enabled() {
+        return Ok(Vec::new());
+    }
+
+    let catalog = ReportCatalog::new(pool, settings);
+    let pinned = catalog.pinned_files().await?;
+
+    // Обход папок и удаление файлов блокирующие, они идут в отдельном потоке и не занимают поток рантайма
+    let reports_dir = settings.get_reports_dir().to_string();
+    let reports = tokio::task::spawn_blocking(move || scan_reports(&reports_dir))
+        .await
+        .map_err(|error| (6651303, format!("Ошибка при обработке задачи: {:?}", error)))??;
+    let now = Utc::now().timestamp();
+
+    let mut removed = Vec::new();
+
+    for (report, reason) in select_for_removal(reports, &pinned, retention, now) {
+        let path = report.path.clone();
+        let result = tokio::task::spawn_blocking(move || std::fs::remove_file(path))
+            .await
+            .map_err(|error| format!("{:?}", error))
+            .and_then(|result| result.map_err(|error| error.to_string()));
+
+        if let Err(error) = result {
+            error!("Не удалось удалить отчет {}: {}", report.path.display(), error);
+            continue;
+        }
+
+        info!("Отчет {} пользователя {} удален с диска ({:?}, {} байт)", report.file_name, report.user_id, reason, report.size);
+
+        // Файла уже нет, запись в каталоге вела бы на не существующий отчет
+        if let Err(error) = catalog.remove(&report.user_id, &report.file_name).await {
+            error!("Не удалось удалить отчет {} из каталога, code: {} message: {}", report.file_name, error.0, error.1);
+        }
+
+        removed.push(RemovedReport {
+            user_id: report.user_id,
+            file_name: report.file_name,
+            size: report.size,
+            modified: report.modified,
+            removed_at: now,
+            reason,
+        });
+    }
+
+    // Файлы уже удалены, ошибка выгрузки не должна терять то что попало в лог
+    if !retention.export_path.is_empty() && !removed.is_empty() {
+        let export_path = retention.export_path.clone();
+        let export = removed.clone();
+        let result = tokio::task::spawn_blocking(move || export_removed(&export_path, &export))
+            .await
+            .map_err(|error| (6651303, format!("Ошибка при обработке задачи: {:?}", error)))
+            .and_then(|result| result);
+
+        if let Err(error) = result {
+            error!("code: {} message: {}", error.0, error.1);
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Все файлы отчетов в папках пользователей. Файлы с другими расширениями не трогаем
+pub fn scan_reports(reports_dir: &str) -> Result<Vec<StoredReport>, ResponseError> {
+    let root = Path::new(reports_dir).join("reports");
+
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let read_error = |path: &Path, error: std::io::Error| (6651301, format!("Не удалось прочитать {}: {}", path.display(), error));
+
+    let mut reports = Vec::new();
+
+    for user_dir in std::fs::read_dir(&root).map_err(|error| read_error(&root, error))? {
+        let user_dir = match user_dir {
+            Ok(user_dir) if user_dir.path().is_dir() => user_dir,
+            _ => continue,
+        };
+
+        let user_id = user_dir.file_name().to_string_lossy().to_string();
+
+        for file in std::fs::read_dir(user_dir.path()).map_err(|error| read_error(&user_dir.path(), error))? {
+            let file = match file {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+
+            let path = file.path();
+
+            let is_report = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| REPORT_EXTENSIONS.contains(&extension));
+
+            let metadata = match file.metadata() {
+                Ok(metadata) if is_report && metadata.is_file() => metadata,
+                _ => continue,
+            };
+
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |modified| modified.as_secs() as i64);
+
+            reports.push(StoredReport {
+                user_id: user_id.clone(),
+                file_name: file.file_name().to_string_lossy().to_string(),
+                path,
+                size: metadata.len(),
+                modified,
+            });
+        }
+    }
+
+    Ok(reports)
+}
+
+/// Выбирает файлы для удаления, самые старые первыми:
+/// сначала файлы старше max_age, потом сверх квоты пользователя, потом сверх общего бюджета.
+/// Закрепленные и свежие файлы не удаляются, но место которое они занимают учитывается в квоте и бюджете
+pub fn select_for_removal(
+    mut reports: Vec<StoredReport>,
+    pinned: &HashSet<(String, String)>,
+    retention: &RetentionSettings,
+    now: i64,
+) -> Vec<(StoredReport, RetentionReason)> {
+    reports.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.path.cmp(&b.path)));
+
+    let is_removable = |report: &StoredReport| {
+        now - report.modified >= RETENTION_GRACE_SECS
+            && !pinned.contains(&(report.user_id.clone(), report.file_name.clone()))
+    };
+
+    let mut selected = Vec::new();
+    let mut kept = Vec::new();
+
+    for report in reports {
+        if retention.max_age_secs > 0 && is_removable(&report) && now - report.modified > retention.max_age_secs as i64 {
+            selected.push((report, RetentionReason::MaxAge));
+        } else {
+            kept.push(report);
+        }
+    }
+
+    if retention.user_quota_bytes > 0 {
+        let mut usage: HashMap<String, u64> = HashMap::new();
+        for report in kept.iter() {
+            *usage.entry(report.user_id.clone()).or_insert(0) += report.size;
+        }
+
+        let mut next = Vec::new();
+        for report in kept {
+            let user_usage = usage.get_mut(&report.user_id).expect("usage посчитан для каждого пользователя");
+
+            if *user_usage > retention.user_quota_bytes && is_removable(&report) {
+                *user_usage -= report.size;
+                selected.push((report, RetentionReason::UserQuota));
+            } else {
+                next.push(report);
+            }
+        }
+        kept = next;
+    }
+
+    if retention.disk_budget_bytes > 0 {
+        let mut total: u64 = kept.iter().map(|report| report.size).sum();
+
+        for report in kept {
+            if total > retention.disk_budget_bytes && is_removable(&report) {
+                total -= report.size;
+                selected.push((report, RetentionReason::DiskBudget));
+            }
+        }
+    }
+
+    selected
+}
+
+/// Дописывает удаленные отчеты в файл [path], по одному json на строку
+fn export_removed(path: &str, removed: &[RemovedReport]) -> Result<(), ResponseError> {
+    let export_error = |error: String| (6651302, format!("Не удалось записать удаленные отчеты в {}: {}", path, error));
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|error| export_error(error.to_string()))?;
+
+    let mut lines = String::new();
+    for report in removed {
+        lines.push_str(&serde_json::to_string(report).map_err(|error| export_error(error.to_string()))?);
+        lines.push('\n');
+    }
+
+    file.write_all(lines.as_bytes()).map_err(|error| export_error(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const DAY: i64 = 86400;
+
+    /// Отчет [file_name] пользователя [user_id] измененный [age] секунд назад
+    fn report(user_id: &str, file_name: &str, size: u64, age: i64) -> StoredReport {
+        StoredReport {
+            user_id: user_id.to_string(),
+            file_name: file_name.to_string(),
+            path: PathBuf::from(format!("/reports/{}/{}", user_id, file_name)),
+            size,
+            modified: NOW - age,
+        }
+    }
+
+    fn retention(max_age_secs: u64, user_quota_bytes: u64, disk_budget_bytes: u64) -> RetentionSettings {
+        RetentionSettings {
+            interval_secs: 3600,
+            max_age_secs,
+            user_quota_bytes,
+            disk_budget_bytes,
+            export_path: String::new(),
+        }
+    }
+
+    fn pinned(files: &[(&str, &str)]) -> HashSet<(String, String)> {
+        files.iter().map(|(user_id, file_name)| (user_id.to_string(), file_name.to_string())).collect()
+    }
+
+    fn select(
+        reports: Vec<StoredReport>,
+        pinned: &HashSet<(String, String)>,
+        retention: &RetentionSettings,
+    ) -> Vec<(String, RetentionReason)> {
+        select_for_removal(reports, pinned, retention, NOW)
+            .into_iter()
+            .map(|(report, reason)| (report.file_name, reason))
+            .collect()
+    }
+
+    fn removed(files: &[(&str, RetentionReason)]) -> Vec<(String, RetentionReason)> {
+        files.iter().map(|(file_name, reason)| (file_name.to_string(), *reason)).collect()
+    }
+
+    #[test]
+    fn max_age_removes_only_old_files() {
+        let reports = vec![
+            report("1", "new.xlsx", 10, 2 * DAY),
+            report("1", "old.xlsx", 10, 10 * DAY),
+        ];
+
+        assert_eq!(
+            select(reports, &pinned(&[]), &retention(5 * DAY as u64, 0, 0)),
+            removed(&[("old.xlsx", RetentionReason::MaxAge)])
+        );
+    }
+
+    #[test]
+    fn grace_period_keeps_fresh_files() {
+        let reports = vec![
+            report("1", "fresh.xlsx", 10, RETENTION_GRACE_SECS - 1),
+            report("1", "grace_ended.xlsx", 10, RETENTION_GRACE_SECS),
+        ];
+
+        // max_age меньше grace периода, но свежий файл все равно остается
+        assert_eq!(
+            select(reports, &pinned(&[]), &retention(60, 0, 0)),
+            removed(&[("grace_ended.xlsx", RetentionReason::MaxAge)])
+        );
+    }
+
+    #[test]
+    fn pinned_files_are_kept() {
+        let reports = vec![
+            report("1", "pinned.xlsx", 100, 10 * DAY),
+            report("2", "pinned.xlsx", 100, 10 * DAY),
+        ];
+
+        // Закреплен файл только первого пользователя, у второго файл с тем же именем удаляется
+        assert_eq!(
+            select(reports, &pinned(&[("1", "pinned.xlsx")]), &retention(DAY as u64, 10, 10)),
+            removed(&[("pinned.xlsx", RetentionReason::MaxAge)])
+        );
+    }
+
+    #[test]
+    fn user_quota_counts_pinned_and_fresh_files() {
+        let reports = vec![
+            report("1", "pinned.xlsx", 50, 5 * DAY),
+            report("1", "oldest.xlsx", 20, 4 * DAY),
+            report("1", "older.xlsx", 20, 3 * DAY),
+            report("1", "old.xlsx", 20, 2 * DAY),
+            report("1", "fresh.xlsx", 30, 60),
+            report("2", "other_user.xlsx", 50, 10 * DAY),
+        ];
+
+        // У первого пользователя 140 байт при квоте 100: закрепленный и свежий файл не удаляются,
+        // но занимают 80 байт, поэтому удаляются два самых старых из остальных
+        assert_eq!(
+            select(reports, &pinned(&[("1", "pinned.xlsx")]), &retention(0, 100, 0)),
+            removed(&[
+                ("oldest.xlsx", RetentionReason::UserQuota),
+                ("older.xlsx", RetentionReason::UserQuota),
+            ])
+        );
+    }
+
+    #[test]
+    fn disk_budget_removes_oldest_files_of_all_users() {
+        let reports = vec![
+            report("1", "first.xlsx", 40, 3 * DAY),
+            report("2", "second.xlsx", 40, 2 * DAY),
+            report("1", "third.xlsx", 40, DAY),
+            report("2", "fresh.xlsx", 30, 60),
+        ];
+
+        // Всего 150 байт при бюджете 100, свежий файл учитывается но не удаляется
+        assert_eq!(
+            select(reports, &pinned(&[]), &retention(0, 0, 100)),
+            removed(&[
+                ("first.xlsx", RetentionReason::DiskBudget),
+                ("second.xlsx", RetentionReason::DiskBudget),
+            ])
+        );
+    }
+
+    #[test]
+    fn files_are_selected_oldest_first_by_each_limit_in_turn() {
+        let reports = vec![
+            report("2", "kept_other.xlsx", 50, 3 * DAY),
+            report("1", "budget.xlsx", 50, 4 * DAY),
+            report("1", "max_age.xlsx", 50, 20 * DAY),
+            report("1", "quota.xlsx", 50, 5 * DAY),
+            report("1", "kept.xlsx", 50, 2 * DAY),
+            report("2", "b.xlsx", 10, 30 * DAY),
+            report("2", "a.xlsx", 10, 30 * DAY),
+        ];
+
+        // Сначала max_age, потом квота первого пользователя (после max_age у него 150 байт при квоте 100),
+        // потом бюджет (осталось 150 байт при бюджете 100). Одинаковое время изменения упорядочено по пути
+        assert_eq!(
+            select(reports, &pinned(&[]), &retention(10 * DAY as u64, 100, 100)),
+            removed(&[
+                ("a.xlsx", RetentionReason::MaxAge),
+                ("b.xlsx", RetentionReason::MaxAge),
+                ("max_age.xlsx", RetentionReason::MaxAge),
+                ("quota.xlsx", RetentionReason::UserQuota),
+                ("budget.xlsx", RetentionReason::DiskBudget),
+            ])
+        );
+    }
+
+    #[test]
+    fn disabled_limits_remove_nothing() {
+        let reports = vec![report("1", "old.xlsx", 1000, 100 * DAY)];
+
+        assert!(select(reports, &pinned(&[]), &retention(0, 0, 0)).is_empty());
+    }
+}
@@ -1,3 +1,6 @@
+pub mod column_mapping;
+
+use crate::indexing_report_struct::column_mapping::{Column, ColumnMapping};
 use crate::r#trait::filter_report::ReportItemType;
 use crate::r#type::types::ResponseError;
 
@@ -19,7 +22,8 @@ pub struct IndexingReport {
     pub index_vendor: Option<usize>,
     pub index_merchant_id: Option<usize>,
     pub index_tran_type: Option<usize>,
-    pub index_payment_system: Option<usize>
+    pub index_payment_system: Option<usize>,
+    pub index_terminal_id: Option<usize>,
 }
 
 impl IndexingReport {
@@ -42,119 +46,86 @@ impl IndexingReport {
             index_tran_type: None,
             index_merchant_id: None,
             index_payment_system: None,
+            index_terminal_id: None,
         }
     }
 
-    /// Поиск индекса по заголовкам
-    pub fn find_index_by_name(&mut self, record: Vec<&str>, report_type: ReportItemType) {
+    /// Поиск индекса по заголовкам.
+    /// Названия колонок берутся из [ColumnMapping] по типу файла [file_type] (pay, c2card, terminal и т.д)
+    pub fn find_index_by_name(&mut self, record: Vec<&str>, file_type: &str, column_mapping: &ColumnMapping) {
         for (index, field) in record.into_iter().enumerate() {
-            // Поля которые есть в [Платежах] и [Переводов]
-            match field.to_lowercase().as_str() {
-                "провайдер" => self.index_provider = Some(index),
-                "provider_id" => self.index_provider_id = Some(index),
-                "статус" => self.index_status = Some(index),
-                "режим" => self.index_mode = Some(index),
-                "сумма" => self.index_amount = Some(index),
-                "комиссия" => self.index_commission = Some(index),
-                "комиссия eops" | "commission_eops" => {
-                    self.index_commission_eops = Some(index)
-                }
-                "комиссия COMANYNAME" | "commission_COMANYNAME" => {
-                    self.index_commission_sys = Some(index)
-                }
-                "комиссия bank" | "commission_bank" => {
-                    self.index_commission_bank = Some(index)
-                }
-                "комиссия partner" | "commission_partner" => {
-                    self.index_commission_partner = Some(index)
-                }
-                "дата транзакции" => self.index_date = Some(index),
-                _ => {}
-            }
-            match report_type {
-                // Поля которые есть только в [Переводах]
-                ReportItemType::Remittance => match field.to_lowercase().as_str() {
-                    "tran_type" => self.index_tran_type = Some(index),
-                    _ => {}
-                },
-                // Поля которые есть только в [Платежах]
-                ReportItemType::Payments => match field.to_lowercase().as_str() {
-                    "комиссия payment" => self.index_commission_payment = Some(index),
-                    "commission_secondbank" => self.commission_secondbank = Some(index),
-                    "вендор" => self.index_vendor = Some(index),
-                    "вендор id" => self.index_merchant_id = Some(index),
-                    "платёжная система" => self.index_payment_system = Some(index),
-                    _ => {},
-                },
-                ReportItemType::Unknown => {}
-                ReportItemType::Empty => {}
-                ReportItemType::Null => {}
+            if let Some(column) = column_mapping.find_column(file_type, field) {
+                *self.index_mut(column) = Some(index);
             }
         }
     }
 
-    // Проверка на обязательные поля
+    /// Индекс колонки [column], None если колонка не найдена
+    pub fn index(&self, column: Column) -> Option<usize> {
+        match column {
+            Column::Provider => self.index_provider,
+            Column::ProviderId => self.index_provider_id,
+            Column::Status => self.index_status,
+            Column::Mode => self.index_mode,
+            Column::Amount => self.index_amount,
+            Column::Date => self.index_date,
+            Column::Commission => self.index_commission,
+            Column::CommissionCompany => self.index_commission_sys,
+            Column::CommissionBank => self.index_commission_bank,
+            Column::CommissionEops => self.index_commission_eops,
+            Column::CommissionPartner => self.index_commission_partner,
+            Column::CommissionPayment => self.index_commission_payment,
+            Column::CommissionSecondbank => self.commission_secondbank,
+            Column::TranType => self.index_tran_type,
+            Column::Vendor => self.index_vendor,
+            Column::MerchantId => self.index_merchant_id,
+            Column::PaymentSystem => self.index_payment_system,
+            Column::TerminalId => self.index_terminal_id,
+        }
+    }
+
+    fn index_mut(&mut self, column: Column) -> &mut Option<usize> {
+        match column {
+            Column::Provider => &mut self.index_provider,
+            Column::ProviderId => &mut self.index_provider_id,
+            Column::Status => &mut self.index_status,
+            Column::Mode => &mut self.index_mode,
+            Column::Amount => &mut self.index_amount,
+            Column::Date => &mut self.index_date,
+            Column::Commission => &mut self.index_commission,
+            Column::CommissionCompany => &mut self.index_commission_sys,
+            Column::CommissionBank => &mut self.index_commission_bank,
+            Column::CommissionEops => &mut self.index_commission_eops,
+            Column::CommissionPartner => &mut self.index_commission_partner,
+            Column::CommissionPayment => &mut self.index_commission_payment,
+            Column::CommissionSecondbank => &mut self.commission_secondbank,
+            Column::TranType => &mut self.index_tran_type,
+            Column::Vendor => &mut self.index_vendor,
+            Column::MerchantId => &mut self.index_merchant_id,
+            Column::PaymentSystem => &mut self.index_payment_system,
+            Column::TerminalId => &mut self.index_terminal_id,
+        }
+    }
+
+    /// Проверка на обязательные поля, в ошибке перечисляются все не найденные колонки
     pub fn check_which_fields_not_found(
         &self,
         report_type: ReportItemType,
+        file_type: &str,
+        column_mapping: &ColumnMapping,
     ) -> Result<(), ResponseError> {
-        // =========================================================================================== \\
-        if self.index_commission.is_none() {
-            return Err((423134, "index_commission field is None".to_string()));
-        }
-        if self.index_date.is_none() {
-            return Err((8423141, "index_date field is None".to_string()));
-        }
-        if self.index_provider.is_none() {
-            return Err((423142, "index_Provider field is None".to_string()));
-        }
-        if self.index_provider_id.is_none() {
-            return Err((423143, "index_provider_id field is None".to_string()));
-        }
-        if self.index_mode.is_none() {
-            return Err((423144, "index_mode field is None".to_string()));
-        }
-        if self.index_status.is_none() {
-            return Err((423145, "index_status field is None".to_string()));
-        }
-        if self.index_amount.is_none() {
-            return Err((423146, "index_amount field is None".to_string()));
-        }
-        if self.index_commission_sys.is_none() {
-            return Err((423135, "index_commission_sys field is None".to_string()));
-        }
-        if self.index_commission_bank.is_none() {
+        let not_found = Column::required(&report_type)
+            .into_iter()
+            .filter(|column| self.index(*column).is_none())
+            .map(|column| format!("{} ({})", column.name(), column_mapping.aliases(file_type, column).join(", ")))
+            .collect::<Vec<String>>();
+
+        if !not_found.is_empty() {
             return Err((
-                423136,
-                "index_commission_bank field is None".to_string(),
+                423149,
+                format!("В файле с типом {} не найдены колонки: {}", file_type, not_found.join("; "))
             ));
         }
-        if self.index_commission_eops.is_none() {
-            return Err((423138, "index_commission_eops field is None".to_string()));
-        }
-        if self.index_commission_partner.is_none() {
-            return Err((423139, "index_commission_partner field is None".to_string()));
-        }
-        // =========================================================================================== \\
-
-        match report_type {
-            ReportItemType::Remittance => {
-                if self.index_tran_type.is_none() {
-                    return Err((423147, "index_commission_payment field is None".to_string()));
-                }
-            }
-            ReportItemType::Payments => {
-                if self.index_commission_payment.is_none() {
-                    return Err((423137, "index_commission_payment field is None".to_string()));
-                }
-                if self.index_vendor.is_none() {
-                    return Err((423147, "index_Merchantfield is None".to_string()));
-                }
-            }
-            ReportItemType::Unknown => {}
-            ReportItemType::Empty => {}
-            ReportItemType::Null => {}
-        }
 
         Ok(())
     }
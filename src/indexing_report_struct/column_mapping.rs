@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fs;
+
+use serde::Deserialize;
+
+use crate::r#trait::filter_report::ReportItemType;
+use crate::r#type::types::ResponseError;
+
+/// Названия колонок поставляемые вместе с сервисом
+const DEFAULT_COLUMN_MAPPING: &str = include_str!("../../column_mapping.toml");
+
+/// [Column] Колонка csv файла, индекс которой ищется в [IndexingReport]
+///
+/// [IndexingReport]: crate::indexing_report_struct::IndexingReport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    Provider,
+    ProviderId,
+    Status,
+    Mode,
+    Amount,
+    Date,
+    Commission,
+    CommissionCompany,
+    CommissionBank,
+    CommissionEops,
+    CommissionPartner,
+    CommissionPayment,
+    CommissionSecondbank,
+    TranType,
+    Vendor,
+    MerchantId,
+    PaymentSystem,
+    TerminalId,
+}
+
+const COLUMNS: [Column; 18] = [
+    Column::Provider,
+    Column::ProviderId,
+    Column::Status,
+    Column::Mode,
+    Column::Amount,
+    Column::Date,
+    Column::Commission,
+    Column::CommissionCompany,
+    Column::CommissionBank,
+    Column::CommissionEops,
+    Column::CommissionPartner,
+    Column::CommissionPayment,
+    Column::CommissionSecondbank,
+    Column::TranType,
+    Column::Vendor,
+    Column::MerchantId,
+    Column::PaymentSystem,
+    Column::TerminalId,
+];
+
+impl Column {
+    /// Имя колонки в файле с названиями колонок
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Provider => "provider",
+            Column::ProviderId => "provider_id",
+            Column::Status => "status",
+            Column::Mode => "mode",
+            Column::Amount => "amount",
+            Column::Date => "date",
+            Column::Commission => "commission",
+            Column::CommissionCompany => "commission_company",
+            Column::CommissionBank => "commission_bank",
+            Column::CommissionEops => "commission_eops",
+            Column::CommissionPartner => "commission_partner",
+            Column::CommissionPayment => "commission_payment",
+            Column::CommissionSecondbank => "commission_secondbank",
+            Column::TranType => "tran_type",
+            Column::Vendor => "vendor",
+            Column::MerchantId => "merchant_id",
+            Column::PaymentSystem => "payment_system",
+            Column::TerminalId => "terminal_id",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Column> {
+        COLUMNS.iter().find(|column| column.name() == name).copied()
+    }
+
+    /// Колонки без которых не собрать часть отчета [item_type]
+    pub fn required(item_type: &ReportItemType) -> Vec<Column> {
+        let mut columns = vec![
+            Column::Commission,
+            Column::Date,
+            Column::Provider,
+            Column::ProviderId,
+            Column::Mode,
+            Column::Status,
+            Column::Amount,
+        ];
+
+        match item_type {
+            ReportItemType::Remittance => {
+                columns.extend([
+                    Column::CommissionCompany, Column::CommissionBank,
+                    Column::CommissionEops, Column::CommissionPartner,
+                    Column::TranType,
+                ]);
+            }
+            ReportItemType::Payments => {
+                columns.extend([
+                    Column::CommissionCompany, Column::CommissionBank,
+                    Column::CommissionEops, Column::CommissionPartner,
+                    Column::CommissionPayment, Column::Vendor,
+                ]);
+            }
+            ReportItemType::Terminal => columns.push(Column::TerminalId),
+            ReportItemType::Unknown | ReportItemType::Empty | ReportItemType::Null => {}
+        }
+
+        columns
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FileColumnMapping {
+    #[serde(default)]
+    common: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    file_types: Vec<FileTypeColumns>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileTypeColumns {
+    file_types: Vec<String>,
+    #[serde(default)]
+    columns: HashMap<String, Vec<String>>,
+}
+
+/// [Column mapping] Варианты заголовков колонок csv по типам файлов.
+/// Заголовки хранятся уже приведенными через [normalize_header]
+#[derive(Debug, Clone)]
+pub struct ColumnMapping {
+    /// Колонки которые есть во всех типах файлов
+    common: Vec<(Column, Vec<String>)>,
+    /// Колонки отдельных типов файлов: типы файлов и их колонки
+    file_types: Vec<(Vec<String>, Vec<(Column, Vec<String>)>)>,
+}
+
+impl ColumnMapping {
+    /// Загружает названия колонок из [path], если путь пустой то берутся названия поставляемые с сервисом
+    pub fn load(path: &str) -> Result<ColumnMapping, ResponseError> {
+        let content = if path.trim().is_empty() {
+            DEFAULT_COLUMN_MAPPING.to_string()
+        } else {
+            fs::read_to_string(path)
+                .map_err(|error| (6543220, format!("Не удалось прочитать файл с названиями колонок {}: {}", path, error)))?
+        };
+
+        ColumnMapping::parse(&content)
+            .map_err(|error| (6543221, format!("Файл с названиями колонок не корректный: {}", error)))
+    }
+
+    fn parse(content: &str) -> Result<ColumnMapping, String> {
+        let file: FileColumnMapping = toml::from_str(content).map_err(|error| error.to_string())?;
+
+        let mut file_types = Vec::new();
+
+        for group in file.file_types {
+            if group.file_types.is_empty() {
+                return Err("у группы колонок не указаны file_types".to_string());
+            }
+
+            file_types.push((group.file_types, parse_columns(group.columns)?));
+        }
+
+        Ok(ColumnMapping {
+            common: parse_columns(file.common)?,
+            file_types,
+        })
+    }
+
+    /// Колонки файла с типом [file_type], сначала общие потом колонки типа
+    fn columns_for<'a>(&'a self, file_type: &'a str) -> impl Iterator<Item = &'a (Column, Vec<String>)> + 'a {
+        self.common.iter().chain(
+            self.file_types
+                .iter()
+                .filter(move |(file_types, _)| file_types.iter().any(|tp| tp == file_type))
+                .flat_map(|(_, columns)| columns.iter())
+        )
+    }
+
+    /// Какая колонка стоит под заголовком [header] в файле с типом [file_type]
+    pub fn find_column(&self, file_type: &str, header: &str) -> Option<Column> {
+        let header = normalize_header(header);
+
+        self.columns_for(file_type)
+            .find(|(_, aliases)| aliases.iter().any(|alias| alias == &header))
+            .map(|(column, _)| *column)
+    }
+
+    /// Все варианты заголовка колонки [column] в файле с типом [file_type]
+    pub fn aliases(&self, file_type: &str, column: Column) -> Vec<String> {
+        self.columns_for(file_type)
+            .filter(|(mapped_column, _)| mapped_column == &column)
+            .flat_map(|(_, aliases)| aliases.iter().cloned())
+            .collect()
+    }
+}
+
+fn parse_columns(columns: HashMap<String, Vec<String>>) -> Result<Vec<(Column, Vec<String>)>, String> {
+    let mut parsed = Vec::new();
+
+    for (name, aliases) in columns {
+        let column = Column::from_name(&name).ok_or(format!("не известная колонка {}", name))?;
+
+        if aliases.is_empty() {
+            return Err(format!("у колонки {} не указано ни одного названия", name));
+        }
+
+        parsed.push((column, aliases.iter().map(|alias| normalize_header(alias)).collect()));
+    }
+
+    Ok(parsed)
+}
+
+/// Приводит заголовок к виду в котором он сравнивается:
+/// без BOM и пробелов по краям, в нижнем регистре, "ё" заменяется на "е"
+pub fn normalize_header(header: &str) -> String {
+    header
+        .trim_start_matches('\u{feff}')
+        .trim()
+        .to_lowercase()
+        .replace('ё', "е")
+}
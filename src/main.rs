@@ -1,10 +1,10 @@
 pub mod args;
 mod db;
-pub mod download_report_chunks;
 pub mod error;
 pub mod handlers;
 pub mod helper;
 pub mod indexing_report_struct;
+pub mod money;
 pub mod routes;
 pub mod share;
 pub mod r#trait;
@@ -15,36 +15,30 @@ mod api_server;
 
 use std::env;
 use std::io::{Error, ErrorKind};
-use std::sync::{Arc, Mutex};
 
 use actix_web::web::Data;
 
-use dotenv_codegen::dotenv;
-
 use env_logger::Env;
 
 use crate::share::Share;
 
-use crate::r#trait::chunks_trait::IDownloadReportChunks;
-
 use crate::args::Settings;
 
 use crate::db::connect::connect_to_database;
+use crate::db::report_catalog::ReportCatalog;
+use crate::db::used_links::UsedLinks;
 
-use tokio::sync::{Mutex as TokioMutex, RwLock as TokioRwLock};
+use tokio::sync::RwLock as TokioRwLock;
 
 use tracing;
 use tracing::{error, info, Level};
 use tracing_subscriber::FmtSubscriber;
 use crate::r#type::types::ReportsStorage;
 use crate::server::tokens_storage::TokensStorage;
-use crate::tokio_tasks::tokio_tasks::{launch_database_handlers, launch_share_handlers, launch_token_handlers};
+use crate::tokio_tasks::tokio_tasks::{launch_database_handlers, launch_retention_handlers, launch_share_handlers, launch_token_handlers};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let address = dotenv!("HOST_ADDRESS");
-    let port = dotenv!("HOST_PORT").parse::<u16>().unwrap_or(8080);
-
     let subscriber = FmtSubscriber::builder()
         // TRACE
         .with_max_level(Level::INFO)
@@ -76,69 +70,72 @@ async fn main() -> std::io::Result<()> {
         ),
     }
 
+    // Настройки читаются при старте: toml файл (если указан) и переменные окружения
+    if let Err(error) = settings.load() {
+        error!("Autotuning: не удалось загрузить настройки:\nmessage: {}\ncode: {}", error.1, error.0);
+        return Err(Error::new(ErrorKind::InvalidInput, error.1));
+    }
+
     info!("{}", format!("Settings: {:#?}", settings));
     info!(
         "\n
             host address: {}\n
             host port: {}\n
         ",
-        address,
-        port
+        settings.host_address,
+        settings.host_port
     );
-    if settings.get_prod() {
-            info!("
-                \nmysql host: {}\n
-                mysql port: {}\n
-                mysql user: {}\n
-                mysql password: {}\n
-                mysql database: {}\n
-            ",
-                dotenv!("GLOBAL_MYSQL_HOST"),
-                dotenv!("GLOBAL_MYSQL_PORT"),
-                dotenv!("GLOBAL_MYSQL_USER"),
-                dotenv!("GLOBAL_MYSQL_PASSWORD"),
-                dotenv!("GLOBAL_MYSQL_DATABASE")
-            );
-    } else {
-        info!(
-            "\n
-            mysql host: {}\n
-            mysql port: {}\n
-            mysql user: {}\n
-            mysql password: {}\n
-            mysql database: {}\n
-            ",
-            dotenv!("LOCAL_MYSQL_HOST"),
-            dotenv!("LOCAL_MYSQL_PORT"),
-            dotenv!("LOCAL_MYSQL_USER"),
-            dotenv!("LOCAL_MYSQL_PASSWORD"),
-            dotenv!("LOCAL_MYSQL_DATABASE")
-        );
+
+    let mysql = settings.get_mysql();
+    info!("
+        \nmysql host: {}\n
+        mysql port: {}\n
+        mysql user: {}\n
+        mysql database: {}\n
+    ",
+        mysql.host,
+        mysql.port,
+        mysql.user,
+        mysql.database
+    );
+
+    // Шифрование при скачивании отчетов, init нужен до первого обращения к sodiumoxide из нескольких потоков
+    if sodiumoxide::init().is_err() {
+        error!("Не удалось инициализировать sodiumoxide");
+        return Err(Error::new(ErrorKind::Other, "Не удалось инициализировать sodiumoxide"));
     }
 
     let db = connect_to_database(&settings).await;
     if let Err(error) = db {
-        if settings.get_prod() {
-            error!("Не удалось подключиться к базе данных: {}\naddress: {}\nport: {}", error.1, dotenv!("GLOBAL_MYSQL_HOST"), dotenv!("GLOBAL_MYSQL_PORT"));
-        } else {
-            error!("Не удалось подключиться к базе данных: {}\naddress: {}\nport: {}", error.1, dotenv!("LOCAL_MYSQL_HOST"), dotenv!("LOCAL_MYSQL_PORT"));
-        }
+        error!("Не удалось подключиться к базе данных: {}\naddress: {}\nport: {}", error.1, mysql.host, mysql.port);
         return Err(Error::new(
             ErrorKind::NotConnected,
             format!("Не удалось подключиться к базе данных: {}", error.1)
         ));
     }
 
+    let pool = db.unwrap();
+
+    // Каталог отчетов хранится в той же базе, таблица создается при первом запуске
+    if let Err(error) = ReportCatalog::new(&pool, &settings).create_table().await {
+        error!("code: {} message: {}", error.0, error.1);
+        return Err(Error::new(ErrorKind::Other, error.1));
+    }
+
+    // Использованные одноразовые ссылки тоже в базе, чтобы перезапуск их не сбрасывал
+    if let Err(error) = UsedLinks::new(&pool, &settings).create_table().await {
+        error!("code: {} message: {}", error.0, error.1);
+        return Err(Error::new(ErrorKind::Other, error.1));
+    }
+
     let tokens_storage = Data::new(TokioRwLock::new(TokensStorage::new()));
 
-    let share: ReportsStorage = Data::new(TokioRwLock::new(Share::new()));
-    let report_download_chunks = Data::new(Arc::new(Mutex::new(
-        download_report_chunks::DownloadReportChunks::new(),
-    )));
+    let share: ReportsStorage = Data::new(TokioRwLock::new(Share::new(settings.max_number_of_reports_in_share)));
 
-    let conn_db = Data::new(Arc::new(TokioMutex::new(
-        db.unwrap(),
-    )));
+    // Pool внутри себя держит Arc, клон нужен чтобы корректно закрыть соединения после остановки сервера
+    let conn_db = Data::new(pool.clone());
+    let address = settings.host_address.clone();
+    let port = settings.host_port;
     let settings = Data::new(settings);
 
     env_logger::init_from_env(Env::default().default_filter_or("info"));
@@ -146,26 +143,33 @@ async fn main() -> std::io::Result<()> {
 
     info!("launching `database worker`...");
     let _database_worker = launch_database_handlers(Data::clone(&conn_db), Data::clone(&settings)).await;
-    info!("share worker has been launched.");
+    info!("database worker has been launched.");
 
     info!("launching `share worker`...");
     let _share_worker = launch_share_handlers(Data::clone(&share)).await;
     info!("share worker has been launched.");
 
+    info!("launching `retention worker`...");
+    let _retention_worker = launch_retention_handlers(Data::clone(&settings), Data::clone(&conn_db)).await;
+    info!("retention worker has been launched.");
+
     info!("launching `tokens worker`...");
     let _tokens_worker = launch_token_handlers(Data::clone(&tokens_storage)).await;
     info!("tokens worker has been launched.");
 
     info!("launching `server`");
     let _server = server::server::run(
-        address,
+        address.as_str(),
         port,
-        report_download_chunks,
         settings,
         conn_db,
         share,
         tokens_storage
     ).await;
 
+    if let Err(error) = pool.disconnect().await {
+        error!("Не удалось корректно закрыть подключения к базе данных: {}", error);
+    }
+
     Ok(())
 }
@@ -0,0 +1,388 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::r#type::types::ResponseError;
+
+/// Сколько знаков после запятой хранится внутри [Money].
+/// Округление до копеек/тийинов делается только при записи в отчет по [CurrencyRule]
+pub const MONEY_SCALE: u32 = 6;
+
+const MONEY_FACTOR: i128 = 10_i128.pow(MONEY_SCALE);
+
+/// [Money] Денежная сумма с фиксированной точкой.
+/// Хранится как целое число миллионных долей, поэтому сложение не накапливает ошибку как f64
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i128);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    /// Разбирает сумму из csv, допускаются `.` и `,` как разделитель дробной части
+    /// и пробелы между разрядами. Пустая ячейка считается нулем.
+    /// Знаки после [MONEY_SCALE] округляются half up
+    pub fn parse(value: &str) -> Result<Money, ResponseError> {
+        let error = || (5435450, format!("Не удалось разобрать сумму: \"{}\"", value));
+
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(Money::ZERO);
+        }
+
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+
+        let digits = digits
+            .chars()
+            .filter(|character| !character.is_whitespace() && *character != '\u{a0}')
+            .collect::<String>();
+
+        let (integer_part, fraction_part) = match digits.find(['.', ',']) {
+            Some(position) => (&digits[..position], &digits[position + 1..]),
+            None => (digits.as_str(), ""),
+        };
+
+        if integer_part.is_empty() && fraction_part.is_empty() {
+            return Err(error());
+        }
+        if !integer_part.chars().all(|character| character.is_ascii_digit())
+            || !fraction_part.chars().all(|character| character.is_ascii_digit())
+        {
+            return Err(error());
+        }
+
+        let integer = if integer_part.is_empty() {
+            0
+        } else {
+            integer_part.parse::<i128>().map_err(|_| error())?
+        };
+
+        let scale = MONEY_SCALE as usize;
+        let mut fraction = 0_i128;
+        for (position, digit) in fraction_part.bytes().enumerate() {
+            let digit = (digit - b'0') as i128;
+
+            if position < scale {
+                fraction = fraction * 10 + digit;
+                continue;
+            }
+
+            // Первый отброшенный знак решает округление
+            if position == scale && digit >= 5 {
+                fraction += 1;
+            }
+            break;
+        }
+        for _ in fraction_part.len()..scale {
+            fraction *= 10;
+        }
+
+        let units = integer
+            .checked_mul(MONEY_FACTOR)
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or_else(error)?;
+
+        Ok(Money(if negative { -units } else { units }))
+    }
+
+    /// Сумма из ячейки записи, если индекс не найден или ячейки нет, то ноль.
+    /// Не разобранная сумма это ошибка, иначе итоги отчета будут неверными
+    pub fn from_record(record: &[String], index: Option<usize>) -> Result<Money, ResponseError> {
+        match index.and_then(|index| record.get(index).map(|value| (index, value))) {
+            Some((index, value)) => Money::parse(value)
+                .map_err(|error| (error.0, format!("{} в колонке {}", error.1, index + 1))),
+            None => Ok(Money::ZERO),
+        }
+    }
+
+    /// Целая сумма без дробной части
+    pub fn from_integer(value: i64) -> Money {
+        Money(value as i128 * MONEY_FACTOR)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Округляет сумму до [CurrencyRule::minor_units] знаков по правилу валюты
+    pub fn round(&self, rule: &CurrencyRule) -> Money {
+        let step = 10_i128.pow(MONEY_SCALE - rule.minor_units.min(MONEY_SCALE));
+        let quotient = self.0 / step;
+        let remainder = self.0 % step;
+
+        if remainder == 0 {
+            return *self;
+        }
+
+        let away_from_zero = match rule.rounding {
+            RoundingMode::HalfUp => remainder.abs() * 2 >= step,
+            RoundingMode::HalfEven => {
+                let doubled = remainder.abs() * 2;
+                doubled > step || (doubled == step && quotient % 2 != 0)
+            }
+            RoundingMode::Down => false,
+            RoundingMode::Up => true,
+        };
+
+        let quotient = if away_from_zero { quotient + self.0.signum() } else { quotient };
+
+        Money(quotient * step)
+    }
+
+    /// Округленная по правилу валюты сумма для записи числом в xlsx
+    pub fn to_f64(&self, rule: &CurrencyRule) -> f64 {
+        let rounded = self.round(rule).0;
+        (rounded / MONEY_FACTOR) as f64 + (rounded % MONEY_FACTOR) as f64 / MONEY_FACTOR as f64
+    }
+
+    /// Округленная по правилу валюты сумма с фиксированным количеством знаков
+    pub fn format(&self, rule: &CurrencyRule) -> String {
+        let minor_units = rule.minor_units.min(MONEY_SCALE);
+        let rounded = self.round(rule).0;
+        let sign = if rounded < 0 { "-" } else { "" };
+        let integer = (rounded / MONEY_FACTOR).abs();
+
+        if minor_units == 0 {
+            return format!("{}{}", sign, integer);
+        }
+
+        let fraction = (rounded % MONEY_FACTOR).abs() / 10_i128.pow(MONEY_SCALE - minor_units);
+
+        format!("{}{}.{:0width$}", sign, integer, fraction, width = minor_units as usize)
+    }
+}
+
+/// Полная сумма без округления, лишние нули в конце отбрасываются
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let integer = (self.0 / MONEY_FACTOR).abs();
+        let fraction = (self.0 % MONEY_FACTOR).abs();
+
+        if fraction == 0 {
+            return write!(f, "{}{}", sign, integer);
+        }
+
+        let fraction = format!("{:0width$}", fraction, width = MONEY_SCALE as usize);
+
+        write!(f, "{}{}.{}", sign, integer, fraction.trim_end_matches('0'))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, money| total + money)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |total, money| total + *money)
+    }
+}
+
+// Сумма сериализуется строкой, чтобы не терять точность в json
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum MoneyValue {
+            Text(String),
+            Integer(i64),
+            Float(f64),
+        }
+
+        let text = match MoneyValue::deserialize(deserializer)? {
+            MoneyValue::Text(text) => text,
+            MoneyValue::Integer(number) => number.to_string(),
+            MoneyValue::Float(number) => number.to_string(),
+        };
+
+        Money::parse(&text).map_err(|error| serde::de::Error::custom(error.1))
+    }
+}
+
+/// [Rounding mode] Как округляется сумма до [CurrencyRule::minor_units] знаков
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// 0.5 округляется от нуля: 1.005 -> 1.01
+    HalfUp,
+    /// 0.5 округляется к четному (банковское округление): 1.005 -> 1.00, 1.015 -> 1.02
+    HalfEven,
+    /// Отбрасываем лишние знаки (к нулю)
+    Down,
+    /// Любой остаток округляется от нуля
+    Up,
+}
+
+/// [Currency rule] Правило округления сумм для валюты отчета
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencyRule {
+    /// [Code] Код валюты, к примеру UZS
+    pub code: String,
+    /// [Minor units] Количество знаков после запятой в отчете
+    pub minor_units: u32,
+    /// [Rounding] Правило округления
+    pub rounding: RoundingMode,
+}
+
+impl CurrencyRule {
+    pub fn new(code: &str, minor_units: u32, rounding: RoundingMode) -> CurrencyRule {
+        CurrencyRule {
+            code: code.to_string(),
+            minor_units,
+            rounding,
+        }
+    }
+
+    /// Валюты которые доступны без настройки
+    pub fn defaults() -> Vec<CurrencyRule> {
+        vec![
+            CurrencyRule::new("UZS", 2, RoundingMode::HalfUp),
+            CurrencyRule::new("USD", 2, RoundingMode::HalfUp),
+            CurrencyRule::new("RUB", 2, RoundingMode::HalfUp),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(value: &str) -> Money {
+        Money::parse(value).unwrap()
+    }
+
+    #[test]
+    fn parse_separators_and_sign() {
+        assert_eq!(money("1234.56").to_string(), "1234.56");
+        assert_eq!(money("1234,56"), money("1234.56"));
+        assert_eq!(money("1 234 567,5"), money("1234567.5"));
+        assert_eq!(money("1\u{a0}000"), money("1000"));
+        assert_eq!(money("-10.5").to_string(), "-10.5");
+        assert_eq!(money("+3"), money("3"));
+        assert_eq!(money(".5"), money("0.5"));
+        assert_eq!(money(""), Money::ZERO);
+        assert_eq!(money("  "), Money::ZERO);
+    }
+
+    #[test]
+    fn parse_rounds_extra_fraction_digits_half_up() {
+        assert_eq!(money("0.1234565").to_string(), "0.123457");
+        assert_eq!(money("0.1234564999").to_string(), "0.123456");
+        assert_eq!(money("1.99999995").to_string(), "2");
+        assert_eq!(money("-0.0000005").to_string(), "-0.000001");
+        assert_eq!(money("-0.00000049").to_string(), "0");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_values() {
+        for value in ["abc", "1.2.3", "1,2,3", "-", ",", "12a", "1e5", "--1"] {
+            assert_eq!(Money::parse(value).map_err(|error| error.0), Err(5435450), "{}", value);
+        }
+    }
+
+    #[test]
+    fn from_record_reports_column() {
+        let record = vec!["2024-01-01".to_string(), "12,5".to_string(), "n/a".to_string()];
+
+        assert_eq!(Money::from_record(&record, Some(1)).unwrap(), money("12.5"));
+        assert_eq!(Money::from_record(&record, Some(5)).unwrap(), Money::ZERO);
+        assert_eq!(Money::from_record(&record, None).unwrap(), Money::ZERO);
+
+        let error = Money::from_record(&record, Some(2)).unwrap_err();
+        assert_eq!(error.0, 5435450);
+        assert!(error.1.ends_with("в колонке 3"), "{}", error.1);
+    }
+
+    #[test]
+    fn round_half_up() {
+        let rule = CurrencyRule::new("UZS", 2, RoundingMode::HalfUp);
+
+        assert_eq!(money("1.005").round(&rule), money("1.01"));
+        assert_eq!(money("1.004999").round(&rule), money("1.00"));
+        assert_eq!(money("1.015").round(&rule), money("1.02"));
+        assert_eq!(money("-1.005").round(&rule), money("-1.01"));
+        assert_eq!(money("-1.004999").round(&rule), money("-1.00"));
+        assert_eq!(money("2.5").round(&CurrencyRule::new("UZS", 0, RoundingMode::HalfUp)), money("3"));
+    }
+
+    #[test]
+    fn round_half_even() {
+        let rule = CurrencyRule::new("UZS", 2, RoundingMode::HalfEven);
+
+        assert_eq!(money("1.005").round(&rule), money("1.00"));
+        assert_eq!(money("1.015").round(&rule), money("1.02"));
+        assert_eq!(money("1.005001").round(&rule), money("1.01"));
+        assert_eq!(money("-1.025").round(&rule), money("-1.02"));
+        assert_eq!(money("-1.035").round(&rule), money("-1.04"));
+
+        let rule = CurrencyRule::new("UZS", 0, RoundingMode::HalfEven);
+        assert_eq!(money("2.5").round(&rule), money("2"));
+        assert_eq!(money("3.5").round(&rule), money("4"));
+    }
+
+    #[test]
+    fn round_down_and_up() {
+        assert_eq!(money("1.009").round(&CurrencyRule::new("UZS", 2, RoundingMode::Down)), money("1.00"));
+        assert_eq!(money("-1.009").round(&CurrencyRule::new("UZS", 2, RoundingMode::Down)), money("-1.00"));
+        assert_eq!(money("1.001").round(&CurrencyRule::new("UZS", 2, RoundingMode::Up)), money("1.01"));
+        assert_eq!(money("-1.001").round(&CurrencyRule::new("UZS", 2, RoundingMode::Up)), money("-1.01"));
+    }
+
+    #[test]
+    fn format_with_currency_rule() {
+        let rule = CurrencyRule::new("UZS", 2, RoundingMode::HalfUp);
+
+        assert_eq!(money("1234.5").format(&rule), "1234.50");
+        assert_eq!(money("-0.005").format(&rule), "-0.01");
+        assert_eq!(money("7").format(&CurrencyRule::new("UZS", 0, RoundingMode::HalfUp)), "7");
+    }
+}
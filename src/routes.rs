@@ -2,22 +2,39 @@ use actix_web::web;
 
 use crate::handlers::not_found::handle_not_found;
 use crate::handlers::{
-    download_report::Streamer, generate_report::generate_report,
-    get_file_weight::get_file_weight, get_share::get_share,
+    download_link::create_download_link, download_report::Streamer, generate_report::generate_report,
+    get_file_weight::get_file_weight, get_job::get_job,
+    get_report_data::{get_report_data, get_report_reconciliation}, get_reports::get_reports, get_share::get_share,
+    pin_report::{pin_report, unpin_report},
 };
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg
-        // Что-бы сгенерировать excel
+        // Что-бы сгенерировать excel, в ответ приходит задача генерации
         .route("/generate_file", web::post().to(generate_report))
+        // Статус задачи генерации
+        .route("/jobs/{id}", web::get().to(get_job))
+        // Каталог сгенерированных отчетов пользователя, с пагинацией и фильтрами
+        .route("/reports", web::get().to(get_reports))
+        // Закрепленный отчет не удаляется при очистке диска
+        .route("/reports/{file_name}/pin", web::post().to(pin_report))
+        .route("/reports/{file_name}/pin", web::delete().to(unpin_report))
+        // Агрегированные данные отчета в json
+        .route("/reports/{key}/data", web::get().to(get_report_data))
+        // Сводка сверки пополнений счета в json
+        .route("/reports/{key}/reconciliation", web::get().to(get_report_reconciliation))
         // Получаем все данные которые находятся в share
         .route("/get_share", web::get().to(get_share))
         // Получить amount за все дни определенного провайдера с определнными фильтрами
         .service(
             web::scope("/download")
-                // Скачать отчет /{path}
+                // Подписанная ссылка на скачивание отчета без токена, с ограниченным сроком
+                .route("/links", web::post().to(create_download_link))
+                // Скачать отчет /{path}, с Range можно докачать файл с места обрыва
                 .route("/{path}", web::get().to(Streamer::download_report))
-                // Получит вес отчета /get_weight/{path}
+                // Размер, ETag и Last-Modified отчета без скачивания
+                .route("/{path}", web::head().to(Streamer::download_report))
+                // Получит вес отчета /get_weight/{path}, то же что HEAD /{path}
                 .route("/get_weight/{path}", web::get().to(get_file_weight)),
         )
         .route("/{any:.*}", web::get().to(handle_not_found));
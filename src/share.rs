@@ -1,6 +1,9 @@
 pub mod share_helper;
+pub mod report_job;
+pub mod report_data;
+pub mod batch_reports;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
@@ -9,24 +12,30 @@ use serde::{Serialize, Deserialize, Deserializer};
 
 use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
 
-use dotenv_codegen::dotenv;
-
 use tokio::sync::RwLock as TokioRwLock;
+use tokio::task::JoinHandle;
 
+use crate::args::Settings;
+use crate::handlers::generate_report::TypeGenerateReport;
 use crate::r#trait::filter_report::{Filter, ReportType, ReportItemType, Status};
 
 use crate::indexing_report_struct::IndexingReport;
-use crate::r#type::types::{ChunksInReport, RecordStrings, ReportsDateRange, ResponseError};
+use crate::money::Money;
+use crate::r#type::types::{RecordStrings, ReportsDateRange, ResponseError};
 use tracing::error;
-use crate::helper::build_tasks::processing_tasks;
+use crate::helper::chunks::chunk_manager::creator_of_chunks::ChunkReceiver;
 use crate::helper::create_file::create_fs::create_file_name;
+use crate::helper::date::month_of_date;
+use crate::helper::reconciliation::Reconciliation;
 
-use crate::helper::generate_xlsx::create_task;
+use crate::share::report_job::Jobs;
 
 
 #[derive(Debug)]
 pub struct Share {
     pub reports: Reports,
+    /// [Jobs] Задачи генерации отчетов, по ним пользователь узнает статус генерации
+    pub jobs: Jobs,
     generated_now: Arc<Mutex<u16>>,
     max_count_record_in_reports: Arc<Mutex<u16>>,
 }
@@ -49,6 +58,9 @@ pub struct Report {
     /// [create at] Время создания репорта
     /// Report creation time
     pub create_at: i64,
+    /// [Reconciliation] Сверка пополнений счета с транзакциями, есть только если её запросили
+    #[serde(default)]
+    reconciliation: Option<Reconciliation>,
 }
 
 #[derive(Debug, Clone)]
@@ -81,21 +93,25 @@ pub struct ReportItem {
     /// [Filter] Фильтры по которым был собран этот отчет
     pub filter: Filter,
     /// [Amount] Общаяя сумма
-    pub amount: f64,
+    pub amount: Money,
     /// [Days amount] сумма забитая по числу на все дни
-    pub days_amount: Vec<(String, f64)>,
+    pub days_amount: Vec<(String, Money)>,
     /// [Days length transactions] количество транзакций за определенный день
     pub days_len_transaction: Vec<(String, u64)>,
     /// [length transactions] Количество транзакций за весь период отчета
     pub len_transactions: u128,
     /// [commission by day] Коммиссия по дням
-    pub commission_by_day: Vec<(String, f64)>,
+    pub commission_by_day: Vec<(String, Money)>,
     /// [commission] общаяя коммиссия
-    pub commission: f64,
+    pub commission: Money,
     /// [percentage of workload] Процент загруженности отчета
     pub percent_load: f64,
     /// [id of the having chunk] Id имеющих чанков
     pub id_having_chunk: Vec<u32>,
+    /// [file ids] Id файлов, чанки которых уже добавлены в эту часть отчета.
+    /// Повторно тот же файл в нее не добавляется
+    #[serde(default)]
+    pub file_ids: Vec<u32>,
     /// [summary by Provider] Тут содержутся данные по каждому провайдеру
     /// summary_by_Provider.0 = Имя Вендора = Вендор,
     /// summary_by_Provider.1 = Количество транзакций вендора = количество транзакций текущего вендора с определенными филтрами
@@ -103,20 +119,20 @@ pub struct ReportItem {
     /// summary_by_Provider.3 = Сумма комиссии с Поставщика = Комиссия,
     /// summary_by_Provider.4 = Вознаграждение Банка 0,2% = Комиссия AloqBank
     /// summary_by_Provider.5 = Вознаграждение COMANYNAME = Комиссия COMANYNAME
-    pub summary_by_Provider: Vec<(String, u32, f64, f64, f64, f64)>,
+    pub summary_by_Provider: Vec<(String, u32, Money, Money, Money, Money)>,
     /// [general report on payments] Тут содержатся данные только для отчета [TaxiCompany]
     /// general_report_on_payments_taxi_company.0 = Имя Provider
     /// general_report_on_payments_taxi_company.1 = Количество транзакций текущего VENDOR(Provider)
     /// general_report_on_payments_taxi_company.2 = Сумма(amount) без комиссий текущего VENDOR(Provider)
     /// general_report_on_payments_taxi_company.3 = Вознаграждение COMANYNAME(commission) VENDOR(Provider)
-    pub general_report_on_payments_taxi_company: Vec<(String, u128, f64, f64)>,
+    pub general_report_on_payments_taxi_company: Vec<(String, u128, Money, Money)>,
     /// [general report on payments] Тут содержатся данные только для отчета [TaxiCompany]
     /// general_report_on_payments_merchant.0 = Имя VENDOR(Provider) Вендора(провайдера)
     /// general_report_on_payments_merchant.1 = Имя платежной системы
     /// general_report_on_payments_merchant.2 = Количество транзакций текущего VENDOR(Provider)
     /// general_report_on_payments_merchant.3 = Сумма(amount) без комиссий текущего VENDOR(Provider)
     /// general_report_on_payments_merchant.4 = Вознаграждение COMANYNAME(commission) VENDOR(Provider)
-    pub general_report_on_payments_merchant: Vec<(String, String, u128, f64, f64)>,
+    pub general_report_on_payments_merchant: Vec<(String, String, u128, Money, Money)>,
     /// [general report on payments agent] Тут содержутся данные только для отчета [Agent]
     /// general_report_on_payments_agent.0 = Имя провайдера(VENDOR)
     /// general_report_on_payments_agent.1 = Количество транзакций текушего провайдера
@@ -124,57 +140,103 @@ pub struct ReportItem {
     /// general_report_on_payments_agent.3 = Комиссиия текущего провайдера
     /// general_report_on_payments_agent.4 = Возногрождение COMANYNAME COMANYNAME + bank
     /// general_report_on_payments_agent.5 = Возногрождение Агента
-    pub general_report_on_remittance_agent: Vec<(String, u32, f64, f64, f64, f64)>,
+    pub general_report_on_remittance_agent: Vec<(String, u32, Money, Money, Money, Money)>,
     /// [refill amount] Общаяя сумма с пополнение счета
-    pub refill_amount: f64,
+    pub refill_amount: Money,
     /// [days_in_report] дни которые есть в отчете
     pub days_in_report: HashSet<String>,
     /// [all_types_of_commissions] Все виды комиссий включая общию коммиссию
     pub all_types_of_commissions: CommissionType,
+    /// [days by status] Разбивка по дням и статусам, заполняется если в фильтре несколько статусов
+    /// days_by_status.0 = День
+    /// days_by_status.1 = Статус транзакций
+    /// days_by_status.2 = Количество транзакций с этим статусом за день
+    /// days_by_status.3 = Сумма транзакций с этим статусом за день
+    #[serde(default)]
+    pub days_by_status: Vec<(String, Status, u64, Money)>,
+    /// [summary by Provider and status] Разбивка сводной по провайдеру по статусам,
+    /// заполняется если в фильтре несколько статусов
+    /// summary_by_Provider_status.0 = Имя Вендора как в [summary_by_Provider]
+    /// summary_by_Provider_status.1 = Статус транзакций
+    /// summary_by_Provider_status.2 = Количество транзакций вендора с этим статусом
+    /// summary_by_Provider_status.3 = Сумма транзакций вендора с этим статусом
+    #[serde(default)]
+    pub summary_by_Provider_status: Vec<(String, Status, u32, Money)>,
+    /// [summary rows] Строки сводной по провайдеру из [ReportItem::from_chunk],
+    /// в [summary_by_Provider] они сводятся при слиянии в [ReportItem::merge]
+    #[serde(skip)]
+    pub summary_rows: Vec<ProviderSummaryRow>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// [Provider summary row] Строка сводной по провайдеру, посчитанная по одному чанку
+#[derive(Debug, Clone, Default)]
+pub struct ProviderSummaryRow {
+    /// [Key] Значение колонки группировки: tran_type, вендор или терминал
+    pub key: String,
+    /// [Name] Имя которое получит провайдер если его еще нет в сводной
+    pub name: String,
+    /// Количество транзакций
+    pub count: u32,
+    /// Сумма
+    pub amount: Money,
+    /// Сумма комиссии с Поставщика
+    pub commission: Money,
+    /// Вознаграждение Банка 0,2%
+    pub commission_bank: Money,
+    /// Вознаграждение COMANYNAME
+    pub commission_sys: Money,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CommissionType {
     /// Поля под названием "Коммиссия" в листе "Платежи"
-    pub commission: f64,
+    pub commission: Money,
     /// Поля под названием "Комиссия COMANYNAME" в листе "Платежи"
-    pub commission_pay_sys: f64,
+    pub commission_pay_sys: Money,
     /// Поля под названием "Комиссия Bank" в листе "Платежи"
-    pub commission_bank: f64,
+    pub commission_bank: Money,
     /// Поля под названием "Комиссия Payment" в листе "Платежи"
-    pub commission_payment: f64,
+    pub commission_payment: Money,
     /// Поля под названием "Комиссия EOPS" в листе "Платежи"
-    pub commission_eops: f64,
+    pub commission_eops: Money,
     /// Поля под названием "Комиссия Partner" в листе "Платежи"
-    pub commission_partner: f64,
+    pub commission_partner: Money,
+}
+
+impl CommissionType {
+    pub fn add(&mut self, other: &CommissionType) {
+        self.commission += other.commission;
+        self.commission_pay_sys += other.commission_pay_sys;
+        self.commission_bank += other.commission_bank;
+        self.commission_payment += other.commission_payment;
+        self.commission_eops += other.commission_eops;
+        self.commission_partner += other.commission_partner;
+    }
 }
 
 impl ReportItem {
     pub fn new(filter: &Filter) -> ReportItem {
         ReportItem {
             filter: filter.clone(),
-            amount: 0.0,
+            amount: Money::ZERO,
             days_amount: vec![],
             days_len_transaction: vec![],
             len_transactions: 0,
             commission_by_day: vec![],
-            commission: 0.0,
+            commission: Money::ZERO,
             percent_load: 0.0,
             id_having_chunk: vec![],
+            file_ids: vec![],
             summary_by_Provider: vec![],
             general_report_on_payments_taxi_company: vec![],
             general_report_on_payments_merchant: vec![],
             general_report_on_remittance_agent: vec![],
-            refill_amount: 0.0,
+            refill_amount: Money::ZERO,
             days_in_report: HashSet::new(),
-            all_types_of_commissions: CommissionType {
-                commission: 0.0,
-                commission_pay_sys: 0.0,
-                commission_bank: 0.0,
-                commission_payment: 0.0,
-                commission_eops: 0.0,
-                commission_partner: 0.0,
-            },
+            all_types_of_commissions: CommissionType::default(),
+            days_by_status: vec![],
+            summary_by_Provider_status: vec![],
+            summary_rows: vec![],
         }
     }
 
@@ -183,64 +245,42 @@ impl ReportItem {
         records: &Vec<Vec<String>>,
         collect_indexing: &IndexingReport,
         type_report: &ReportItemType,
-    ) {
-        let mut commission = 0.0;
-        let mut commission_pay_sys = 0.0;
-        let mut commission_bank = 0.0;
-        let mut commission_payment = 0.0;
-        let mut commission_eops = 0.0;
-        let mut commission_partner = 0.0;
+    ) -> Result<(), ResponseError> {
+        let mut commissions = CommissionType::default();
 
         // Перебераем все виды комисий
-        records.iter().for_each(|record| {
+        for record in records {
             // Сохраняем число комиссий содержащиеся в чанке в переменную
-            commission += record[collect_indexing.index_commission.unwrap()]
-                .parse::<f64>()
-                .unwrap();
-            commission_pay_sys += record[collect_indexing.index_commission_sys.unwrap()]
-                .parse::<f64>()
-                .unwrap();
-            commission_bank += record[collect_indexing.index_commission_bank.unwrap()]
-                .parse::<f64>()
-                .unwrap();
-            commission_eops += record[collect_indexing.index_commission_eops.unwrap()]
-                .parse::<f64>()
-                .unwrap();
-            commission_partner += record[collect_indexing.index_commission_partner.unwrap()]
-                .parse::<f64>()
-                .unwrap();
+            commissions.commission += Money::from_record(record, collect_indexing.index_commission)?;
+            commissions.commission_pay_sys += Money::from_record(record, collect_indexing.index_commission_sys)?;
+            commissions.commission_bank += Money::from_record(record, collect_indexing.index_commission_bank)?;
+            commissions.commission_eops += Money::from_record(record, collect_indexing.index_commission_eops)?;
+            commissions.commission_partner += Money::from_record(record, collect_indexing.index_commission_partner)?;
             if type_report == &ReportItemType::Payments {
-                commission_payment += record[collect_indexing.index_commission_payment.unwrap()]
-                    .parse::<f64>()
-                    .unwrap();
+                commissions.commission_payment += Money::from_record(record, collect_indexing.index_commission_payment)?;
             }
-        });
+        }
 
         // записываем получившуюся комиссию из чанка в report
-        self.all_types_of_commissions.commission += commission;
-        self.all_types_of_commissions.commission_pay_sys += commission_pay_sys;
-        self.all_types_of_commissions.commission_bank += commission_bank;
-        self.all_types_of_commissions.commission_eops += commission_eops;
-        self.all_types_of_commissions.commission_partner += commission_partner;
-        if type_report == &ReportItemType::Payments {
-            self.all_types_of_commissions.commission_payment += commission_payment;
-        }
+        self.all_types_of_commissions.add(&commissions);
+
+        Ok(())
     }
 
     pub fn calculate_commission(
         &mut self,
         record: &Vec<Vec<String>>,
         collecting_indexing: &IndexingReport,
-    ) {
-        record.iter().for_each(|commission| {
-            self.commission += commission[collecting_indexing.index_commission.unwrap()]
-                .parse::<f64>()
-                .unwrap_or(0.0);
-        })
+    ) -> Result<(), ResponseError> {
+        for commission in record {
+            self.commission += Money::from_record(commission, collecting_indexing.index_commission)?;
+        }
+
+        Ok(())
     }
 
     /// Добовляем в поля [days_amount] дату и сумму переведенную за эту дату
-    pub fn set_days_amount(&mut self, day_amount: Vec<(String, f64)>) {
+    pub fn set_days_amount(&mut self, day_amount: Vec<(String, Money)>) {
         day_amount.into_iter().for_each(|amount| {
             let mut exist_day = false;
             for date_amount in &mut self.days_amount {
@@ -257,7 +297,7 @@ impl ReportItem {
     }
 
     /// Добовляем в поля [commission_by_day] дату и сумму переведенную за эту дату
-    pub fn set_days_commissions(&mut self, day_commission: Vec<(String, f64)>) {
+    pub fn set_days_commissions(&mut self, day_commission: Vec<(String, Money)>) {
         day_commission.into_iter().for_each(|amount| {
             let mut exist_day = false;
             for date_commission in &mut self.commission_by_day {
@@ -282,121 +322,181 @@ impl ReportItem {
     }
 
     // @43252
-    pub fn build_summary_by_Provider(
-        &mut self,
+    /// Строки сводной по провайдеру из чанка. Сводная здесь не трогается, поэтому чанки можно считать параллельно.
+    /// Если у всех строк чанка колонка группировки совпадает с колонкой имени, то строки сразу группируются
+    pub fn build_summary_rows(
         chunk: &Vec<Vec<String>>,
         collect_indexing: &IndexingReport,
         type_report: &ReportItemType,
-    ) -> Result<(), ResponseError> {
-        let index_tran_type_or_merchant= collect_indexing
-            .index_tran_type
-            .unwrap_or(collect_indexing.index_vendor.unwrap_or(0));
-
-        for record in chunk {
-            let mut is_exist_Merchant= false;
-            if self.summary_by_Provider.len() == 0 {
-                match type_report {
-                    ReportItemType::Remittance | ReportItemType::Payments => {
-                        if index_tran_type_or_merchant == 0 {
-                            return Err((
-                                5432520,
-                                "Индекс index_tran_type не был найден!".to_string(),
-                            ));
-                        }
+    ) -> Result<Vec<ProviderSummaryRow>, ResponseError> {
+        let index_tran_type_or_merchant= ReportItem::summary_group_index(collect_indexing);
 
-                        self.summary_by_Provider.push((
-                            // Поставщик
-                            record[index_tran_type_or_merchant].to_string(),
-                            // кол-во
-                            1,
-                            // Сумма
-                            record[collect_indexing.index_amount.unwrap()]
-                                .parse::<f64>()
-                                .unwrap_or(0.0),
-                            // Сумма комиссии с Поставщика
-                            record[collect_indexing.index_commission.unwrap()]
-                                .parse::<f64>()
-                                .unwrap_or(0.0),
-                            // Вознаграждение Банка 0,2%
-                            record[collect_indexing.index_commission_bank.unwrap()]
-                                .parse::<f64>()
-                                .unwrap_or(0.0),
-                            // Вознаграждение COMANYNAME
-                            record[collect_indexing.index_commission_sys.unwrap()]
-                                .parse::<f64>()
-                                .unwrap_or(0.0),
-                        ));
-                    }
-                    ReportItemType::Unknown => {
-                        return Err((
-                            5432521,
-                            "Тип отчета \"Unknown\" не был корректно обработан".to_string(),
-                        ))
-                    }
-                    ReportItemType::Empty => {
-                        return Err((
-                            5432522,
-                            "Тип отчета \"Empty\" не был корректно обработан".to_string(),
-                        ))
-                    }
-                    ReportItemType::Null => {
+        if !chunk.is_empty() {
+            match type_report {
+                ReportItemType::Remittance | ReportItemType::Payments | ReportItemType::Terminal => {
+                    if index_tran_type_or_merchant == 0 {
                         return Err((
-                            5432523,
-                            "Тип отчета \"Null\" не был корректно обработан".to_string(),
-                        ))
+                            5432520,
+                            "Индекс index_tran_type не был найден!".to_string(),
+                        ));
                     }
                 }
-
-                continue;
+                ReportItemType::Unknown => {
+                    return Err((
+                        5432521,
+                        "Тип отчета \"Unknown\" не был корректно обработан".to_string(),
+                    ))
+                }
+                ReportItemType::Empty => {
+                    return Err((
+                        5432522,
+                        "Тип отчета \"Empty\" не был корректно обработан".to_string(),
+                    ))
+                }
+                ReportItemType::Null => {
+                    return Err((
+                        5432523,
+                        "Тип отчета \"Null\" не был корректно обработан".to_string(),
+                    ))
+                }
             }
+        }
 
-            for vendor in self.summary_by_Provider.iter_mut() {
-                // Считаем сумму, комиссию под каждого вендора
-                if vendor.0 == record[index_tran_type_or_merchant] {
-                    vendor.1 += 1;
-                    vendor.2 += record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
-                    vendor.3 += record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
-                    vendor.4 += record[collect_indexing.index_commission_bank.unwrap()]
-                            .parse::<f64>()
-                            .unwrap_or(0.0);
-                    vendor.5 += record[collect_indexing.index_commission_sys.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
-                    is_exist_Merchant= true;
+        // Терминалы называются по номеру терминала, остальные по вендору
+        let index_name = match type_report {
+            ReportItemType::Terminal => index_tran_type_or_merchant,
+            _ => collect_indexing.index_vendor.unwrap_or(index_tran_type_or_merchant),
+        };
+        let is_grouped = chunk.iter().all(|record| record[index_tran_type_or_merchant] == record[index_name]);
+
+        let mut rows: Vec<ProviderSummaryRow> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+
+        for record in chunk {
+            let row = ProviderSummaryRow {
+                key: record[index_tran_type_or_merchant].to_string(),
+                name: record[index_name].to_string(),
+                count: 1,
+                amount: Money::from_record(record, collect_indexing.index_amount)?,
+                commission: Money::from_record(record, collect_indexing.index_commission)?,
+                commission_bank: Money::from_record(record, collect_indexing.index_commission_bank)?,
+                commission_sys: Money::from_record(record, collect_indexing.index_commission_sys)?,
+            };
+
+            if is_grouped {
+                if let Some(position) = positions.get(&row.key) {
+                    let current = &mut rows[*position];
+                    current.count += 1;
+                    current.amount += row.amount;
+                    current.commission += row.commission;
+                    current.commission_bank += row.commission_bank;
+                    current.commission_sys += row.commission_sys;
                     continue;
                 }
+
+                positions.insert(row.key.clone(), rows.len());
             }
 
-            if !is_exist_Merchant{
-                self.summary_by_Provider.push((
-                    // Называние вендора
-                    record[collect_indexing
-                        .index_vendor
-                        .unwrap_or(index_tran_type_or_merchant)]
-                    .to_string(),
-                    // Количество транзакций
-                    1,
-                    // Сумма
-                    record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
-                    // Общаяя коммиссия
-                    record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap(),
-                    // Коммиссия AloqBank
-                    record[collect_indexing.index_commission_bank.unwrap()]
-                        .parse::<f64>()
-                        .unwrap(),
-                    // Коммиссия COMANYNAME
-                    record[collect_indexing.index_commission_sys.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
-                ));
+            rows.push(row);
+        }
+
+        Ok(rows)
+    }
+
+    /// Добавляет строку в [summary_by_Provider] так же как при чтении файла одним проходом:
+    /// первый провайдер называется по колонке группировки, следующие новые по колонке вендора
+    pub fn add_summary_row(&mut self, row: ProviderSummaryRow) {
+        let mut is_exist_vendor = false;
+
+        if self.summary_by_Provider.is_empty() {
+            self.summary_by_Provider.push((
+                // Поставщик
+                row.key,
+                // кол-во
+                row.count,
+                row.amount,
+                row.commission,
+                row.commission_bank,
+                row.commission_sys,
+            ));
+            return;
+        }
+
+        for vendor in self.summary_by_Provider.iter_mut() {
+            // Считаем сумму, комиссию под каждого вендора
+            if vendor.0 == row.key {
+                vendor.1 += row.count;
+                vendor.2 += row.amount;
+                vendor.3 += row.commission;
+                vendor.4 += row.commission_bank;
+                vendor.5 += row.commission_sys;
+                is_exist_vendor = true;
+            }
+        }
+
+        if !is_exist_vendor {
+            self.summary_by_Provider.push((
+                // Называние вендора
+                row.name,
+                // Количество транзакций
+                row.count,
+                // Сумма
+                row.amount,
+                // Общаяя коммиссия
+                row.commission,
+                // Коммиссия AloqBank
+                row.commission_bank,
+                // Коммиссия COMANYNAME
+                row.commission_sys,
+            ));
+        }
+    }
+
+    /// Колонка по которой группируется сводная по провайдеру:
+    /// в переводах группируем по tran_type, в платежах по вендору, в терминалах по терминалу
+    fn summary_group_index(collect_indexing: &IndexingReport) -> usize {
+        // В выгрузке по терминалам есть и вендор, поэтому терминал проверяется первым
+        collect_indexing
+            .index_terminal_id
+            .or(collect_indexing.index_tran_type)
+            .or(collect_indexing.index_vendor)
+            .unwrap_or(0)
+    }
+
+    /// Разбивает количество и сумму по статусам транзакций: по дням и по провайдерам
+    pub fn build_status_breakdown(
+        &mut self,
+        chunk: &Vec<RecordStrings>,
+        collect_indexing: &IndexingReport,
+    ) -> Result<(), ResponseError> {
+        let index_status = match collect_indexing.index_status {
+            Some(index_status) => index_status,
+            None => return Err((5432524, "Индекс index_status не был найден!".to_string())),
+        };
+        let index_date = collect_indexing.index_date.unwrap();
+        let index_group = ReportItem::summary_group_index(collect_indexing);
+
+        for record in chunk {
+            let amount = Money::from_record(record, collect_indexing.index_amount)?;
+            let status = Status::from_label(&record[index_status]);
+            let day = record[index_date].split(" ").next().unwrap_or("").to_string();
+
+            match self.days_by_status.iter_mut().find(|current| current.0 == day && current.1 == status) {
+                Some(current) => {
+                    current.2 += 1;
+                    current.3 += amount;
+                }
+                None => self.days_by_status.push((day, status.clone(), 1, amount)),
+            }
+
+            let vendor = &record[index_group];
+
+            match self.summary_by_Provider_status.iter_mut().find(|current| current.0 == *vendor && current.1 == status) {
+                Some(current) => {
+                    current.2 += 1;
+                    current.3 += amount;
+                }
+                None => self.summary_by_Provider_status.push((vendor.to_string(), status, 1, amount)),
             }
         }
 
@@ -408,36 +508,35 @@ impl ReportItem {
         chunk: &Vec<RecordStrings>,
         collect_indexing: &IndexingReport,
     ) -> Result<(), ResponseError> {
+        let index_provider = collect_indexing
+            .index_provider
+            .ok_or((5432525, "Индекс index_provider не был найден!".to_string()))?;
+
         for record in chunk {
             let mut is_exist_Merchant= false;
+            let amount = Money::from_record(record, collect_indexing.index_amount)?;
+            let commission = Money::from_record(record, collect_indexing.index_commission)?;
+
             if self.general_report_on_payments_taxi_company.len() == 0 {
                 self.general_report_on_payments_taxi_company.push((
                     // Называние вендора(провайдера)
-                    record[collect_indexing.index_provider.unwrap()].to_string(),
+                    record[index_provider].to_string(),
                     // Количество транзакций
                     1,
                     // Сумма
-                    record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
+                    amount,
                     // Общаяя коммиссия
-                    record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
+                    commission,
                 ));
                 continue;
             }
 
             for Provider in self.general_report_on_payments_taxi_company.iter_mut() {
                 // Считаем сумму, комиссию под каждого вендора
-                if Provider.0 == record[collect_indexing.index_provider.unwrap()] {
+                if Provider.0 == record[index_provider] {
                     Provider.1 += 1;
-                    Provider.2 += record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
-                    Provider.3 += record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
+                    Provider.2 += amount;
+                    Provider.3 += commission;
                     is_exist_Merchant= true;
                     continue;
                 }
@@ -446,17 +545,13 @@ impl ReportItem {
             if !is_exist_Merchant{
                 self.general_report_on_payments_taxi_company.push((
                     // Называние вендора
-                    record[collect_indexing.index_provider.unwrap()].to_string(),
+                    record[index_provider].to_string(),
                     // Количество транзакций
                     1,
                     // Сумма
-                    record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
+                    amount,
                     // Общаяя коммиссия
-                    record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap(),
+                    commission,
                 ));
             }
         }
@@ -468,22 +563,27 @@ impl ReportItem {
         &mut self,
         chunk: &Vec<RecordStrings>,
         collect_indexing: &IndexingReport
-    ) {
+    ) -> Result<(), ResponseError> {
+        let index_provider = collect_indexing
+            .index_provider
+            .ok_or((5432526, "Индекс index_provider не был найден!".to_string()))?;
+
         for record in chunk {
             let mut is_exist_Merchant= false;
-            let commission = record[collect_indexing.index_commission.unwrap()].parse::<f64>().unwrap();
-            let pay_sys = record[collect_indexing.index_commission_sys.unwrap()].parse::<f64>().unwrap();
-            let bank = record[collect_indexing.index_commission_bank.unwrap()].parse::<f64>().unwrap();
-            let partner = record[collect_indexing.index_commission_partner.unwrap()].parse::<f64>().unwrap();
+            let amount = Money::from_record(record, collect_indexing.index_amount)?;
+            let commission = Money::from_record(record, collect_indexing.index_commission)?;
+            let pay_sys = Money::from_record(record, collect_indexing.index_commission_sys)?;
+            let bank = Money::from_record(record, collect_indexing.index_commission_bank)?;
+            let partner = Money::from_record(record, collect_indexing.index_commission_partner)?;
 
             if self.general_report_on_remittance_agent.len() == 0 {
                 self.general_report_on_remittance_agent.push((
                     // Название провайдера
-                    record[collect_indexing.index_provider.unwrap()].to_string(),
+                    record[index_provider].to_string(),
                     // Количество
                     1,
                     // Сумма
-                    record[collect_indexing.index_amount.unwrap()].parse::<f64>().unwrap(),
+                    amount,
                     // Комиссия
                     commission,
                     // Вознаграждение COMANYNAME
@@ -496,11 +596,9 @@ impl ReportItem {
 
             for vendor in self.general_report_on_remittance_agent.iter_mut() {
                 // Считаем сумму, комиссию под каждого вендора и его платежную систему
-                if vendor.0 == record[collect_indexing.index_provider.unwrap()] {
+                if vendor.0 == record[index_provider] {
                     vendor.1 += 1;
-                    vendor.2 += record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
+                    vendor.2 += amount;
                     vendor.3 += commission;
                     vendor.4 += pay_sys + bank;
                     vendor.5 += partner;
@@ -512,11 +610,11 @@ impl ReportItem {
             if !is_exist_Merchant{
                 self.general_report_on_remittance_agent.push((
                     // Название провайдера
-                    record[collect_indexing.index_provider.unwrap()].to_string(),
+                    record[index_provider].to_string(),
                     // Количество
                     1,
                     // Сумма
-                    record[collect_indexing.index_amount.unwrap()].parse::<f64>().unwrap(),
+                    amount,
                     // Комиссия
                     commission,
                     // Вознаграждение COMANYNAME
@@ -526,15 +624,20 @@ impl ReportItem {
                 ));
             }
         }
+
+        Ok(())
     }
 
     pub fn build_general_report_merchant(
         &mut self,
         chunk: &Vec<RecordStrings>,
         collect_indexing: &IndexingReport,
-    ) {
+    ) -> Result<(), ResponseError> {
         for record in chunk {
             let mut is_exist_Merchant= false;
+            let amount = Money::from_record(record, collect_indexing.index_amount)?;
+            let commission = Money::from_record(record, collect_indexing.index_commission)?;
+
             if self.general_report_on_payments_merchant.len() == 0 {
                 self.general_report_on_payments_merchant.push((
                     // Называние вендора(провайдера)
@@ -545,13 +648,9 @@ impl ReportItem {
                     // Количество транзакций
                     1,
                     // Сумма
-                    record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
+                    amount,
                     // Общаяя коммиссия
-                    record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
+                    commission,
                 ));
                 continue;
             }
@@ -563,12 +662,8 @@ impl ReportItem {
                     Provider.1.to_lowercase() == record[collect_indexing.index_payment_system.unwrap()].to_lowercase() {
 
                     Provider.2 += 1;
-                    Provider.3 += record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
-                    Provider.4 += record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0);
+                    Provider.3 += amount;
+                    Provider.4 += commission;
                     is_exist_Merchant= true;
                     continue;
                 }
@@ -582,37 +677,220 @@ impl ReportItem {
                     // Количество транзакций
                     1,
                     // Сумма
-                    record[collect_indexing.index_amount.unwrap()]
-                        .parse::<f64>()
-                        .unwrap_or(0.0),
+                    amount,
                     // Общаяя коммиссия
-                    record[collect_indexing.index_commission.unwrap()]
-                        .parse::<f64>()
-                        .unwrap(),
+                    commission,
                 ));
             }
         }
+
+        Ok(())
     }
 
-    pub fn get_min_mount_from_filed_day_in_report(&self) -> u8 {
-        let mut min_mount = 0;
+    /// Считает частичный [ReportItem] по одному чанку.
+    /// Общий отчет здесь не трогается, поэтому чанки можно считать параллельно
+    pub fn from_chunk(
+        filter: &Filter,
+        records: &Vec<RecordStrings>,
+        collect_indexing: &IndexingReport,
+        type_report: &ReportItemType,
+        report_type: &ReportType,
+    ) -> Result<ReportItem, ResponseError> {
+        let mut report_item = ReportItem::new(filter);
 
-        let _ = &self.days_in_report.iter().for_each(|date| {
-            let current_mount = date.split("-").collect::<Vec<&str>>()[1]
-                .parse::<u8>()
-                .unwrap_or(1);
-            if min_mount == 0 {
-                min_mount = current_mount;
+        //===================================================================================================\\
+        // EN Here we get the date and amount for the day that are in the chunk
+        // RU Здесь мы получаем сумму и дни которые доступны в чанке
+        let amount_per_day = Share::calculate_amount_commission(
+            records,
+            collect_indexing.index_amount.unwrap(),
+            collect_indexing,
+        )?;
+        let commission_per_day = Share::calculate_amount_commission(
+            records,
+            collect_indexing.index_commission.unwrap(),
+            collect_indexing,
+        )?;
+        //===================================================================================================\\
+
+        report_item.summary_rows = match ReportItem::build_summary_rows(records, collect_indexing, type_report) {
+            Ok(summary_rows) => summary_rows,
+            Err(error) => {
+                error!("{}", format!("code: {} message {}", error.0, error.1));
+                return Err(error);
             }
-            if current_mount < min_mount {
-                min_mount = current_mount;
+        };
+
+        if filter.has_status_breakdown() {
+            report_item.build_status_breakdown(records, collect_indexing)?;
+        }
+
+        // Агрегации которые нужны только этому виду отчета
+        if let Err(error) = report_type.kind()?.aggregate(&mut report_item, records, collect_indexing, type_report) {
+            error!("{}", format!("code: {} message {}", error.0, error.1));
+            return Err(error);
+        }
+
+        for (day, _) in amount_per_day.iter() {
+            report_item.days_in_report.insert(day.clone());
+        }
+
+        report_item.amount += Share::calculate_amount(records, collect_indexing.index_amount.unwrap_or(8))?;
+        // Считаем все виды комиссии
+        report_item.calculate_all_type_commissions(records, collect_indexing, type_report)?;
+
+        // Считаем комиссию общую комиссию
+        report_item.calculate_commission(records, collect_indexing)?;
+        // Устанавливаем amount за каждый день
+        report_item.set_days_amount(amount_per_day);
+        // Устанавливаем commission за каждый день;
+        report_item.set_days_commissions(commission_per_day);
+
+        // Тут мы добовляем дни в месяце, сортируем чтобы порядок не зависел от HashSet
+        let mut days = report_item.days_in_report.iter().cloned().collect::<Vec<String>>();
+        days.sort();
+        report_item.days_len_transaction = days.into_iter().map(|day| (day, 0)).collect();
+
+        // Считаем количемство транзакций за каждый день
+        Share::count_transaction(
+            records,
+            &mut report_item.days_len_transaction,
+            collect_indexing,
+        );
+        // Устанавливаем общее количество транзакций
+        report_item.set_len_transactions(records.len());
+
+        Ok(report_item)
+    }
+
+    /// Добавляет частичный результат [from_chunk].
+    /// Частичные результаты нужно добавлять в порядке чанков,
+    /// тогда порядок провайдеров и дней такой же как при последовательной обработке
+    pub fn merge(&mut self, partial: ReportItem) {
+        self.amount += partial.amount;
+        self.commission += partial.commission;
+        self.len_transactions += partial.len_transactions;
+
+        self.all_types_of_commissions.add(&partial.all_types_of_commissions);
+
+        self.days_in_report.extend(partial.days_in_report);
+        self.set_days_amount(partial.days_amount);
+        self.set_days_commissions(partial.commission_by_day);
+
+        for (day, len) in partial.days_len_transaction {
+            match self.days_len_transaction.iter_mut().find(|date| date.0 == day) {
+                Some(date) => date.1 += len,
+                None => self.days_len_transaction.push((day, len)),
             }
-        });
+        }
+
+        for row in partial.summary_rows {
+            self.add_summary_row(row);
+        }
+
+        for day in partial.days_by_status {
+            match self.days_by_status.iter_mut().find(|current| current.0 == day.0 && current.1 == day.1) {
+                Some(current) => {
+                    current.2 += day.2;
+                    current.3 += day.3;
+                }
+                None => self.days_by_status.push(day),
+            }
+        }
+
+        for vendor in partial.summary_by_Provider_status {
+            match self.summary_by_Provider_status.iter_mut().find(|current| current.0 == vendor.0 && current.1 == vendor.1) {
+                Some(current) => {
+                    current.2 += vendor.2;
+                    current.3 += vendor.3;
+                }
+                None => self.summary_by_Provider_status.push(vendor),
+            }
+        }
 
-        min_mount
+        for Provider in partial.general_report_on_payments_taxi_company {
+            match self.general_report_on_payments_taxi_company.iter_mut().find(|current| current.0 == Provider.0) {
+                Some(current) => {
+                    current.1 += Provider.1;
+                    current.2 += Provider.2;
+                    current.3 += Provider.3;
+                }
+                None => self.general_report_on_payments_taxi_company.push(Provider),
+            }
+        }
+
+        for Provider in partial.general_report_on_payments_merchant {
+            // Платёжная система сравнивается без учета регистра, как и в [build_general_report_merchant]
+            match self.general_report_on_payments_merchant.iter_mut().find(|current| {
+                current.0 == Provider.0 && current.1.to_lowercase() == Provider.1.to_lowercase()
+            }) {
+                Some(current) => {
+                    current.2 += Provider.2;
+                    current.3 += Provider.3;
+                    current.4 += Provider.4;
+                }
+                None => self.general_report_on_payments_merchant.push(Provider),
+            }
+        }
+
+        for vendor in partial.general_report_on_remittance_agent {
+            match self.general_report_on_remittance_agent.iter_mut().find(|current| current.0 == vendor.0) {
+                Some(current) => {
+                    current.1 += vendor.1;
+                    current.2 += vendor.2;
+                    current.3 += vendor.3;
+                    current.4 += vendor.4;
+                    current.5 += vendor.5;
+                }
+                None => self.general_report_on_remittance_agent.push(vendor),
+            }
+        }
     }
 
-    pub fn set_refill_amount(&mut self, amount: f64)  {
+    /// Месяцы (год, месяц) в которых есть транзакции, по порядку
+    pub fn get_months_in_report(&self) -> Vec<(i32, u32)> {
+        let mut months = self.days_in_report.iter()
+            .filter_map(|day| month_of_date(day))
+            .collect::<Vec<(i32, u32)>>();
+        months.sort();
+        months.dedup();
+
+        months
+    }
+
+    /// Итоги по месяцам в которых есть транзакции, по порядку
+    /// months_totals.0 = Месяц (год, месяц)
+    /// months_totals.1 = Количество транзакций за месяц
+    /// months_totals.2 = Сумма за месяц
+    /// months_totals.3 = Комиссия за месяц
+    pub fn get_months_totals(&self) -> Vec<((i32, u32), u64, Money, Money)> {
+        let mut totals: Vec<((i32, u32), u64, Money, Money)> = self.get_months_in_report()
+            .into_iter()
+            .map(|month| (month, 0, Money::ZERO, Money::ZERO))
+            .collect();
+
+        for (day, count) in self.days_len_transaction.iter() {
+            if let Some(total) = month_of_date(day).and_then(|month| totals.iter_mut().find(|total| total.0 == month)) {
+                total.1 += count;
+            }
+        }
+
+        for (day, amount) in self.days_amount.iter() {
+            if let Some(total) = month_of_date(day).and_then(|month| totals.iter_mut().find(|total| total.0 == month)) {
+                total.2 += *amount;
+            }
+        }
+
+        for (day, commission) in self.commission_by_day.iter() {
+            if let Some(total) = month_of_date(day).and_then(|month| totals.iter_mut().find(|total| total.0 == month)) {
+                total.3 += *commission;
+            }
+        }
+
+        totals
+    }
+
+    pub fn set_refill_amount(&mut self, amount: Money)  {
         self.refill_amount = amount;
     }
 }
@@ -626,34 +904,32 @@ impl Report {
             report_items: HashMap::new(),
             is_report_read: false,
             create_at: Utc::now().timestamp(),
+            reconciliation: None,
         }
     }
 
     pub fn create_empty_item(&mut self, item_type: ReportItemType, filter: &Filter) {
         let empty_item = ReportItem {
             filter: filter.clone(),
-            amount: 0.0,
+            amount: Money::ZERO,
             days_amount: vec![],
             days_len_transaction: vec![],
             len_transactions: 0,
             commission_by_day: vec![],
-            commission: 0.0,
+            commission: Money::ZERO,
             percent_load: 100.0,
             id_having_chunk: vec![],
+            file_ids: vec![],
             summary_by_Provider: vec![],
             general_report_on_payments_taxi_company: vec![],
             general_report_on_payments_merchant: vec![],
             general_report_on_remittance_agent: vec![],
-            refill_amount: 0.0,
+            refill_amount: Money::ZERO,
             days_in_report: Default::default(),
-            all_types_of_commissions: CommissionType {
-                commission: 0.0,
-                commission_pay_sys: 0.0,
-                commission_bank: 0.0,
-                commission_payment: 0.0,
-                commission_eops: 0.0,
-                commission_partner: 0.0,
-            },
+            all_types_of_commissions: CommissionType::default(),
+            days_by_status: vec![],
+            summary_by_Provider_status: vec![],
+            summary_rows: vec![],
         };
 
         self.report_items.insert(item_type, empty_item);
@@ -686,6 +962,14 @@ impl Report {
 
     pub fn get_provider_name(&self) -> String { self.report_organization_name.clone() }
 
+    pub fn set_reconciliation(&mut self, reconciliation: Option<Reconciliation>) {
+        self.reconciliation = reconciliation;
+    }
+
+    pub fn get_reconciliation(&self) -> Option<&Reconciliation> {
+        self.reconciliation.as_ref()
+    }
+
     pub fn get_formatted_date(&self) -> DateTime<Local> {
         Local.timestamp_opt(self.create_at, 0).unwrap()
     }
@@ -702,10 +986,26 @@ impl Report {
         }
     }
 
+    /// Средний процент загруженности по всем элементам отчета
+    pub fn get_percent_load(&self) -> f64 {
+        if self.report_items.is_empty() {
+            return 0.0;
+        }
+
+        let percent_sum: f64 = self.report_items.values().map(|item| item.get_percent_load()).sum();
+
+        (percent_sum / self.report_items.len() as f64).round()
+    }
+
     pub fn get_report_item<'a>(&'a mut self, key: &'a ReportItemType) -> Option<&'a mut ReportItem> {
         self.report_items.get_mut(key)
     }
 
+    /// Часть отчета [key] только для чтения
+    pub fn read_report_item(&self, key: &ReportItemType) -> Option<&ReportItem> {
+        self.report_items.get(key)
+    }
+
     pub fn get_report_type(&self) -> &ReportType {
         &self.report_type
     }
@@ -733,6 +1033,11 @@ impl Report {
         )
     }
 
+    /// Часть отчета с выгрузкой по терминалам
+    pub fn get_terminal(&self) -> Option<ReportItem> {
+        self.read_report_item(&ReportItemType::Terminal).cloned()
+    }
+
     pub fn get_remittance_and_payments_date(&mut self) -> Option<(String, String)> {
         let days_rem = if let Some(item_report) = self.report_items.get(&ReportItemType::Remittance) {
             let days_in_report = item_report.days_in_report.clone();
@@ -741,7 +1046,12 @@ impl Report {
             None
         };
 
-        let days_pay = if let Some(item_report) = self.report_items.get(&ReportItemType::Payments) {
+        // Если платежей нет, период берется из выгрузки по терминалам
+        let payments_or_terminal = self.report_items
+            .get(&ReportItemType::Payments)
+            .or(self.report_items.get(&ReportItemType::Terminal));
+
+        let days_pay = if let Some(item_report) = payments_or_terminal {
             let days_in_report = item_report.days_in_report.clone();
             Some(days_in_report.into_iter().collect::<Vec<String>>())
         } else {
@@ -803,102 +1113,24 @@ impl Report {
         return report_item_type;
     }
 
-    /// Пушим в Share данные для сиаимсимки
-    pub fn push_in_share_records_by_chunks(
+    /// Добавляет в отчет частичный результат по чанку [chunk_num]
+    pub fn merge_partial_item(
         &mut self,
-        records: Vec<Vec<String>>,
+        type_report: &ReportItemType,
+        partial: ReportItem,
         chunk_num: usize,
-        number_of_chunks: usize,
-        collect_indexing: IndexingReport,
-        type_report: ReportItemType,
-        report_type: ReportType,
+        percent: f64,
     ) -> Result<f64, ResponseError> {
-        //===================================================================================================\\
-        // EN Here we get the date and amount for the day that are in the chunk
-        // RU Здесь мы получаем сумму и дни которые доступны в чанке
-        let amount_per_day = Share::calculate_amount_commission(
-            &records,
-            collect_indexing.index_amount.unwrap(),
-            &collect_indexing,
-        );
-        let commission_per_day = Share::calculate_amount_commission(
-            &records,
-            collect_indexing.index_commission.unwrap(),
-            &collect_indexing,
-        );
-        //===================================================================================================\\
-
-        let ref_report_item= self.get_report_item(&type_report);
-
-        if let None = ref_report_item {
-            return Err((4132425, "Не удалось получить часть отчета".to_string()));
-        }
-
-        let report_item = ref_report_item.unwrap();
-
-        if let Err(error) = report_item.build_summary_by_Provider(&records, &collect_indexing, &type_report) {
-            error!("{}", format!("code: {} message {}", error.0, error.1));
-            return Err(error);
-        }
-
-        if report_type == ReportType::TaxiCompany || report_type == ReportType::Agent {
-            if let Err(error) = report_item.build_general_report_taxi_company(&records, &collect_indexing) {
-                error!("{}", format!("code: {} message {}", error.0, error.1));
-                return Err(error);
-            }
-        }
-
-        if report_type == ReportType::Agent && type_report == ReportItemType::Remittance {
-            report_item.build_general_report_agent(&records, &collect_indexing);
-        }
-
-        if report_type == ReportType::Merchant {
-            report_item.build_general_report_merchant(&records, &collect_indexing);
-        }
-
-        for (day, _) in amount_per_day.iter() {
-            if report_item.days_in_report.get(day.as_str()).is_none() {
-                report_item.days_in_report.insert(day.clone());
-            }
-        }
-
-        report_item.amount += Share::calculate_amount(&records,collect_indexing.index_amount.unwrap_or(8));
-        // Считаем все виды комиссии
-        report_item.calculate_all_type_commissions(&records, &collect_indexing, &type_report);
-
-        // Считаем комиссию общую комиссию
-        report_item.calculate_commission(&records, &collect_indexing);
-        // Устанавливаем amount за каждый день
-        report_item.set_days_amount(amount_per_day);
-        // Устанавливаем commission за каждый день;
-        report_item.set_days_commissions(commission_per_day);
-
-        // Тут мы добовляем дни в месяце и их amount
-        for day in &report_item.days_in_report {
-            let mut exist = false;
-            for date in report_item.days_len_transaction.iter() {
-                if date.0 == day.to_string() {
-                    exist = true;
-                    break;
-                }
-            }
-            if !exist {
-                report_item.days_len_transaction.push((day.to_string(), 0));
-            }
-        }
+        let report_item = match self.get_report_item(type_report) {
+            Some(report_item) => report_item,
+            None => return Err((4132425, "Не удалось получить часть отчета".to_string())),
+        };
 
-        // Считаем количемство транзакций за каждый день
-        Share::count_transaction(
-            &records,
-            &mut report_item.days_len_transaction,
-            &collect_indexing,
-        );
-        // Устанавливаем общее количество транзакций
-        report_item.set_len_transactions(records.len());
+        report_item.merge(partial);
         // Добавляем новый id загруженного чанка
         report_item.id_having_chunk.push(chunk_num as u32);
         // Обновляем процент загруженности
-        Share::check_percentage_load_report(report_item, number_of_chunks);
+        Share::check_percentage_load_report(report_item, percent);
 
         Ok(report_item.percent_load)
     }
@@ -915,8 +1147,10 @@ impl Reports {
         report_type: &ReportType,
         organization_provider_id: &str,
         from_to: &ReportsDateRange,
-        s_m_p: (Vec<Status>, Vec<String>, Vec<Vec<String>>),
+        s_m_p: (Vec<Vec<Status>>, Vec<Vec<String>>, Vec<Vec<String>>),
         id: String,
+        output_format: &TypeGenerateReport,
+        expressions: Vec<String>,
     ) -> String {
         let mut statuses = s_m_p.0;
         statuses.sort();
@@ -927,8 +1161,10 @@ impl Reports {
             system.sort();
         });
         payments_system.sort();
+        let mut expressions = expressions;
+        expressions.sort();
 
-        create_file_name(report_type, organization_provider_id, from_to, &statuses, &modes, id, &payments_system)
+        create_file_name(report_type, organization_provider_id, from_to, &statuses, &modes, id, &payments_system, output_format, &expressions)
     }
 
     pub async fn insert_new_report(&self, key: String, report: ArcMutexWrapper<Report>) {
@@ -952,15 +1188,14 @@ impl Reports {
 
 // @43231
 impl Share {
-    pub fn new() -> Share {
+    pub fn new(max_count_record_in_reports: u16) -> Share {
         Share {
             reports: Reports {
                 data: TokioRwLock::new(HashMap::new()),
             },
+            jobs: Jobs::new(),
             generated_now: Arc::new(Mutex::new(0)),
-            max_count_record_in_reports: Arc::new(Mutex::new(dotenv!("MAX_NUMBER_OF_REPORTS_IN_SHARE")
-                .parse::<u16>()
-                .unwrap_or(1000))),
+            max_count_record_in_reports: Arc::new(Mutex::new(max_count_record_in_reports)),
         }
     }
 
@@ -986,32 +1221,30 @@ impl Share {
         let report_arc = Arc::clone(&report.unwrap().0);
         let mut report = report_arc.write().await;
 
-        match report.get_report_item(&ReportItemType::Payments) {
-            Some(item_report) => {
-                let _ = item_report.days_len_transaction.sort_by(|a, b| {
+        for item_type in [ReportItemType::Payments, ReportItemType::Remittance, ReportItemType::Terminal] {
+            if let Some(item_report) = report.get_report_item(&item_type) {
+                item_report.days_len_transaction.sort_by(|a, b| {
                     let date_a = NaiveDate::parse_from_str(a.0.as_str(), "%Y-%m-%d").unwrap();
                     let date_b = NaiveDate::parse_from_str(b.0.as_str(), "%Y-%m-%d").unwrap();
                     date_a.cmp(&date_b)
                 });
-            },
-            None => {}
-        };
-        match report.get_report_item(&ReportItemType::Remittance) {
-            Some(item_report) => {
-                let _ = item_report.days_len_transaction.sort_by(|a, b| {
-                    let date_a = NaiveDate::parse_from_str(a.0.as_str(), "%Y-%m-%d").unwrap();
-                    let date_b = NaiveDate::parse_from_str(b.0.as_str(), "%Y-%m-%d").unwrap();
-                    date_a.cmp(&date_b)
-                });
-            },
-            None => {}
-        };
+            }
+        }
 
         Ok(())
     }
 
-    pub fn is_exist_file_report(&self, file_name: &String, user_id: &str ) -> (bool, Option<String>) {
-        let path_to_file = format!("{}/reports/{}/{}.xlsx", dotenv!("REPORTS_DIR"), user_id, file_name);
+    pub fn is_exist_file_report(
+        &self,
+        file_name: &String,
+        user_id: &str,
+        extension: &str,
+        settings: &Settings
+    ) -> (bool, Option<String>) {
+        let path_to_file = format!(
+            "{}/reports/{}/{}.{}",
+            settings.get_reports_dir(), user_id, file_name, extension
+        );
 
         if Path::new(&path_to_file).exists() {
             (true, Some(path_to_file))
@@ -1029,27 +1262,14 @@ impl Share {
     }
 
     /// Подсчитывает amount в одном чанке который потом мы запишем в share либо прибавим к существующему числу
-    pub fn calculate_amount(chunk: &Vec<Vec<String>>, index_amount: usize) -> f64 {
-        // Пересчитываем все поля amount
-        // amount не был протестирован
-        let amount = if chunk.is_empty() {
-            0.0
-        } else {
-            chunk
-                .iter()
-                .map(|sum| {
-                    if sum.get(index_amount).is_none() {
-                        0.0
-                    } else {
-                        sum.get(index_amount).unwrap().parse::<f64>().unwrap_or(0.0)
-                    }
-                })
-                .collect::<Vec<f64>>()
-                .iter()
-                .sum()
-        };
+    pub fn calculate_amount(chunk: &Vec<Vec<String>>, index_amount: usize) -> Result<Money, ResponseError> {
+        let mut amount = Money::ZERO;
+
+        for record in chunk {
+            amount += Money::from_record(record, Some(index_amount))?;
+        }
 
-        amount
+        Ok(amount)
     }
 
     /// Считаем amount на каждый день сгенерированного отчета
@@ -1057,8 +1277,8 @@ impl Share {
         records: &Vec<Vec<String>>,
         index: usize,
         collecting_index: &IndexingReport,
-    ) -> Vec<(String, f64)> {
-        let mut amounts_for_days: Vec<(String, f64)> = Vec::new();
+    ) -> Result<Vec<(String, Money)>, ResponseError> {
+        let mut amounts_for_days: Vec<(String, Money)> = Vec::new();
 
         let first_date = records.get(0).map_or("".to_string(), |info| {
             return info
@@ -1135,7 +1355,7 @@ impl Share {
                 }
 
                 if !date_exist {
-                    amounts_for_days.push((date.to_string(), 0.0))
+                    amounts_for_days.push((date.to_string(), Money::ZERO))
                 }
             });
 
@@ -1150,13 +1370,13 @@ impl Share {
                             .parse::<String>()
                             .unwrap()
                     {
-                        day.1 += record.get(index).unwrap().parse::<f64>().unwrap();
+                        day.1 += Money::from_record(record, Some(index))?;
                     }
                 }
             }
         }
 
-        amounts_for_days
+        Ok(amounts_for_days)
     }
 
     pub fn count_transaction(
@@ -1180,10 +1400,8 @@ impl Share {
 
     pub fn check_percentage_load_report(
         report: &mut ReportItem,
-        number_of_chunks: usize,
+        percent: f64,
     ) -> Option<f64> {
-        let percent = (report.id_having_chunk.len() as f64 / number_of_chunks as f64) * 100.0;
-
         if percent <= 100.0 {
             report.percent_load = percent.round();
             Some(report.percent_load)
@@ -1208,87 +1426,115 @@ impl Share {
         return *self.max_count_record_in_reports.lock().unwrap();
     }
 
-    pub fn set_refill_amount(&mut self, amount: f64, item_report: &mut ReportItem)  {
+    pub fn set_refill_amount(&mut self, amount: Money, item_report: &mut ReportItem)  {
         item_report.refill_amount = amount;
     }
 
+    /// Забирает чанки из канала и сразу агрегирует их в отчет.
+    /// Возвращает количество чанков которые были в файле
     pub async fn processing_chunks(
         report: Arc<TokioRwLock<Report>>,
-        chunks: ChunksInReport,
+        mut chunks: ChunkReceiver,
         Provider_name: &mut String,
         filter: &Filter,
         collect_indexing: &IndexingReport,
         report_type: &ReportType
-    ) -> Result<(), ResponseError> {
-        return if !chunks.is_empty() {
-            let mut tasks = Vec::new();
-
-            let total_amount_of_chunks: usize = chunks.len();
-
+    ) -> Result<usize, ResponseError> {
+        // Тип генерируемого отчета
+        let report_item_type = filter.get_type_report_that_generated().unwrap_or(&ReportItemType::Unknown).clone();
+
+        // Если часть отчета уже есть (к примеру переводы из c2card и c2cpayme),
+        // то чанки этого файла добавляются в нее после уже посчитанных.
+        // Файл который уже есть в части отчета только дочитывается, иначе суммы посчитались бы дважды
+        let (chunk_offset, is_file_processed) = {
             let mut report_guard = report.write().await;
 
-            // Тип генерируемого отчета
-            let report_item_type = filter.get_type_report_that_generated().unwrap_or(&ReportItemType::Unknown);
+            match report_guard.get_report_item(&report_item_type) {
+                Some(report_item) => (Some(report_item.id_having_chunk.len()), report_item.file_ids.contains(&filter.id)),
+                None => {
+                    report_guard.set_report(report_item_type.clone(), filter);
+                    (None, false)
+                }
+            }
+        };
 
-            let is_report_exist = report_guard.get_report_item(report_item_type).is_some();
+        let mut number_of_chunks = 0;
+        // Чанки считаются параллельно, но добавляются в отчет строго по порядку
+        let mut in_flight: VecDeque<(usize, f64, JoinHandle<Result<ReportItem, ResponseError>>)> = VecDeque::new();
+        let max_in_flight = std::thread::available_parallelism().map(|number| number.get()).unwrap_or(4);
 
-            if !is_report_exist {
-                report_guard.set_report(report_item_type.clone(), filter);
+        while let Some(chunk) = chunks.recv().await {
+            if let Err(error) = chunk {
+                return Err(error);
+            }
 
-                let process_tasks = processing_tasks(
-                    chunks.clone(),
-                    Arc::clone(&report),
-                    &report_item_type,
-                    &collect_indexing,
-                    Provider_name,
-                    report_type.clone(),
-                    total_amount_of_chunks,
-                ).await;
+            let chunk = chunk.unwrap();
+            number_of_chunks += 1;
 
-                for task in process_tasks {
-                    tasks.push(task);
-                }
+            if is_file_processed {
+                continue;
+            }
 
-                // Вставляем organization_id
-                report_guard.set_Provider_name(Provider_name.clone());
-            } else {
-                let mut report_item_writer = report.write().await;
-                let report_item_opt = report_item_writer.get_report_item(report_item_type);
-                let report_item = report_item_opt.unwrap();
-
-                for (chunk_index, chunk) in chunks.into_iter().enumerate() {
-                    // Продолжаем с того чанка на котором закончили
-                    if chunk_index > *report_item.id_having_chunk.last().unwrap_or(&0) as usize {
-                        let task = create_task(
-                            Arc::clone(&report),
-                            chunk_index,
-                            chunk,
-                            total_amount_of_chunks,
-                            collect_indexing,
-                            report_item_type,
-                            report_type,
-                        ).await;
-                        tasks.push(task);
-                    }
+            // В файлах терминалов колонки провайдера нет, имя берется только если она есть
+            if chunk.index == 0 && Provider_name.is_empty() {
+                if let (Some(record), Some(index_provider)) = (chunk.records.first(), collect_indexing.index_provider) {
+                    Provider_name.push_str(record[index_provider].as_str());
                 }
             }
 
-            drop(report_guard);
+            let filter = filter.clone();
+            let collect_indexing = collect_indexing.clone();
+            let type_report = report_item_type.clone();
+            let report_type = report_type.clone();
+            let records = chunk.records;
 
-            // Если же в нашем tasks находятся задачи, значит нам нужно их обработать
-            for task in tasks {
-                if let Err(error) = task.await {
-                    error!("Ошибка при обработке задачи: {:?}", error);
-                    continue;
-                }
+            let task = tokio::task::spawn_blocking(move || {
+                ReportItem::from_chunk(&filter, &records, &collect_indexing, &type_report, &report_type)
+            });
+
+            in_flight.push_back((chunk.index + chunk_offset.unwrap_or(0), chunk.percent, task));
+
+            if in_flight.len() >= max_in_flight {
+                let (chunk_index, percent, task) = in_flight.pop_front().unwrap();
+                Share::merge_chunk_task(&report, &report_item_type, chunk_index, percent, task).await?;
             }
+        }
 
-            Ok(())
-        } else {
-            // Создаем пустышки
-            // share.lock().await.reports.get_report(key).unwrap().lock().unwrap().create_empty_item(report_item_type, filter);
-            Ok(())
-        };
+        while let Some((chunk_index, percent, task)) = in_flight.pop_front() {
+            Share::merge_chunk_task(&report, &report_item_type, chunk_index, percent, task).await?;
+        }
+
+        if !is_file_processed {
+            if let Some(report_item) = report.write().await.get_report_item(&report_item_type) {
+                report_item.file_ids.push(filter.id);
+            }
+        }
+
+        if chunk_offset.is_none() {
+            // Вставляем organization_id
+            report.write().await.set_Provider_name(Provider_name.clone());
+        }
+
+        Ok(number_of_chunks)
+    }
+
+    /// Дожидается подсчета чанка и добавляет его в отчет.
+    /// Блокировка на запись держится только на время слияния.
+    /// Ошибка чанка возвращается, без него итоги отчета были бы неверными
+    async fn merge_chunk_task(
+        report: &Arc<TokioRwLock<Report>>,
+        report_item_type: &ReportItemType,
+        chunk_index: usize,
+        percent: f64,
+        task: JoinHandle<Result<ReportItem, ResponseError>>,
+    ) -> Result<(), ResponseError> {
+        let partial = task
+            .await
+            .map_err(|error| (4132426, format!("Ошибка при обработке задачи: {:?}", error)))??;
+
+        report.write().await.merge_partial_item(report_item_type, partial, chunk_index, percent)?;
+
+        Ok(())
     }
 
     pub async fn get_processed_report(&self, key: &str) -> Result<Arc<TokioRwLock<Report>>, ResponseError> {
@@ -1322,3 +1568,216 @@ pub fn get_number_from_date(date: &str) -> (i32, u32, u32) {
 
     (year, month, day)
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::channel;
+
+    use super::*;
+    use crate::helper::chunks::chunk_manager::creator_of_chunks::{
+        create_chunk_sources_by_types, stream_chunks_for_share, RecordsChunk
+    };
+    use crate::indexing_report_struct::column_mapping::ColumnMapping;
+
+    /// Колонки: дата, провайдер, tran_type, вендор, сумма, комиссия
+    fn indexing() -> IndexingReport {
+        let mut indexing = IndexingReport::new();
+        indexing.index_date = Some(0);
+        indexing.index_provider = Some(1);
+        indexing.index_tran_type = Some(2);
+        indexing.index_vendor = Some(3);
+        indexing.index_amount = Some(4);
+        indexing.index_commission = Some(5);
+        indexing
+    }
+
+    fn filter() -> Filter {
+        let mut filter: Filter = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        filter.set_type_of_report_we_depend("c2card".to_string());
+        filter.set_type_report_that_generated().unwrap();
+        filter
+    }
+
+    fn records() -> Vec<RecordStrings> {
+        [
+            ["2024-01-01 10:00:00", "Taxi", "c2c", "Uzcard", "100.50", "1.5"],
+            ["2024-01-01 11:00:00", "Taxi", "c2c", "Uzcard", "200", "2"],
+            ["2024-01-02 09:00:00", "Taxi", "p2p", "Humo", "300.25", "3"],
+            ["2024-01-02 12:00:00", "Taxi", "c2c", "Uzcard", "50", "0.5"],
+            ["2024-01-03 08:00:00", "Taxi", "p2p", "Humo", "75.75", "0.75"],
+            ["2024-01-03 18:00:00", "Taxi", "c2c", "Uzcard", "10", "0.1"],
+            ["2024-01-04 10:00:00", "Taxi", "p2p", "Humo", "20", "0.2"],
+        ]
+            .iter()
+            .map(|record| record.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    /// Читает файл [filter] в [report] через [Share::processing_chunks], файл режется на чанки по [chunk_size] строк.
+    /// Возвращает количество чанков
+    async fn read_file(
+        report: &Arc<TokioRwLock<Report>>,
+        records: Vec<RecordStrings>,
+        chunk_size: usize,
+        filter: &Filter,
+        indexing: &IndexingReport,
+    ) -> usize {
+        let report_type = ReportType::from_name("Agent");
+
+        let chunks = records.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect::<Vec<Vec<RecordStrings>>>();
+        let chunks_len = chunks.len();
+        let (sender, receiver) = channel(chunks_len);
+
+        for (index, records) in chunks.into_iter().enumerate() {
+            let percent = (index + 1) as f64 / chunks_len as f64 * 100.0;
+            sender.send(Ok(RecordsChunk { index, records, percent })).await.unwrap();
+        }
+        drop(sender);
+
+        let mut provider_name = String::new();
+        Share::processing_chunks(Arc::clone(report), receiver, &mut provider_name, filter, indexing, &report_type)
+            .await
+            .unwrap()
+    }
+
+    /// Собирает часть отчета из одного файла
+    async fn process(records: Vec<RecordStrings>, chunk_size: usize, indexing: &IndexingReport) -> ReportItem {
+        let report = Arc::new(TokioRwLock::new(Report::new(ReportType::from_name("Agent"), "1".to_string())));
+
+        read_file(&report, records, chunk_size, &filter(), indexing).await;
+
+        let mut report = report.write().await;
+        let mut report_item = report.get_report_item(&ReportItemType::Remittance).unwrap().clone();
+        // Номера чанков зависят только от размера чанка
+        report_item.id_having_chunk.clear();
+        report_item
+    }
+
+    /// Сравнивает части отчета целиком, кроме порядка дней в HashSet
+    fn assert_same_item(parallel: &ReportItem, single_pass: &ReportItem, chunk_size: usize) {
+        assert_eq!(parallel.days_in_report, single_pass.days_in_report, "chunk_size: {}", chunk_size);
+
+        let to_value = |report_item: &ReportItem| {
+            let mut value = serde_json::to_value(report_item).unwrap();
+            value.as_object_mut().unwrap().remove("days_in_report");
+            value
+        };
+
+        assert_eq!(to_value(parallel), to_value(single_pass), "chunk_size: {}", chunk_size);
+    }
+
+    #[actix_web::test]
+    async fn parallel_chunks_match_single_pass() {
+        let indexing = indexing();
+        let single_pass = process(records(), records().len(), &indexing).await;
+
+        for chunk_size in [1, 2, 3] {
+            let parallel = process(records(), chunk_size, &indexing).await;
+
+            assert_same_item(&parallel, &single_pass, chunk_size);
+        }
+
+        // Первый провайдер назван по tran_type, следующий новый по вендору
+        let names = single_pass.summary_by_Provider.iter().map(|vendor| vendor.0.as_str()).collect::<Vec<&str>>();
+        assert_eq!(names[..2], ["c2c", "Humo"]);
+    }
+
+    #[actix_web::test]
+    async fn malformed_amount_fails_processing() {
+        let mut records = records();
+        records[4][4] = "75.7.5".to_string();
+
+        let filter = filter();
+        let report_type = ReportType::from_name("Agent");
+        let report = Arc::new(TokioRwLock::new(Report::new(report_type, "1".to_string())));
+
+        let (sender, receiver) = channel(1);
+        sender.send(Ok(RecordsChunk { index: 0, records, percent: 100.0 })).await.unwrap();
+        drop(sender);
+
+        let result = Share::processing_chunks(report, receiver, &mut String::new(), &filter, &indexing(), &report_type).await;
+
+        assert_eq!(result.map_err(|error| error.0), Err(5435450));
+    }
+
+    #[actix_web::test]
+    async fn parallel_chunks_match_single_pass_grouped_by_vendor() {
+        let mut indexing = indexing();
+        indexing.index_tran_type = None;
+
+        let single_pass = process(records(), records().len(), &indexing).await;
+        let parallel = process(records(), 2, &indexing).await;
+
+        assert_same_item(&parallel, &single_pass, 2);
+
+        let summary = single_pass.summary_by_Provider.iter().map(|vendor| (vendor.0.as_str(), vendor.1)).collect::<Vec<(&str, u32)>>();
+        assert_eq!(summary, [("Uzcard", 4), ("Humo", 3)]);
+    }
+
+    #[actix_web::test]
+    async fn same_file_is_not_counted_twice() {
+        let indexing = indexing();
+        let report = Arc::new(TokioRwLock::new(Report::new(ReportType::from_name("Agent"), "1".to_string())));
+
+        read_file(&report, records(), 2, &filter(), &indexing).await;
+        // Файл дочитывается до конца, чтобы проверка на пустой файл видела его чанки
+        assert_eq!(read_file(&report, records(), 2, &filter(), &indexing).await, 4);
+
+        let report_item = report.write().await.get_report_item(&ReportItemType::Remittance).unwrap().clone();
+        assert_eq!(report_item.len_transactions, 7);
+        assert_eq!(report_item.file_ids, [1]);
+
+        // Другой файл той же части отчета добавляется после первого
+        let mut other_file = filter();
+        other_file.id = 2;
+        read_file(&report, records()[..3].to_vec(), 2, &other_file, &indexing).await;
+
+        let report_item = report.write().await.get_report_item(&ReportItemType::Remittance).unwrap().clone();
+        assert_eq!(report_item.len_transactions, 10);
+        assert_eq!(report_item.file_ids, [1, 2]);
+    }
+
+    /// Выгрузка по терминалам проходит весь путь от csv файла до части отчета "Терминалы"
+    #[actix_web::test]
+    async fn terminal_file_is_processed() {
+        let report_type = ReportType::from_name("Terminal");
+        assert!(report_type.kind().unwrap().file_types().contains(&"terminal"));
+
+        let path = std::env::temp_dir().join(format!("terminal_{}.csv", std::process::id()));
+        std::fs::write(&path, [
+            "Дата транзакции,Провайдер,Provider_id,Статус,Режим,Сумма,Комиссия,Терминал,Вендор,Вендор id",
+            "2024-01-01 10:00:00,Taxi,1,Успешно,Боевой,100.50,1.5,T-1,Shop,77",
+            "2024-01-01 11:00:00,Taxi,1,Успешно,Боевой,200,2,T-2,Shop,77",
+            "2024-01-02 09:00:00,Taxi,1,Успешно,Боевой,300.25,3,T-1,Shop,77",
+            "2024-01-02 12:00:00,Taxi,1,Успешно,Боевой,50,0.5,T-3,Market,78",
+        ].join("\n")).unwrap();
+
+        let mut filter: Filter = serde_json::from_str(r#"{"id": 5}"#).unwrap();
+        filter.set_type_of_report_we_depend("terminal".to_string());
+        filter.set_path_to_file(path.to_string_lossy().to_string());
+
+        let column_mapping = ColumnMapping::load("").unwrap();
+        let mut filters = vec![filter];
+        let source = create_chunk_sources_by_types(&mut filters, &column_mapping).unwrap().remove(0);
+        assert_eq!(source.report_item_type, ReportItemType::Terminal);
+
+        let receiver = stream_chunks_for_share(
+            source.reader, "77".to_string(), source.filter.clone(), source.index_collection, report_type
+        );
+
+        let report = Arc::new(TokioRwLock::new(Report::new(report_type, "77".to_string())));
+        Share::processing_chunks(
+            Arc::clone(&report), receiver, &mut String::new(), source.filter, &source.index_collection, &report_type
+        )
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let report_item = report.write().await.get_report_item(&ReportItemType::Terminal).unwrap().clone();
+        assert_eq!(report_item.len_transactions, 3);
+        assert_eq!(report_item.amount, Money::parse("600.75").unwrap());
+
+        let summary = report_item.summary_by_Provider.iter().map(|terminal| (terminal.0.as_str(), terminal.1)).collect::<Vec<(&str, u32)>>();
+        assert_eq!(summary, [("T-1", 2), ("T-2", 1)]);
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use crate::helper::chunks::chunk_manager::creator_of_chunks::ChunkReceiver;
+use crate::indexing_report_struct::IndexingReport;
+use crate::r#trait::filter_report::{Filter, ReportItemType, ReportType};
+use crate::r#type::types::{RecordStrings, ResponseError};
+use crate::share::{Report, ReportItem, Share};
+
+/// Какая часть процента загруженности пакетной задачи приходится на чтение файлов,
+/// остальное это сохранение отчетов по организациям
+pub const BATCH_READ_PERCENT: f64 = 80.0;
+
+/// [Batch reports] Отчеты по нескольким организациям, которые собираются за одно чтение файлов.
+/// Строки каждого чанка раскладываются по id организации, у каждой организации свой [Report]
+pub struct BatchReports {
+    report_type: ReportType,
+    /// [Requested] Организации из запроса, None значит все организации которые есть в файлах
+    requested: Option<Vec<String>>,
+    /// [Max organizations] Сколько организаций может быть в одной задаче
+    max_organizations: usize,
+    /// [Reports] Отчеты в порядке первого появления организации в файлах
+    reports: Vec<(String, Arc<TokioRwLock<Report>>)>,
+}
+
+/// Строки одной организации из чанка: id, название и сами строки
+type OrganizationRecords = (String, String, Vec<RecordStrings>);
+
+impl BatchReports {
+    pub fn new(report_type: ReportType, requested: Option<Vec<String>>, max_organizations: usize) -> BatchReports {
+        BatchReports {
+            report_type,
+            requested,
+            max_organizations,
+            reports: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    pub fn get_report(&self, organization_id: &str) -> Option<Arc<TokioRwLock<Report>>> {
+        self.reports
+            .iter()
+            .find(|(id, _)| id == organization_id)
+            .map(|(_, report)| Arc::clone(report))
+    }
+
+    /// Организации в том порядке, в котором они попадут в индекс:
+    /// для списка из запроса порядок запроса (вместе с теми которых нет в файлах),
+    /// для всех организаций из файлов сортировка по id
+    pub fn organization_ids(&self) -> Vec<String> {
+        match self.requested.as_ref() {
+            Some(requested) => requested.clone(),
+            None => {
+                let mut ids = self.reports.iter().map(|(id, _)| id.clone()).collect::<Vec<String>>();
+                ids.sort();
+                ids
+            }
+        }
+    }
+
+    /// Забирает чанки файла из канала и раскладывает строки по отчетам организаций.
+    /// [file] это номер файла и количество файлов, нужны для процента загруженности задачи [job_id].
+    /// Возвращает количество чанков которые были в файле
+    pub async fn processing_chunks(
+        &mut self,
+        share: &TokioRwLock<Share>,
+        job_id: &str,
+        mut chunks: ChunkReceiver,
+        filter: &Filter,
+        collect_indexing: &IndexingReport,
+        file: (usize, usize),
+    ) -> Result<usize, ResponseError> {
+        let report_item_type = filter.get_type_report_that_generated().unwrap_or(&ReportItemType::Unknown).clone();
+        let index_organization_id = self.report_type.kind()?.organization_id_index(collect_indexing)?;
+
+        let mut number_of_chunks = 0;
+        // Организации у которых были строки в этом файле
+        let mut organizations_in_file: Vec<String> = Vec::new();
+
+        while let Some(chunk) = chunks.recv().await {
+            let chunk = chunk?;
+            number_of_chunks += 1;
+
+            let percent = chunk.percent;
+            let groups = self.group_by_organization(chunk.records, index_organization_id, collect_indexing);
+
+            let filter_task = filter.clone();
+            let collect_indexing_task = collect_indexing.clone();
+            let type_report = report_item_type.clone();
+            let report_type = self.report_type;
+
+            // Частичные результаты всех организаций чанка считаются одной задачей
+            let partials = tokio::task::spawn_blocking(move || {
+                groups
+                    .into_iter()
+                    .map(|(organization_id, organization_name, records)| {
+                        ReportItem::from_chunk(&filter_task, &records, &collect_indexing_task, &type_report, &report_type)
+                            .map(|partial| (organization_id, organization_name, partial))
+                    })
+                    .collect::<Result<Vec<(String, String, ReportItem)>, ResponseError>>()
+            })
+                .await
+                .map_err(|error| (4132426, format!("Ошибка при обработке задачи: {:?}", error)))??;
+
+            for (organization_id, organization_name, partial) in partials {
+                let report = self.get_or_create_report(&organization_id)?;
+                let mut report_writer = report.write().await;
+
+                if report_writer.get_report_item(&report_item_type).is_none() {
+                    report_writer.set_report(report_item_type.clone(), filter);
+                }
+                report_writer.set_Provider_name(organization_name);
+
+                let chunk_num = report_writer
+                    .get_report_item(&report_item_type)
+                    .map_or(0, |report_item| report_item.id_having_chunk.len());
+
+                report_writer.merge_partial_item(&report_item_type, partial, chunk_num, percent)?;
+
+                if !organizations_in_file.contains(&organization_id) {
+                    organizations_in_file.push(organization_id);
+                }
+            }
+
+            let (file_index, files_count) = file;
+            let job_percent = (file_index as f64 + percent / 100.0) / files_count.max(1) as f64 * BATCH_READ_PERCENT;
+            share.read().await.jobs.set_percent_load(job_id, job_percent).await;
+        }
+
+        // Файл дочитан, последний чанк организации мог прийти раньше конца файла
+        for organization_id in organizations_in_file.iter() {
+            if let Some(report) = self.get_report(organization_id) {
+                if let Some(report_item) = report.write().await.get_report_item(&report_item_type) {
+                    Share::check_percentage_load_report(report_item, 100.0);
+                }
+            }
+        }
+
+        Ok(number_of_chunks)
+    }
+
+    /// Раскладывает строки чанка по id организации, строки организаций которых нет в запросе пропускаются
+    fn group_by_organization(
+        &self,
+        records: Vec<RecordStrings>,
+        index_organization_id: usize,
+        collect_indexing: &IndexingReport,
+    ) -> Vec<OrganizationRecords> {
+        let mut groups: Vec<OrganizationRecords> = Vec::new();
+
+        for record in records {
+            let organization_id = record[index_organization_id].trim().to_string();
+
+            if organization_id.is_empty() {
+                continue;
+            }
+
+            if let Some(requested) = self.requested.as_ref() {
+                if !requested.contains(&organization_id) {
+                    continue;
+                }
+            }
+
+            match groups.iter_mut().find(|(id, _, _)| id == &organization_id) {
+                Some((_, _, group)) => group.push(record),
+                None => {
+                    let organization_name = collect_indexing.index_provider
+                        .and_then(|index| record.get(index).cloned())
+                        .unwrap_or_default();
+
+                    groups.push((organization_id, organization_name, vec![record]));
+                }
+            }
+        }
+
+        groups
+    }
+
+    fn get_or_create_report(&mut self, organization_id: &str) -> Result<Arc<TokioRwLock<Report>>, ResponseError> {
+        if let Some(report) = self.get_report(organization_id) {
+            return Ok(report);
+        }
+
+        if self.reports.len() >= self.max_organizations {
+            return Err((
+                7357583,
+                format!("В файлах больше {} организаций, передайте список организаций в organizations", self.max_organizations)
+            ));
+        }
+
+        let report = Arc::new(TokioRwLock::new(Report::new(self.report_type, organization_id.to_string())));
+        self.reports.push((organization_id.to_string(), Arc::clone(&report)));
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,326 @@
+use serde::Serialize;
+
+use crate::helper::date::find_day_money;
+use crate::helper::reconciliation::Reconciliation;
+use crate::money::CurrencyRule;
+use crate::r#trait::filter_report::{ReportItemType, Status};
+use crate::share::{Report, ReportItem};
+
+/// Версия формата ответа GET /reports/{key}/data.
+/// Поднимается при любом несовместимом изменении структуры ниже
+pub const REPORT_DATA_VERSION: u32 = 1;
+
+/// [Report data] Агрегированные данные отчета для внешних потребителей.
+/// В отличие от [Report] тут нет позиционных кортежей, у каждого значения есть имя.
+/// Все суммы отдаются строкой, округленной по правилу валюты [ReportData::currency]
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportData {
+    pub version: u32,
+    pub key: String,
+    /// [ReportKind::slug] вида отчета: agent, taxi_company, merchant
+    ///
+    /// [ReportKind::slug]: crate::r#trait::report_kind::ReportKind::slug
+    pub report_type: &'static str,
+    pub organization_id: String,
+    pub organization_name: String,
+    pub currency: String,
+    pub create_at: i64,
+    /// Сначала переводы, потом платежи, потом терминалы, если они есть в отчете
+    pub items: Vec<ReportItemData>,
+    /// Сверка пополнений, есть только если она была запрошена при генерации
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconciliation: Option<ReconciliationData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportItemData {
+    /// remittance, payments или terminal
+    pub item_type: &'static str,
+    pub filter: FilterData,
+    pub transactions: u128,
+    pub amount: String,
+    pub refill_amount: String,
+    pub commissions: CommissionsData,
+    /// Отсортированы по дате
+    pub days: Vec<DayData>,
+    /// Итоги по месяцам с транзакциями, отсортированы по месяцу
+    pub months: Vec<MonthData>,
+    pub summary_by_provider: Vec<ProviderSummaryData>,
+    /// Заполняется только для отчета Agent
+    pub general_agent: Vec<AgentRowData>,
+    /// Заполняется только для отчета TaxiCompany
+    pub general_taxi_company: Vec<TaxiCompanyRowData>,
+    /// Заполняется только для отчета Merchant
+    pub general_merchant: Vec<MerchantRowData>,
+}
+
+/// Фильтры по которым собран элемент отчета
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterData {
+    pub file_id: u32,
+    pub status: Option<Vec<Status>>,
+    pub mode: Option<Vec<String>>,
+    pub payments_system: Option<Vec<String>>,
+    /// Каноничная запись выражения фильтра
+    pub expression: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommissionsData {
+    pub commission: String,
+    pub commission_pay_sys: String,
+    pub commission_bank: String,
+    pub commission_payment: String,
+    pub commission_eops: String,
+    pub commission_partner: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DayData {
+    pub date: String,
+    pub transactions: u64,
+    pub amount: String,
+    pub commission: String,
+    /// Разбивка по статусам, есть только если в фильтре несколько статусов
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_status: Vec<StatusData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthData {
+    /// Месяц в виде 2023-01
+    pub month: String,
+    pub transactions: u64,
+    pub amount: String,
+    pub commission: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderSummaryData {
+    pub provider: String,
+    pub transactions: u32,
+    pub amount: String,
+    pub commission: String,
+    pub bank_award: String,
+    pub company_award: String,
+    /// Разбивка по статусам, есть только если в фильтре несколько статусов
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_status: Vec<StatusData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusData {
+    pub status: Status,
+    pub transactions: u64,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentRowData {
+    pub provider: String,
+    pub transactions: u32,
+    pub amount: String,
+    pub commission: String,
+    pub company_award: String,
+    pub agent_award: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxiCompanyRowData {
+    pub provider: String,
+    pub transactions: u128,
+    pub amount: String,
+    pub company_award: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MerchantRowData {
+    pub provider: String,
+    pub payment_system: String,
+    pub transactions: u128,
+    pub amount: String,
+    pub company_award: String,
+}
+
+/// [Reconciliation data] Сводка сверки пополнений счета с суммой транзакций,
+/// её же отдает GET /reports/{key}/reconciliation
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationData {
+    pub tolerance: String,
+    pub refill: String,
+    pub amount: String,
+    /// Пополнения минус сумма транзакций за весь период
+    pub difference: String,
+    pub days_count: usize,
+    pub discrepancy_days_count: usize,
+    /// Только дни с расхождением больше допустимого, отсортированы по дате
+    pub discrepancies: Vec<ReconciliationDayData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationDayData {
+    pub date: String,
+    pub refill: String,
+    pub amount: String,
+    pub difference: String,
+}
+
+impl ReconciliationData {
+    pub fn from_reconciliation(reconciliation: &Reconciliation, currency: &CurrencyRule) -> ReconciliationData {
+        ReconciliationData {
+            tolerance: reconciliation.tolerance.format(currency),
+            refill: reconciliation.refill.format(currency),
+            amount: reconciliation.amount.format(currency),
+            difference: reconciliation.difference.format(currency),
+            days_count: reconciliation.days.len(),
+            discrepancy_days_count: reconciliation.discrepancy_days(),
+            discrepancies: reconciliation.days.iter()
+                .filter(|day| day.is_discrepancy)
+                .map(|day| ReconciliationDayData {
+                    date: day.date.clone(),
+                    refill: day.refill.format(currency),
+                    amount: day.amount.format(currency),
+                    difference: day.difference.format(currency),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ReportData {
+    pub fn from_report(key: &str, report: &Report, currency: &CurrencyRule) -> ReportData {
+        let mut items = Vec::new();
+
+        let item_types = [
+            ("remittance", ReportItemType::Remittance),
+            ("payments", ReportItemType::Payments),
+            ("terminal", ReportItemType::Terminal),
+        ];
+
+        for (name, item_type) in item_types.iter() {
+            if let Some(report_item) = report.read_report_item(item_type) {
+                items.push(ReportItemData::from_report_item(name, report_item, currency));
+            }
+        }
+
+        let report_type = report.get_report_type().kind().map_or("unknown", |kind| kind.slug());
+
+        ReportData {
+            version: REPORT_DATA_VERSION,
+            key: key.to_string(),
+            report_type,
+            organization_id: report.get_organization_id(),
+            organization_name: report.get_provider_name(),
+            currency: currency.code.clone(),
+            create_at: report.create_at,
+            items,
+            reconciliation: report.get_reconciliation()
+                .map(|reconciliation| ReconciliationData::from_reconciliation(reconciliation, currency)),
+        }
+    }
+}
+
+impl ReportItemData {
+    fn from_report_item(item_type: &'static str, item: &ReportItem, currency: &CurrencyRule) -> ReportItemData {
+        let commissions = &item.all_types_of_commissions;
+
+        let mut days_in_report = item.days_in_report.iter().collect::<Vec<&String>>();
+        days_in_report.sort();
+
+        let days = days_in_report
+            .into_iter()
+            .map(|day| DayData {
+                date: day.clone(),
+                transactions: item.days_len_transaction.iter()
+                    .find(|(date, _)| date == day)
+                    .map_or(0, |(_, count)| *count),
+                amount: find_day_money(&item.days_amount, day).format(currency),
+                commission: find_day_money(&item.commission_by_day, day).format(currency),
+                by_status: item.days_by_status.iter()
+                    .filter(|by_status| &by_status.0 == day)
+                    .map(|by_status| StatusData {
+                        status: by_status.1.clone(),
+                        transactions: by_status.2,
+                        amount: by_status.3.format(currency),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        ReportItemData {
+            item_type,
+            filter: FilterData {
+                file_id: item.filter.id,
+                status: item.filter.status.clone(),
+                mode: item.filter.mode.clone(),
+                payments_system: item.filter.payments_system.clone(),
+                expression: item.filter.expression.as_ref().map(|expression| expression.canonical()),
+            },
+            transactions: item.len_transactions,
+            amount: item.amount.format(currency),
+            refill_amount: item.refill_amount.format(currency),
+            commissions: CommissionsData {
+                commission: commissions.commission.format(currency),
+                commission_pay_sys: commissions.commission_pay_sys.format(currency),
+                commission_bank: commissions.commission_bank.format(currency),
+                commission_payment: commissions.commission_payment.format(currency),
+                commission_eops: commissions.commission_eops.format(currency),
+                commission_partner: commissions.commission_partner.format(currency),
+            },
+            days,
+            months: item.get_months_totals().into_iter()
+                .map(|((year, month), transactions, amount, commission)| MonthData {
+                    month: format!("{}-{:02}", year, month),
+                    transactions,
+                    amount: amount.format(currency),
+                    commission: commission.format(currency),
+                })
+                .collect(),
+            summary_by_provider: item.summary_by_Provider.iter()
+                .map(|provider| ProviderSummaryData {
+                    provider: provider.0.clone(),
+                    transactions: provider.1,
+                    amount: provider.2.format(currency),
+                    commission: provider.3.format(currency),
+                    bank_award: provider.4.format(currency),
+                    company_award: provider.5.format(currency),
+                    by_status: item.summary_by_Provider_status.iter()
+                        .filter(|by_status| by_status.0 == provider.0)
+                        .map(|by_status| StatusData {
+                            status: by_status.1.clone(),
+                            transactions: by_status.2 as u64,
+                            amount: by_status.3.format(currency),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            general_agent: item.general_report_on_remittance_agent.iter()
+                .map(|row| AgentRowData {
+                    provider: row.0.clone(),
+                    transactions: row.1,
+                    amount: row.2.format(currency),
+                    commission: row.3.format(currency),
+                    company_award: row.4.format(currency),
+                    agent_award: row.5.format(currency),
+                })
+                .collect(),
+            general_taxi_company: item.general_report_on_payments_taxi_company.iter()
+                .map(|row| TaxiCompanyRowData {
+                    provider: row.0.clone(),
+                    transactions: row.1,
+                    amount: row.2.format(currency),
+                    company_award: row.3.format(currency),
+                })
+                .collect(),
+            general_merchant: item.general_report_on_payments_merchant.iter()
+                .map(|row| MerchantRowData {
+                    provider: row.0.clone(),
+                    payment_system: row.1.clone(),
+                    transactions: row.2,
+                    amount: row.3.format(currency),
+                    company_award: row.4.format(currency),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::RwLock as TokioRwLock;
+
+use uuid::Uuid;
+
+use crate::r#type::types::ResponseError;
+use crate::share::Report;
+
+/// [Job status] Состояние задачи генерации отчета
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Задача создана, но генерация еще не началась
+    Queued,
+    /// Идет чтение csv и агрегация чанков
+    Running,
+    /// Файл отчета сохранен
+    Done,
+    /// Генерация завершилась ошибкой
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportJob {
+    /// [Id] Уникальный id задачи который возвращается пользователю
+    pub id: String,
+    /// [Key] Ключ отчета в share
+    pub key: String,
+    /// [User id] Пользователь который запустил генерацию
+    pub user_id: String,
+    /// [Status] Текущее состояние задачи
+    pub status: JobStatus,
+    /// [Percent load] Последний известный процент загруженности отчета
+    percent_load: f64,
+    /// [Path] Путь до готового файла, заполняется когда статус [JobStatus::Done]
+    pub path: Option<String>,
+    /// [Error] Ошибка генерации, заполняется когда статус [JobStatus::Failed]
+    pub error: Option<ResponseError>,
+    /// [Report] Отчет который сейчас собирается, по нему считаем процент загруженности
+    report: Option<Arc<TokioRwLock<Report>>>,
+    /// [create at] Время создания задачи
+    pub create_at: i64,
+    /// [update at] Время последнего изменения статуса
+    pub update_at: i64,
+}
+
+/// Ошибка задачи в том же виде что и ошибка в [Response]
+#[derive(Debug, Clone, Serialize)]
+pub struct JobError {
+    pub code: i32,
+    pub message: String,
+}
+
+/// То что отдаем пользователю по запросу GET /jobs/{id}
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportJobView {
+    pub id: String,
+    pub key: String,
+    pub status: JobStatus,
+    pub percent_load: f64,
+    pub path: Option<String>,
+    pub error: Option<JobError>,
+    pub create_at: i64,
+    pub update_at: i64,
+}
+
+#[derive(Debug)]
+pub struct Jobs {
+    pub data: TokioRwLock<HashMap<String, ReportJob>>,
+}
+
+impl ReportJob {
+    pub fn new(key: String, user_id: String) -> ReportJob {
+        let now = Utc::now().timestamp();
+
+        ReportJob {
+            id: Uuid::new_v4().to_string(),
+            key,
+            user_id,
+            status: JobStatus::Queued,
+            percent_load: 0.0,
+            path: None,
+            error: None,
+            report: None,
+            create_at: now,
+            update_at: now,
+        }
+    }
+
+    /// Задача закончила работу (успешно или нет)
+    pub fn is_finished(&self) -> bool {
+        self.status == JobStatus::Done || self.status == JobStatus::Failed
+    }
+
+    /// Обновляем процент загруженности из отчета.
+    /// Если отчет сейчас заблокирован на запись, оставляем последний известный процент
+    pub fn refresh_percent_load(&mut self) {
+        if let Some(report) = self.report.as_ref() {
+            if let Ok(report_reader) = report.try_read() {
+                self.percent_load = report_reader.get_percent_load();
+            }
+        }
+    }
+
+    pub fn to_view(&self) -> ReportJobView {
+        ReportJobView {
+            id: self.id.clone(),
+            key: self.key.clone(),
+            status: self.status,
+            percent_load: self.percent_load,
+            path: self.path.clone(),
+            error: self.error.clone().map(|(code, message)| JobError { code, message }),
+            create_at: self.create_at,
+            update_at: self.update_at,
+        }
+    }
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Jobs {
+    pub fn new() -> Jobs {
+        Jobs {
+            data: TokioRwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Создает задачу в статусе [JobStatus::Queued] и возвращает её id
+    pub async fn create_job(&self, key: String, user_id: String) -> String {
+        let job = ReportJob::new(key, user_id);
+        let id = job.id.clone();
+
+        self.data.write().await.insert(id.clone(), job);
+
+        id
+    }
+
+    /// Создает задачу для уже существующего файла, сразу в статусе [JobStatus::Done]
+    pub async fn create_done_job(&self, key: String, user_id: String, path: String) -> String {
+        let mut job = ReportJob::new(key, user_id);
+        job.status = JobStatus::Done;
+        job.percent_load = 100.0;
+        job.path = Some(path);
+        let id = job.id.clone();
+
+        self.data.write().await.insert(id.clone(), job);
+
+        id
+    }
+
+    pub async fn set_running(&self, id: &str, report: Arc<TokioRwLock<Report>>) {
+        if let Some(job) = self.data.write().await.get_mut(id) {
+            job.status = JobStatus::Running;
+            job.report = Some(report);
+            job.update_at = Utc::now().timestamp();
+        }
+    }
+
+    /// Пакетная задача собирает сразу несколько отчетов,
+    /// процент загруженности для нее выставляется через [Jobs::set_percent_load]
+    pub async fn set_running_batch(&self, id: &str) {
+        if let Some(job) = self.data.write().await.get_mut(id) {
+            job.status = JobStatus::Running;
+            job.report = None;
+            job.update_at = Utc::now().timestamp();
+        }
+    }
+
+    pub async fn set_percent_load(&self, id: &str, percent: f64) {
+        if let Some(job) = self.data.write().await.get_mut(id) {
+            job.percent_load = percent.min(100.0).round();
+        }
+    }
+
+    pub async fn set_done(&self, id: &str, path: String) {
+        if let Some(job) = self.data.write().await.get_mut(id) {
+            job.status = JobStatus::Done;
+            job.percent_load = 100.0;
+            job.path = Some(path);
+            // Отчет больше не нужен задаче, он либо в share либо уже удален
+            job.report = None;
+            job.update_at = Utc::now().timestamp();
+        }
+    }
+
+    pub async fn set_failed(&self, id: &str, error: ResponseError) {
+        if let Some(job) = self.data.write().await.get_mut(id) {
+            job.refresh_percent_load();
+            job.status = JobStatus::Failed;
+            job.error = Some(error);
+            job.report = None;
+            job.update_at = Utc::now().timestamp();
+        }
+    }
+
+    /// Возвращает задачу в виде для ответа пользователю
+    pub async fn get_job_view(&self, id: &str) -> Option<(String, ReportJobView)> {
+        let mut jobs_writer = self.data.write().await;
+
+        match jobs_writer.get_mut(id) {
+            None => None,
+            Some(job) => {
+                if job.status == JobStatus::Running {
+                    job.refresh_percent_load();
+                }
+
+                Some((job.user_id.clone(), job.to_view()))
+            }
+        }
+    }
+
+    /// Запускал ли пользователь хотя бы одну задачу по отчету с ключом [key]
+    pub async fn is_key_owner(&self, key: &str, user_id: &str) -> bool {
+        self.data.read().await
+            .values()
+            .any(|job| job.key == key && job.user_id == user_id)
+    }
+
+    /// Удаляет завершенные задачи которые старше [max_age] секунд, возвращает id удаленных задач
+    pub async fn remove_old_jobs(&self, max_age: i64) -> Vec<String> {
+        let now = Utc::now().timestamp();
+        let mut removed = Vec::new();
+
+        self.data.write().await.retain(|id, job| {
+            if job.is_finished() && now - job.update_at >= max_age {
+                removed.push(id.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        removed
+    }
+}
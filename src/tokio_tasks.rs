@@ -1,11 +1,9 @@
 /// Модуль для работы с асинхронными задачами токенов
 pub mod tokio_tasks {
-    use std::sync::Arc;
     use actix_web::web::Data;
-    use mysql_async::Conn;
-    use tokio::sync::{RwLock as TokioRwLock, Mutex as TokioMutex};
+    use tokio::sync::RwLock as TokioRwLock;
     use crate::args::Settings;
-    use crate::r#type::types::ReportsStorage;
+    use crate::r#type::types::{DatabasePool, ReportsStorage};
     use crate::server::tokens_storage::TokensStorage;
 
     /// Запускает таски по токенам
@@ -19,8 +17,14 @@ pub mod tokio_tasks {
     }
 
     /// Запускает таски по базе данных
-    pub async fn launch_database_handlers(db_conn: Data<Arc<TokioMutex<Conn>>>, settings: Data<Settings>) {
-        database_task::check_database_connection(db_conn, settings).await;
+    pub async fn launch_database_handlers(pool: DatabasePool, settings: Data<Settings>) {
+        database_task::check_database_connection(Data::clone(&pool)).await;
+        database_task::remove_expired_links(settings, pool).await;
+    }
+
+    /// Запускает таски по файлам отчетов на диске
+    pub async fn launch_retention_handlers(settings: Data<Settings>, pool: DatabasePool) {
+        retention_tasks::remove_old_files(settings, pool).await;
     }
 
     pub mod token_tasks {
@@ -90,46 +94,110 @@ pub mod tokio_tasks {
                             info!("Отчет {} был удален", rp_key);
                         }
                     }
+
+                    drop(share_writer);
+
+                    // Завершенные задачи генерации живут столько же сколько и отчеты
+                    for job_id in share_reader.jobs.remove_old_jobs(INTERVAL_TIME_REMOVE_REPORTS as i64).await {
+                        info!("Задача {} была удалена", job_id);
+                    }
                 }
             });
         }
     }
 
     pub mod database_task {
-        use std::sync::{Arc};
         use std::time::Duration;
 
         use actix_web::web::Data;
-        use mysql_async::Conn;
-        use mysql_async::prelude::Queryable;
-        use tokio::sync::{Mutex as TokioMutex, MutexGuard as TokioMutexGuard};
+        use chrono::Utc;
         use tokio::time::interval;
         use tracing::{error, info};
         use crate::args::Settings;
-        use crate::db::connect::connect_to_database;
+        use crate::db::connect::ping_database;
+        use crate::db::used_links::UsedLinks;
+        use crate::r#type::types::DatabasePool;
 
         const INTERVAL_CHECK_DATABASE_CONNECTION: u64 = 10;
+        const INTERVAL_REMOVE_EXPIRED_LINKS: u64 = 1800;
 
-        pub async fn check_database_connection(mut db_conn: Data<Arc<TokioMutex<Conn>>>, settings: Data<Settings>) {
+        /// Периодически проверяет доступность базы данных.
+        /// Переподключение делает сам пул, здесь только следим за состоянием и пишем в лог
+        pub async fn check_database_connection(pool: DatabasePool) {
             let mut interval = interval(Duration::from_secs(INTERVAL_CHECK_DATABASE_CONNECTION));
 
+            tokio::spawn(async move {
+                let mut is_available = true;
+
+                loop {
+                    interval.tick().await;
+
+                    match ping_database(&pool).await {
+                        Ok(_) => {
+                            if !is_available {
+                                info!("Подключение к базе данных восстановлено!");
+                            }
+                            is_available = true;
+                        }
+                        Err(error) => {
+                            error!("База данных недоступна - code: {} message: {}", error.0, error.1);
+                            is_available = false;
+                        }
+                    }
+                }
+            });
+        }
+
+        /// Использованные одноразовые ссылки нужны только пока ссылка не истекла,
+        /// раз в [INTERVAL_REMOVE_EXPIRED_LINKS] секунд истекшие удаляются из базы
+        pub async fn remove_expired_links(settings: Data<Settings>, pool: DatabasePool) {
+            let mut interval = interval(Duration::from_secs(INTERVAL_REMOVE_EXPIRED_LINKS));
+
             tokio::spawn(async move {
                 loop {
                     interval.tick().await;
-                    let ping_db = db_conn.lock().await.ping().await;
 
-                    if let Err(error) = ping_db {
-                        error!("Не удалось пропинговать базу данных: {}", error);
-                        let settings_clone = Data::clone(&settings);
+                    match UsedLinks::new(&pool, &settings).remove_expired(Utc::now().timestamp()).await {
+                        Ok(0) => {}
+                        Ok(removed) => info!("Удалено истекших одноразовых ссылок: {}", removed),
+                        Err(error) => error!("code: {} message: {}", error.0, error.1),
+                    }
+                }
+            });
+        }
+    }
+
+    pub mod retention_tasks {
+        use std::time::Duration;
+
+        use actix_web::web::Data;
+        use tokio::time::interval;
+        use tracing::{error, info};
+        use crate::args::Settings;
+        use crate::helper::retention::apply_retention;
+        use crate::r#type::types::DatabasePool;
+
+        /// Удаляет файлы отчетов по ограничениям [Settings::retention] раз в interval_secs.
+        /// В share отчеты живут 30 минут, а файлы на диске только этой задачей и удаляются
+        pub async fn remove_old_files(settings: Data<Settings>, pool: DatabasePool) {
+            if !settings.retention.is_enabled() {
+                info!("Все ограничения retention отключены, файлы отчетов не удаляются");
+                return;
+            }
+
+            let mut interval = interval(Duration::from_secs(settings.retention.interval_secs));
+
+            tokio::spawn(async move {
+                loop {
+                    interval.tick().await;
 
-                        let conn = connect_to_database(&**settings_clone).await;
-                        if let Err(error) = conn {
-                            error!("Не удалось повторно подключиться к базе данных - code: {} message: {}", error.0, error.1);
-                            panic!("Приложение не может корректно работать из-за отсутствие подключения к базе данных ");
+                    match apply_retention(&settings, &pool).await {
+                        Ok(removed) if removed.is_empty() => {}
+                        Ok(removed) => {
+                            let size = removed.iter().map(|report| report.size).sum::<u64>();
+                            info!("Retention: удалено отчетов {}, освобождено {} байт", removed.len(), size);
                         }
-                        let new_conn = Data::new(Arc::new(TokioMutex::new(conn.unwrap())));
-                        db_conn = new_conn;
-                        info!("Было успешно создано новое подключение к базе данных!");
+                        Err(error) => error!("Retention: code: {} message: {}", error.0, error.1),
                     }
                 }
             });
@@ -1,4 +1,4 @@
 pub mod automated_report_response;
-pub mod chunks_trait;
 pub mod filter_report;
-pub mod api_request;
\ No newline at end of file
+pub mod api_request;
+pub mod report_kind;
\ No newline at end of file
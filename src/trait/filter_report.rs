@@ -1,24 +1,35 @@
-use crate::helper::generate_xlsx::get_status;
+use crate::helper::report_type::find_report_kind;
 use crate::indexing_report_struct::IndexingReport;
+use crate::r#trait::filter_report::filter_expression::FilterExpression;
+use crate::r#trait::report_kind::ReportKind;
 use crate::r#type::types::ResponseError;
 use serde::de::Visitor;
-use serde::{de, Deserialize, Deserializer, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::fmt::Formatter;
 use csv::StringRecord;
 
+pub mod filter_expression;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Filter {
     /// [Id] Это id файла в user_interface.lo по которому мы собираемся генерировать отчет.
     pub id: u32,
-    /// [Status] Фильтрция по статусу транзакции, есть несколько видов трннзакций к примеру [Завершена, Создана, Ошибка]
-    pub status: Option<Status>,
-    /// [Mode] Фильтрация по моду, существует несколько видов модов, [Боевой, Тестовый]
-    pub mode: Option<String>,
+    /// [Status] Фильтрция по статусу транзакции, есть несколько видов трннзакций к примеру [Завершена, Создана, Ошибка].
+    /// Можно передать один статус или список, тогда в отчет попадают транзакции с любым из статусов
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub status: Option<Vec<Status>>,
+    /// [Mode] Фильтрация по моду, существует несколько видов модов, [Боевой, Тестовый].
+    /// Как и [Filter::status] принимает одно значение или список
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub mode: Option<Vec<String>>,
     /// [Payments system] фильтрация по платежным системам
     /// к примеру [Uzcard, QIWI Kassa (₽), MIR Pay и т.д]
     /// Этот фильтр предназначен для [Платежи]
     pub payments_system: Option<Vec<String>>,
+    /// [Expression] Выражение по колонкам файла с and/or/not и сравнениями,
+    /// к примеру диапазон сумм, окно по времени или список vendor которые нужно исключить
+    pub expression: Option<FilterExpression>,
     /// [Type report that generated] тип файла по которому генерируется отчет к примеру [pay, c2card, c2cCOMANYNAME и т.д]
     type_report_that_generated: Option<ReportItemType>,
     /// [Type of report we depend] Это поля подставляется само, в зависимости от типа файла, есть такие типы как
@@ -42,45 +53,31 @@ impl Filter {
     ) -> Result<bool, ResponseError> {
         let mut filter_status = true;
 
-        if self.status.as_ref().is_some() && self.status.as_ref().unwrap_or(&Status::Unknown) != &Status::Unknown {
-            if get_status(&self).unwrap().to_string()
-                != record[collect_indexing.index_status.unwrap()]
-            {
+        let statuses = self.get_statuses();
+
+        // Транзакция подходит если ее статус совпал с любым из статусов фильтра
+        if !statuses.is_empty() {
+            let record_status = &record[collect_indexing.index_status.unwrap()];
+
+            if !statuses.iter().any(|status| status.label() == record_status) {
                 filter_status = false;
             }
-        } else if self.status.as_ref().is_some() && self.status.as_ref().unwrap_or(&Status::Unknown) == &Status::Unknown {
-            filter_status = false;
         }
 
-        if !self.mode.as_ref().unwrap_or(&"".to_string()).is_empty() {
-            if self.mode.as_ref().is_some()
-                && self.mode.as_ref().unwrap() != &record[collect_indexing.index_mode.unwrap()]
-            {
-                filter_status = false;
-            };
-        }
+        let modes = self.get_modes();
 
-        let index_organization_id = match report_type {
-            ReportType::Agent => match collect_indexing.index_provider_id {
-                Some(index) => Ok(index),
-                None => Err((3443242, "index_Provider не был найден".to_string()))
-            },
-            ReportType::TaxiCompany => match collect_indexing.index_provider_id {
-                Some(index) => Ok(index),
-                None => Err((3443243, "index_Provider не был найден".to_string()))
-            },
-            ReportType::Merchant => match collect_indexing.index_merchant_id {
-                Some(index) => Ok(index),
-                None => Err((3443244, "index_merchant_id не был найден".to_string()))
-            },
-            ReportType::Unknown => Err((3443245, "Невозможно распознать index так как вы передали не известный отчет".to_string()))
-        };
+        if !modes.is_empty() {
+            let record_mode = &record[collect_indexing.index_mode.unwrap()];
 
-        if let Err(error) = index_organization_id {
-            return Err(error);
+            if !modes.iter().any(|mode| mode == record_mode) {
+                filter_status = false;
+            }
         }
 
-        let index_organization_id = index_organization_id.unwrap();
+        let index_organization_id = match report_type.kind() {
+            Ok(kind) => kind.organization_id_index(collect_indexing)?,
+            Err(_) => return Err((3443245, "Невозможно распознать index так как вы передали не известный отчет".to_string()))
+        };
 
         if !organization_provider_id.is_empty() && organization_provider_id != &record[index_organization_id] {
             filter_status = false;
@@ -109,9 +106,57 @@ impl Filter {
             }
         }
 
+        // Выражение проверяется последним и только если остальные фильтры совпали
+        if filter_status {
+            if let Some(expression) = self.expression.as_ref() {
+                filter_status = expression.evaluate(record, collect_indexing)?;
+            }
+        }
+
         return Ok(filter_status);
     }
 
+    /// Проверяет фильтр до начала генерации
+    pub fn check(&self) -> Result<(), ResponseError> {
+        if self.get_statuses().contains(&Status::Unknown) {
+            return Err((7357570, format!("В фильтре файла {} передан не известный статус", self.id)));
+        }
+
+        if let Some(expression) = self.expression.as_ref() {
+            expression.check()?;
+        }
+
+        Ok(())
+    }
+
+    /// Статусы фильтра без повторов в порядке из запроса, пустой список если фильтра по статусу нет
+    pub fn get_statuses(&self) -> Vec<Status> {
+        let mut statuses: Vec<Status> = Vec::new();
+
+        for status in self.status.iter().flatten() {
+            if !statuses.contains(status) {
+                statuses.push(status.clone());
+            }
+        }
+
+        statuses
+    }
+
+    /// Моды фильтра без пустых значений, пустой список если фильтра по моду нет
+    pub fn get_modes(&self) -> Vec<String> {
+        self.mode
+            .iter()
+            .flatten()
+            .filter(|mode| !mode.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// Нужна ли разбивка сумм и количества по статусам, она есть если в фильтре больше одного статуса
+    pub fn has_status_breakdown(&self) -> bool {
+        self.get_statuses().len() > 1
+    }
+
     pub fn set_to_lowercase_payments_system_field(&mut self) {
         if self.payments_system.is_some() {
             let mut new_payments_systems = Vec::new();
@@ -141,18 +186,13 @@ impl Filter {
                     format!("Не удалось получить тип файла под id {}", &self.id)
                 ))
             },
-            Some(report_type) => {
-                match report_type.as_str() {
-                    "pay" | "pay_f" => {
-                        self.type_report_that_generated = Some(ReportItemType::Payments);
+            Some(file_type) => {
+                match ReportItemType::from_file_type(file_type) {
+                    Some(item_type) => {
+                        self.type_report_that_generated = Some(item_type);
                         Ok(())
                     },
-                    "c2card" | "c2cCOMANYNAME" => {
-                        self.type_report_that_generated = Some(ReportItemType::Remittance);
-                        Ok(())
-                    },
-                    _ => Err((543544, "Не известный тип переданного отчета".to_string()))
-
+                    None => Err((543544, format!("Не известный тип переданного отчета: {}", file_type)))
                 }
             }
         }
@@ -174,16 +214,49 @@ impl Filter {
     }
 }
 
+/// Типы файлов с платежами
+pub const PAYMENTS_FILE_TYPES: [&str; 2] = ["pay", "pay_f"];
+/// Типы файлов с переводами, у каждого процессинга свой тип файла
+pub const REMITTANCE_FILE_TYPES: [&str; 6] = ["c2card", "c2cCOMANYNAME", "c2cplum", "c2ckapitalbank", "c2cpayme", "c2cuzcard"];
+/// Типы файлов с выгрузкой по терминалам
+pub const TERMINAL_FILE_TYPES: [&str; 1] = ["terminal"];
+
 #[derive(Serialize, Debug, Clone, Eq, Hash, PartialEq, Deserialize)]
 pub enum ReportItemType {
     Remittance,
     Payments,
+    Terminal,
     Unknown,
     Empty,
     Null,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Ord, Eq, PartialOrd)]
+impl ReportItemType {
+    /// Типы файлов транзакции из которых попадают в эту часть отчета
+    pub fn file_types(&self) -> &'static [&'static str] {
+        match self {
+            ReportItemType::Payments => &PAYMENTS_FILE_TYPES,
+            ReportItemType::Remittance => &REMITTANCE_FILE_TYPES,
+            ReportItemType::Terminal => &TERMINAL_FILE_TYPES,
+            _ => &[],
+        }
+    }
+
+    /// Часть отчета в которую попадают транзакции из файла с типом [file_type]
+    pub fn from_file_type(file_type: &str) -> Option<ReportItemType> {
+        if PAYMENTS_FILE_TYPES.contains(&file_type) {
+            Some(ReportItemType::Payments)
+        } else if REMITTANCE_FILE_TYPES.contains(&file_type) {
+            Some(ReportItemType::Remittance)
+        } else if TERMINAL_FILE_TYPES.contains(&file_type) {
+            Some(ReportItemType::Terminal)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Ord, Eq, PartialOrd, Hash)]
 pub enum Status {
     Completed,
     Mistake,
@@ -193,22 +266,96 @@ pub enum Status {
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Copy)]
-pub enum ReportType {
-    Agent,
-    TaxiCompany,
-    Merchant,
-    Unknown,
+/// Статусы которые могут быть в колонке статуса csv файла
+const STATUSES: [Status; 5] = [Status::Completed, Status::Mistake, Status::Created, Status::Cancel, Status::Null];
+
+impl Status {
+    /// Как статус записан в колонке статуса csv файла
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Completed => "Завершена",
+            Status::Mistake => "Ошибка",
+            Status::Created => "Создана",
+            Status::Cancel => "Отмена",
+            Status::Null => "Null",
+            Status::Unknown => "Unknown",
+        }
+    }
+
+    /// Статус транзакции по значению из колонки статуса, [Status::Unknown] если значение не известно
+    pub fn from_label(label: &str) -> Status {
+        STATUSES.iter()
+            .find(|status| status.label() == label)
+            .cloned()
+            .unwrap_or(Status::Unknown)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
 }
 
+/// Поле фильтра которое принимает как одно значение так и список значений
+fn deserialize_one_or_many<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(match Option::<OneOrMany<T>>::deserialize(deserializer)? {
+        None => None,
+        Some(OneOrMany::One(value)) => Some(vec![value]),
+        Some(OneOrMany::Many(values)) => Some(values),
+    })
+}
+
+/// [Report type] Тип отчета из запроса, ссылается на зарегистрированный [ReportKind].
+/// Если в запросе передан не известный тип, то это [ReportType::UNKNOWN]
+#[derive(Clone, Copy)]
+pub struct ReportType(Option<&'static dyn ReportKind>);
+
 impl ReportType {
+    pub const UNKNOWN: ReportType = ReportType(None);
+
+    /// Ищет тип отчета по имени среди зарегистрированных видов отчета
+    pub fn from_name(name: &str) -> ReportType {
+        ReportType(find_report_kind(name))
+    }
+
+    /// Вид отчета, ошибка если тип отчета не известен
+    pub fn kind(&self) -> Result<&'static dyn ReportKind, ResponseError> {
+        self.0.ok_or((1357836, "Передан не известный тип отчета: Unknown".to_string()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0.map_or("Unknown", |kind| kind.name())
+    }
+
     pub fn report_type_to_string(&self) -> &str {
-        match self {
-            ReportType::Agent => "agent",
-            ReportType::TaxiCompany => "taxi_compony",
-            ReportType::Merchant => "merchant",
-            ReportType::Unknown => "unknown",
-        }
+        self.0.map_or("unknown", |kind| kind.key_part())
+    }
+}
+
+impl PartialEq for ReportType {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl fmt::Debug for ReportType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Serialize for ReportType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
     }
 }
 
@@ -274,12 +421,14 @@ impl<'de> Visitor<'de> for ReportTypeVisitor {
         A: de::EnumAccess<'de>,
     {
         let (variant, _): (String, _) = de::EnumAccess::variant(data).unwrap();
-        match variant.to_lowercase().as_str() {
-            "agent" => Ok(ReportType::Agent),
-            "merchant" => Ok(ReportType::Merchant),
-            "taxicompany" => Ok(ReportType::TaxiCompany),
-            _ => Ok(ReportType::Unknown),
-        }
+        Ok(ReportType::from_name(&variant))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(ReportType::from_name(value))
     }
 }
 
@@ -288,7 +437,6 @@ impl<'de> Deserialize<'de> for ReportType {
     where
         D: Deserializer<'de>,
     {
-        const FIELDS: &'static [&'static str] = &["agent", "merchant", "taxicompany"];
-        deserializer.deserialize_enum("ReportType", FIELDS, ReportTypeVisitor)
+        deserializer.deserialize_enum("ReportType", &[], ReportTypeVisitor)
     }
 }
\ No newline at end of file
@@ -0,0 +1,526 @@
+use std::sync::OnceLock;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use csv::StringRecord;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::indexing_report_struct::column_mapping::{normalize_header, Column};
+use crate::indexing_report_struct::IndexingReport;
+use crate::money::Money;
+use crate::r#type::types::ResponseError;
+
+/// [Filter expression] Выражение по колонкам csv файла, строка попадает в отчет если выражение истинно.
+///
+/// ```json
+/// {"and": [
+///     {"field": "amount", "op": "gte", "value": "10000"},
+///     {"field": "date", "op": "lt", "value": "2023-01-15 12:00:00"},
+///     {"not": {"field": "vendor", "op": "in", "value": ["test vendor", "demo"]}},
+///     {"or": [
+///         {"field": "merchant_id", "op": "in", "value": [101, 102]},
+///         {"field": "provider", "op": "contains", "value": "taxi"}
+///     ]}
+/// ]}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterExpression {
+    And { and: Vec<FilterExpression> },
+    Or { or: Vec<FilterExpression> },
+    Not { not: Box<FilterExpression> },
+    Comparison(Comparison),
+}
+
+/// [Comparison] Сравнение колонки [Comparison::field] со значением [Comparison::value]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparison {
+    /// [Field] Имя колонки как в файле с названиями колонок: amount, date, vendor, merchant_id ...
+    pub field: String,
+    pub op: Operator,
+    /// [Value] Строка или число, для [Operator::In] и [Operator::NotIn] массив
+    pub value: Value,
+    /// Колонка и разобранные значения, заполняются при первой проверке и дальше не разбираются на каждой строке
+    #[serde(skip)]
+    operands: OnceLock<Operands>,
+}
+
+/// Колонка сравнения и значения из запроса, приведенные к типу колонки
+#[derive(Debug, Clone)]
+struct Operands {
+    column: Column,
+    values: OperandValues,
+}
+
+#[derive(Debug, Clone)]
+enum OperandValues {
+    Money(Vec<Money>),
+    Date(Vec<NaiveDateTime>),
+    /// Текст уже приведен к виду как у заголовков, см. [normalize_header]
+    Text(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    NotIn,
+    /// Подстрока, только для текстовых колонок
+    Contains,
+}
+
+impl Operator {
+    fn name(&self) -> &'static str {
+        match self {
+            Operator::Eq => "eq",
+            Operator::Ne => "ne",
+            Operator::Gt => "gt",
+            Operator::Gte => "gte",
+            Operator::Lt => "lt",
+            Operator::Lte => "lte",
+            Operator::In => "in",
+            Operator::NotIn => "not_in",
+            Operator::Contains => "contains",
+        }
+    }
+
+    fn is_list(&self) -> bool {
+        matches!(self, Operator::In | Operator::NotIn)
+    }
+}
+
+/// Как сравниваются значения колонки
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueKind {
+    Money,
+    Date,
+    Text,
+}
+
+impl ValueKind {
+    fn of(column: Column) -> ValueKind {
+        match column {
+            Column::Amount
+            | Column::Commission
+            | Column::CommissionCompany
+            | Column::CommissionBank
+            | Column::CommissionEops
+            | Column::CommissionPartner
+            | Column::CommissionPayment
+            | Column::CommissionSecondbank => ValueKind::Money,
+            Column::Date => ValueKind::Date,
+            _ => ValueKind::Text,
+        }
+    }
+}
+
+impl FilterExpression {
+    /// Проверяет выражение до начала генерации: колонки, операторы и значения
+    pub fn check(&self) -> Result<(), ResponseError> {
+        match self {
+            FilterExpression::And { and: expressions } | FilterExpression::Or { or: expressions } => {
+                if expressions.is_empty() {
+                    return Err((7357560, "and/or в фильтре не может быть пустым".to_string()));
+                }
+
+                expressions.iter().try_for_each(|expression| expression.check())
+            }
+            FilterExpression::Not { not } => not.check(),
+            FilterExpression::Comparison(comparison) => comparison.check(),
+        }
+    }
+
+    /// Подходит ли строка [record] под выражение
+    pub fn evaluate(&self, record: &StringRecord, collect_indexing: &IndexingReport) -> Result<bool, ResponseError> {
+        match self {
+            FilterExpression::And { and } => {
+                for expression in and {
+                    if !expression.evaluate(record, collect_indexing)? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            FilterExpression::Or { or } => {
+                for expression in or {
+                    if expression.evaluate(record, collect_indexing)? {
+                        return Ok(true);
+                    }
+                }
+
+                Ok(false)
+            }
+            FilterExpression::Not { not } => Ok(!not.evaluate(record, collect_indexing)?),
+            FilterExpression::Comparison(comparison) => comparison.evaluate(record, collect_indexing),
+        }
+    }
+
+    /// Каноничная запись выражения для ключа отчета.
+    /// Одинаковые по смыслу выражения (другой порядок в and/or, вложенные and, двойное not,
+    /// другой регистр или запись суммы) дают одну и ту же запись
+    pub fn canonical(&self) -> String {
+        match self {
+            FilterExpression::And { and } => canonical_group("and", and, |expression| match expression {
+                FilterExpression::And { and } => Some(and),
+                _ => None,
+            }),
+            FilterExpression::Or { or } => canonical_group("or", or, |expression| match expression {
+                FilterExpression::Or { or } => Some(or),
+                _ => None,
+            }),
+            FilterExpression::Not { not } => match not.as_ref() {
+                FilterExpression::Not { not } => not.canonical(),
+                expression => format!("not({})", expression.canonical()),
+            },
+            FilterExpression::Comparison(comparison) => comparison.canonical(),
+        }
+    }
+}
+
+fn canonical_group<F>(name: &str, expressions: &[FilterExpression], same_group: F) -> String
+    where F: Fn(&FilterExpression) -> Option<&Vec<FilterExpression>> + Copy
+{
+    let mut parts = Vec::new();
+    collect_group(expressions, same_group, &mut parts);
+    parts.sort();
+    parts.dedup();
+
+    if parts.len() == 1 {
+        return parts.remove(0);
+    }
+
+    format!("{}({})", name, parts.join(";"))
+}
+
+/// Раскрывает вложенные группы того же вида: and(a, and(b, c)) это and(a, b, c)
+fn collect_group<F>(expressions: &[FilterExpression], same_group: F, parts: &mut Vec<String>)
+    where F: Fn(&FilterExpression) -> Option<&Vec<FilterExpression>> + Copy
+{
+    for expression in expressions {
+        match same_group(expression) {
+            Some(nested) => collect_group(nested, same_group, parts),
+            None => parts.push(expression.canonical()),
+        }
+    }
+}
+
+impl Comparison {
+    fn column(&self) -> Result<Column, ResponseError> {
+        Column::from_name(&self.field)
+            .ok_or((7357561, format!("Не известная колонка в фильтре: {}", self.field)))
+    }
+
+    fn check(&self) -> Result<(), ResponseError> {
+        self.operands().map(|_| ())
+    }
+
+    /// Колонка и значения сравнения, разбираются один раз и сохраняются в [Comparison::operands]
+    fn operands(&self) -> Result<&Operands, ResponseError> {
+        if let Some(operands) = self.operands.get() {
+            return Ok(operands);
+        }
+
+        let operands = self.resolve()?;
+
+        Ok(self.operands.get_or_init(|| operands))
+    }
+
+    fn resolve(&self) -> Result<Operands, ResponseError> {
+        let column = self.column()?;
+        let kind = ValueKind::of(column);
+
+        if self.op == Operator::Contains && kind != ValueKind::Text {
+            return Err((7357562, format!("Оператор contains нельзя применить к колонке {}", self.field)));
+        }
+        if kind == ValueKind::Text && matches!(self.op, Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte) {
+            return Err((7357562, format!("Оператор {} нельзя применить к колонке {}", self.op.name(), self.field)));
+        }
+
+        let values = self.values()?;
+
+        let values = match kind {
+            ValueKind::Money => OperandValues::Money(
+                values.iter()
+                    .map(|value| Money::parse(value)
+                        .map_err(|_| (7357564, format!("Значение {} для колонки {} не является суммой", value, self.field))))
+                    .collect::<Result<Vec<Money>, ResponseError>>()?
+            ),
+            ValueKind::Date => OperandValues::Date(
+                values.iter()
+                    .map(|value| parse_date_time(value)
+                        .ok_or((7357565, format!("Значение {} для колонки {} не является датой", value, self.field))))
+                    .collect::<Result<Vec<NaiveDateTime>, ResponseError>>()?
+            ),
+            ValueKind::Text => OperandValues::Text(values.iter().map(|value| normalize_header(value)).collect()),
+        };
+
+        Ok(Operands { column, values })
+    }
+
+    /// Значения из запроса строками, для [Operator::In] и [Operator::NotIn] их может быть несколько
+    fn values(&self) -> Result<Vec<String>, ResponseError> {
+        let error = || (7357563, format!("Не корректное значение для колонки {} с оператором {}", self.field, self.op.name()));
+
+        let values = match (&self.value, self.op.is_list()) {
+            (Value::Array(values), true) if !values.is_empty() => values.iter().map(scalar).collect::<Option<Vec<String>>>(),
+            (value, false) => scalar(value).map(|value| vec![value]),
+            _ => None,
+        };
+
+        values.ok_or_else(error)
+    }
+
+    fn evaluate(&self, record: &StringRecord, collect_indexing: &IndexingReport) -> Result<bool, ResponseError> {
+        let operands = self.operands()?;
+        let index = collect_indexing
+            .index(operands.column)
+            .ok_or((7357566, format!("Колонка {} из фильтра не найдена в файле", self.field)))?;
+        let field = record.get(index).unwrap_or("");
+
+        match &operands.values {
+            OperandValues::Money(values) => Ok(matches_ordered(self.op, Money::parse(field)?, values)),
+            OperandValues::Date(values) => {
+                let field = parse_date_time(field)
+                    .ok_or((7357567, format!("Не удалось разобрать дату \"{}\" в колонке {}", field, self.field)))?;
+                Ok(matches_ordered(self.op, field, values))
+            }
+            OperandValues::Text(values) => {
+                let field = normalize_header(field);
+
+                Ok(match self.op {
+                    Operator::Contains => field.contains(values[0].as_str()),
+                    _ => matches_ordered(self.op, field, values),
+                })
+            }
+        }
+    }
+
+    fn canonical(&self) -> String {
+        let kind = Column::from_name(&self.field).map_or(ValueKind::Text, ValueKind::of);
+
+        let mut values = self.values().unwrap_or_default()
+            .into_iter()
+            .map(|value| match kind {
+                ValueKind::Money => Money::parse(&value).map_or(value, |money| money.to_string()),
+                ValueKind::Date => parse_date_time(&value)
+                    .map_or(value, |date| date.format("%Y-%m-%d %H:%M:%S").to_string()),
+                ValueKind::Text => normalize_header(&value),
+            })
+            .collect::<Vec<String>>();
+
+        if self.op.is_list() {
+            values.sort();
+            values.dedup();
+            return format!("{}:{}:[{}]", self.field, self.op.name(), values.join(","));
+        }
+
+        format!("{}:{}:{}", self.field, self.op.name(), values.join(","))
+    }
+}
+
+fn matches_ordered<T: Ord>(op: Operator, field: T, values: &[T]) -> bool {
+    match op {
+        Operator::Eq => field == values[0],
+        Operator::Ne => field != values[0],
+        Operator::Gt => field > values[0],
+        Operator::Gte => field >= values[0],
+        Operator::Lt => field < values[0],
+        Operator::Lte => field <= values[0],
+        Operator::In => values.contains(&field),
+        Operator::NotIn => !values.contains(&field),
+        Operator::Contains => false,
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Дата транзакции из csv (2023-01-15 12:30:00), дата без времени это начало дня
+fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date);
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn expression(value: Value) -> FilterExpression {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Колонки: сумма, дата, вендор, merchant_id
+    fn indexing() -> IndexingReport {
+        let mut indexing = IndexingReport::new();
+        indexing.index_amount = Some(0);
+        indexing.index_date = Some(1);
+        indexing.index_vendor = Some(2);
+        indexing.index_merchant_id = Some(3);
+        indexing
+    }
+
+    fn record(amount: &str, date: &str, vendor: &str, merchant_id: &str) -> StringRecord {
+        StringRecord::from(vec![amount, date, vendor, merchant_id])
+    }
+
+    fn evaluate(expression: &FilterExpression, record: &StringRecord) -> bool {
+        expression.evaluate(record, &indexing()).unwrap()
+    }
+
+    #[test]
+    fn evaluate_comparisons_by_column_kind() {
+        let amount = expression(json!({"field": "amount", "op": "gte", "value": "10 000"}));
+        assert!(evaluate(&amount, &record("10000.00", "2023-01-15 12:00:00", "Shop", "101")));
+        assert!(!evaluate(&amount, &record("9999,99", "2023-01-15 12:00:00", "Shop", "101")));
+
+        // Дата без времени это начало дня
+        let date = expression(json!({"field": "date", "op": "lt", "value": "2023-01-15"}));
+        assert!(evaluate(&date, &record("1", "2023-01-14 23:59:59", "Shop", "101")));
+        assert!(!evaluate(&date, &record("1", "2023-01-15 00:00:00", "Shop", "101")));
+
+        // Текст сравнивается без учета регистра и пробелов по краям
+        let vendor = expression(json!({"field": "vendor", "op": "in", "value": ["Test Vendor", "demo"]}));
+        assert!(evaluate(&vendor, &record("1", "2023-01-15", " test vendor ", "101")));
+        assert!(!evaluate(&vendor, &record("1", "2023-01-15", "Shop", "101")));
+
+        let contains = expression(json!({"field": "vendor", "op": "contains", "value": "SHO"}));
+        assert!(evaluate(&contains, &record("1", "2023-01-15", "Big shop", "101")));
+
+        let merchant = expression(json!({"field": "merchant_id", "op": "not_in", "value": [101, 102]}));
+        assert!(!evaluate(&merchant, &record("1", "2023-01-15", "Shop", "102")));
+        assert!(evaluate(&merchant, &record("1", "2023-01-15", "Shop", "103")));
+    }
+
+    #[test]
+    fn evaluate_and_or_not() {
+        let expression = expression(json!({"and": [
+            {"field": "amount", "op": "gt", "value": 100},
+            {"not": {"field": "vendor", "op": "eq", "value": "demo"}},
+            {"or": [
+                {"field": "merchant_id", "op": "eq", "value": 101},
+                {"field": "vendor", "op": "contains", "value": "taxi"}
+            ]}
+        ]}));
+
+        assert!(evaluate(&expression, &record("150", "2023-01-15", "Shop", "101")));
+        assert!(evaluate(&expression, &record("150", "2023-01-15", "Taxi Plus", "200")));
+        assert!(!evaluate(&expression, &record("50", "2023-01-15", "Shop", "101")));
+        assert!(!evaluate(&expression, &record("150", "2023-01-15", "Demo", "101")));
+        assert!(!evaluate(&expression, &record("150", "2023-01-15", "Shop", "200")));
+    }
+
+    #[test]
+    fn evaluate_reports_bad_rows_and_missing_columns() {
+        let date = expression(json!({"field": "date", "op": "gt", "value": "2023-01-01"}));
+        let result = date.evaluate(&record("1", "15.01.2023", "Shop", "101"), &indexing());
+        assert_eq!(result.map_err(|error| error.0), Err(7357567));
+
+        let provider = expression(json!({"field": "provider", "op": "eq", "value": "Taxi"}));
+        let result = provider.evaluate(&record("1", "2023-01-15", "Shop", "101"), &indexing());
+        assert_eq!(result.map_err(|error| error.0), Err(7357566));
+    }
+
+    #[test]
+    fn check_rejects_wrong_columns_operators_and_values() {
+        let cases = [
+            (json!({"field": "color", "op": "eq", "value": "red"}), 7357561),
+            (json!({"field": "amount", "op": "contains", "value": "1"}), 7357562),
+            (json!({"field": "vendor", "op": "gt", "value": "a"}), 7357562),
+            (json!({"field": "vendor", "op": "in", "value": []}), 7357563),
+            (json!({"field": "vendor", "op": "eq", "value": ["a"]}), 7357563),
+            (json!({"field": "amount", "op": "eq", "value": "1.2.3"}), 7357564),
+            (json!({"field": "date", "op": "eq", "value": "15.01.2023"}), 7357565),
+            (json!({"and": []}), 7357560),
+        ];
+
+        for (value, code) in cases {
+            assert_eq!(expression(value.clone()).check().map_err(|error| error.0), Err(code), "{}", value);
+        }
+    }
+
+    #[test]
+    fn check_stores_typed_operands() {
+        let expression = expression(json!({"field": "amount", "op": "in", "value": ["10", "1 000,5"]}));
+        expression.check().unwrap();
+
+        let FilterExpression::Comparison(comparison) = &expression else {
+            panic!("ожидалось сравнение");
+        };
+
+        let operands = comparison.operands.get().unwrap();
+        assert_eq!(operands.column, Column::Amount);
+        assert!(matches!(&operands.values, OperandValues::Money(values) if values == &[Money::parse("10").unwrap(), Money::parse("1000.5").unwrap()]));
+    }
+
+    #[test]
+    fn canonical_flattens_and_sorts_groups() {
+        let a = json!({"field": "amount", "op": "gt", "value": 100});
+        let b = json!({"field": "vendor", "op": "eq", "value": "demo"});
+        let c = json!({"field": "merchant_id", "op": "eq", "value": 101});
+
+        let nested = expression(json!({"and": [a, {"and": [b, c]}]}));
+        let flat = expression(json!({"and": [c, b, a, a]}));
+        assert_eq!(nested.canonical(), flat.canonical());
+        assert_eq!(flat.canonical(), "and(amount:gt:100;merchant_id:eq:101;vendor:eq:demo)");
+
+        let or = expression(json!({"or": [{"or": [b, a]}, c]}));
+        assert_eq!(or.canonical(), "or(amount:gt:100;merchant_id:eq:101;vendor:eq:demo)");
+
+        // and внутри or не раскрывается
+        let mixed = expression(json!({"or": [{"and": [a, b]}, c]}));
+        assert_eq!(mixed.canonical(), "or(and(amount:gt:100;vendor:eq:demo);merchant_id:eq:101)");
+
+        // Группа из одного выражения это само выражение
+        assert_eq!(expression(json!({"and": [a]})).canonical(), "amount:gt:100");
+    }
+
+    #[test]
+    fn canonical_removes_double_not() {
+        let comparison = json!({"field": "vendor", "op": "eq", "value": "demo"});
+
+        assert_eq!(expression(json!({"not": {"not": comparison}})).canonical(), "vendor:eq:demo");
+        assert_eq!(expression(json!({"not": {"not": {"not": comparison}}})).canonical(), "not(vendor:eq:demo)");
+    }
+
+    #[test]
+    fn canonical_sorts_lists_and_normalizes_values() {
+        let merchants = expression(json!({"field": "merchant_id", "op": "in", "value": [102, "101", 101]}));
+        assert_eq!(merchants.canonical(), "merchant_id:in:[101,102]");
+
+        let vendors = expression(json!({"field": "vendor", "op": "not_in", "value": ["Demo ", "Ёлка", "demo"]}));
+        assert_eq!(vendors.canonical(), "vendor:not_in:[demo,елка]");
+
+        for value in [json!("10000"), json!("10 000.00"), json!(10000), json!("10000,0")] {
+            let amount = expression(json!({"field": "amount", "op": "gte", "value": value}));
+            assert_eq!(amount.canonical(), "amount:gte:10000", "{}", value);
+        }
+
+        for value in ["2023-01-15", "2023-01-15 00:00", "2023-01-15T00:00:00"] {
+            let date = expression(json!({"field": "date", "op": "lt", "value": value}));
+            assert_eq!(date.canonical(), "date:lt:2023-01-15 00:00:00", "{}", value);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use rust_xlsxwriter::Workbook;
+
+use crate::helper::report_template::render::{render_template, TemplateContext};
+use crate::helper::report_template::ReportTemplate;
+use crate::indexing_report_struct::IndexingReport;
+use crate::r#trait::filter_report::ReportItemType;
+use crate::r#type::types::{RecordStrings, ResponseError};
+use crate::share::ReportItem;
+
+/// [Organization] Организация по которой строится отчет.
+/// От неё зависит какой id передается в запросе и по какой колонке csv фильтруются транзакции
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Organization {
+    /// provider_id в запросе, колонка [IndexingReport::index_provider_id]
+    Provider,
+    /// merchant_id в запросе, колонка [IndexingReport::index_merchant_id]
+    Merchant,
+}
+
+/// [Report kind] Вид отчета. Всё чем один тип отчета отличается от другого описывается здесь,
+/// остальной код работает с типом отчета только через этот трейт.
+/// Чтобы добавить новый вид отчета, достаточно реализовать трейт и зарегистрировать его в [REPORT_KINDS]
+///
+/// [REPORT_KINDS]: crate::helper::report_type::REPORT_KINDS
+pub trait ReportKind: Send + Sync {
+    /// [Name] Имя вида отчета в поле report_type запроса, регистр не учитывается
+    fn name(&self) -> &'static str;
+
+    /// [Slug] Имя вида отчета в json данных отчета, оно же имя файла шаблона xlsx
+    fn slug(&self) -> &'static str;
+
+    /// [Key part] Имя вида отчета в ключе отчета и в имени файла.
+    /// Менять нельзя, иначе уже сгенерированные отчеты перестанут находиться
+    fn key_part(&self) -> &'static str {
+        self.slug()
+    }
+
+    /// [Item types] Части отчета которые можно собрать для этого вида отчета
+    fn item_types(&self) -> &'static [ReportItemType];
+
+    /// [File types] Типы файлов (pay, c2card ...) из которых можно собрать отчет, берутся из [ReportKind::item_types]
+    fn file_types(&self) -> Vec<&'static str> {
+        self.item_types()
+            .iter()
+            .flat_map(|item_type| item_type.file_types().iter().copied())
+            .collect()
+    }
+
+    /// [Organization] По какой организации строится отчет
+    fn organization(&self) -> Organization;
+
+    /// [Required item] Часть отчета без которой отчет не собрать, по ней же строятся сводные листы
+    fn required_item(&self) -> ReportItemType;
+
+    /// Ошибка когда в отчете нет [ReportKind::required_item]
+    fn missing_data_error(&self) -> ResponseError;
+
+    /// Есть ли у отчета сводные листы и лист с пополнениями счета
+    fn has_summary_sheets(&self) -> bool {
+        true
+    }
+
+    /// Нужны ли отчету пополнения счета организации
+    fn needs_refill(&self) -> bool {
+        false
+    }
+
+    /// [Reconciliation item] Часть отчета с которой сверяются пополнения счета: на неё тратятся деньги со счета.
+    /// По умолчанию это [ReportKind::required_item], у агента платежи, у таксопарка переводы водителям
+    fn reconciliation_item(&self) -> ReportItemType {
+        self.required_item()
+    }
+
+    /// Учитывается ли в отчете абонентская плата (monthly_subscription_fee)
+    fn uses_subscription_fee(&self) -> bool {
+        false
+    }
+
+    /// Встроенный шаблон xlsx отчета
+    fn default_template(&self) -> &'static str;
+
+    /// Индекс колонки с id организации в csv файле
+    fn organization_id_index(&self, collect_indexing: &IndexingReport) -> Result<usize, ResponseError> {
+        match self.organization() {
+            Organization::Provider => collect_indexing.index_provider_id
+                .ok_or((3443242, "index_Provider не был найден".to_string())),
+            Organization::Merchant => collect_indexing.index_merchant_id
+                .ok_or((3443244, "index_merchant_id не был найден".to_string())),
+        }
+    }
+
+    /// Агрегации которые нужны только этому виду отчета, считаются по одному чанку.
+    /// Общие агрегации (суммы, дни, сводная по провайдеру) уже посчитаны в [ReportItem::from_chunk]
+    fn aggregate(
+        &self,
+        _report_item: &mut ReportItem,
+        _records: &Vec<RecordStrings>,
+        _collect_indexing: &IndexingReport,
+        _item_type: &ReportItemType,
+    ) -> Result<(), ResponseError> {
+        Ok(())
+    }
+
+    /// Проверяет что в отчете есть [ReportKind::required_item] и возвращает часть отчета для сводных листов
+    fn summary_item<'a>(
+        &self,
+        required_item: Option<&'a ReportItem>,
+    ) -> Result<Option<&'a ReportItem>, ResponseError> {
+        match required_item {
+            None => Err(self.missing_data_error()),
+            Some(item) if self.has_summary_sheets() => Ok(Some(item)),
+            Some(_) => Ok(None),
+        }
+    }
+
+    /// Строки листа "Общий отчет о платежах" для csv и pdf, первая строка это описание колонок
+    fn general_report_rows(&self, context: &TemplateContext) -> Vec<RecordStrings>;
+
+    /// Шапка pdf отчета: левая и правая колонки
+    fn pdf_header(&self, context: &TemplateContext) -> Result<(Vec<String>, Vec<String>), ResponseError>;
+
+    /// Заполняет xlsx книгу листами отчета по шаблону
+    fn render_xlsx(
+        &self,
+        workbook: &mut Workbook,
+        context: &TemplateContext,
+        templates_dir: &str,
+    ) -> Result<(), ResponseError> {
+        self.summary_item(context.report_item(&self.required_item()))?;
+
+        let template = ReportTemplate::load(self, templates_dir)?;
+
+        render_template(workbook, &template, context)
+    }
+}
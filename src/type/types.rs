@@ -1,37 +1,25 @@
 use actix_web::web::Data;
+use mysql_async::Pool;
 use tokio::sync::RwLock;
+use crate::db::file_repository::FileRecord;
 use crate::server::tokens_storage::TokensStorage;
 use crate::share::Share;
 
 /// Массив со строками из csv файла
 pub type RecordStrings = Vec<String>;
-/// Информация о файле по которому будет генерироваться отчет.
+/// Информация о файлах по которым будет генерироваться отчет.
+/// С базы данных, вместе с ошибками по отдельным файлам.
+pub type InformationAboutFileMicroApiDBResult = Vec<Result<FileRecord, ResponseError>>;
+/// Информация о файлах по которым будет генерироваться отчет.
 /// С базы данных.
-/// 1. id Файла
-/// 2. Путь до файла
-/// 3. Сегмент
-/// 4. Статус Файла
-/// 5. Отчет за период "От"
-/// 6. Отчет за период "До"
-/// 7. User_id
-pub type InformationAboutFileMicroApiDBResult = Vec<Result<(usize, String, isize, isize, String, String, isize), ResponseError>>;
-/// Информация о файле по которому будет генерироваться отчет.
-/// С базы данных.
-/// 1. id Файла
-/// 2. Путь до файла
-/// 3. Тип файла
-/// 4. Статус Файла
-/// 5. Отчет за период "От"
-/// 6. Отчет за период "До"
-/// 7. User_id
-pub type InformationAboutFileMicroApiDB = Vec<(usize, String, isize, isize, String, String, isize)>;
+pub type InformationAboutFileMicroApiDB = Vec<FileRecord>;
 /// Тип ошибки для ответа пользователю
 pub type ResponseError = (i32, String);
 /// Дата from, to отчетов по которым геерируется отчет
 pub type ReportsDateRange = Vec<(String, String)>;
-/// Сборник чанков
-pub type ChunksInReport = Vec<Vec<Vec<String>>>;
 /// Хранилище отчетов для формулирования отчетов Share
 pub type ReportsStorage = Data<RwLock<Share>>;
+/// Пул подключений к базе данных
+pub type DatabasePool = Data<Pool>;
 /// Token Storage
 pub type TokensStorageT = Data<RwLock<TokensStorage>>;